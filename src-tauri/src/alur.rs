@@ -0,0 +1,776 @@
+// Alur kontrol di dalam macro: blok Repeat dan If/Else, Wait, Set variabel, Break dan Continue.
+// Aksi lain yang bukan input (Script, Type, ClipboardSet, Paste, ClipboardGet, Run, Var, Call, WaitWindow, WaitPixel, WaitImage) juga dibaca di sini supaya semua tipe baris ada di satu tempat.
+// Di file tetap satu aksi per baris seperti event biasa, setiap blok ditutup dengan baris End.
+// Player memainkan pohon Langkah hasil susun(), macro tanpa aksi kontrol hanya menjadi pohon berisi event saja.
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use rdev::{EventType, Key};
+use serde::{Deserialize, Serialize};
+
+use crate::event::{Aksi, DataKey, UpdateKey};
+use crate::jendela::{self, InfoWindow};
+use crate::layar::{TargetLayar, TungguLayar};
+use crate::variabel;
+
+pub const TIPE_ULANG: &str = "Repeat";
+pub const TIPE_TUNGGU: &str = "Wait";
+pub const TIPE_JIKA: &str = "If";
+pub const TIPE_LAINNYA: &str = "Else";
+pub const TIPE_SELESAI: &str = "End";
+pub const TIPE_PUTUS: &str = "Break";
+pub const TIPE_LANJUTKAN: &str = "Continue";
+pub const TIPE_ATUR: &str = "Set";
+pub const TIPE_SKRIP: &str = "Script";
+pub const TIPE_KETIK: &str = "Type";
+pub const TIPE_VARIABEL: &str = "Var";
+pub const TIPE_PANGGIL: &str = "Call";
+pub const TIPE_TUNGGU_WINDOW: &str = "WaitWindow";
+pub const TIPE_TUNGGU_PIKSEL: &str = "WaitPixel";
+pub const TIPE_TUNGGU_GAMBAR: &str = "WaitImage";
+pub const TIPE_ATUR_PAPAN_KLIP: &str = "ClipboardSet";
+pub const TIPE_TEMPEL: &str = "Paste";
+pub const TIPE_BACA_PAPAN_KLIP: &str = "ClipboardGet";
+pub const TIPE_PERINTAH: &str = "Run";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Operator {
+    SamaDengan,
+    TidakSama,
+    Kurang,
+    KurangSama,
+    Lebih,
+    LebihSama
+}
+
+impl FromStr for Operator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "==" => Ok(Operator::SamaDengan),
+            "!=" => Ok(Operator::TidakSama),
+            "<" => Ok(Operator::Kurang),
+            "<=" => Ok(Operator::KurangSama),
+            ">" => Ok(Operator::Lebih),
+            ">=" => Ok(Operator::LebihSama),
+            _ => Err(format!("Operator {} tidak dikenal", s))
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let teks = match self {
+            Operator::SamaDengan => "==",
+            Operator::TidakSama => "!=",
+            Operator::Kurang => "<",
+            Operator::KurangSama => "<=",
+            Operator::Lebih => ">",
+            Operator::LebihSama => ">="
+        };
+        write!(f, "{}", teks)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "jenis", content = "nilai")]
+pub enum JenisKondisi {
+    Variabel { nama: String, operator: Operator, nilai: String },
+    // Tombol sedang ditahan
+    Tombol(Key),
+    // Judul window aktif mengandung teks ini, tanpa membedakan huruf besar kecil
+    JudulWindow(String)
+}
+
+// Di file ditulis sebagai "var hitung < 5", "key ShiftLeft" atau "window Firefox", diawali "!" untuk kebalikannya
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Kondisi {
+    pub jenis: JenisKondisi,
+    #[serde(default)]
+    pub negasi: bool
+}
+
+impl FromStr for Kondisi {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let teks = s.trim();
+        let (negasi, teks) = match teks.strip_prefix('!') {
+            Some(sisa) => (true, sisa.trim_start()),
+            None => (false, teks)
+        };
+        let (jenis, isi) = teks.split_once(char::is_whitespace).map(|(jenis, isi)| (jenis, isi.trim())).unwrap_or((teks, ""));
+        if isi.is_empty() {
+            return Err(format!("Kondisi {} tidak lengkap", s));
+        }
+
+        let jenis = match jenis {
+            "var" => {
+                let bagian: Vec<&str> = isi.splitn(3, char::is_whitespace).collect();
+                match bagian[..] {
+                    [nama, operator, nilai] => JenisKondisi::Variabel { nama: nama.to_string(), operator: operator.parse()?, nilai: nilai.trim().to_string() },
+                    _ => return Err(format!("Kondisi variabel {} harus berbentuk \"var <nama> <operator> <nilai>\"", s))
+                }
+            },
+            "key" => JenisKondisi::Tombol(*UpdateKey::from_str(isi).map_err(|_| format!("Tombol {} tidak dikenal", isi))?.0),
+            "window" => JenisKondisi::JudulWindow(isi.to_string()),
+            _ => return Err(format!("Jenis kondisi {} tidak dikenal", jenis))
+        };
+
+        Ok(Kondisi { jenis, negasi })
+    }
+}
+
+impl fmt::Display for Kondisi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negasi {
+            write!(f, "!")?;
+        }
+        match &self.jenis {
+            JenisKondisi::Variabel { nama, operator, nilai } => write!(f, "var {} {} {}", nama, operator, nilai),
+            JenisKondisi::Tombol(key) => write!(f, "key {}", UpdateKey(key)),
+            JenisKondisi::JudulWindow(pola) => write!(f, "window {}", pola)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SaatHabis {
+    Gagal,
+    Lewati,
+    // Mainkan lagi dari penanda sebelumnya di blok yang sama (atau dari awal blok), lalu tunggu lagi
+    Ulangi
+}
+
+// Batas waktu aksi WaitWindow, WaitPixel dan WaitImage, ditulis "timeout=10000; on_timeout=retry; retries=3"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BatasTunggu {
+    pub batas_ms: u64,
+    pub saat_habis: SaatHabis,
+    pub maks_ulang: u32
+}
+
+impl Default for BatasTunggu {
+    fn default() -> Self {
+        BatasTunggu { batas_ms: 30_000, saat_habis: SaatHabis::Gagal, maks_ulang: 3 }
+    }
+}
+
+impl BatasTunggu {
+    // false kalau kunci bukan timeout, on_timeout atau retries
+    pub fn atur(&mut self, kunci: &str, nilai: &str) -> Result<bool, String> {
+        let angka = || nilai.parse::<u64>().map_err(|_| format!("{} {} bukan angka", kunci, nilai));
+        match kunci {
+            "timeout" => self.batas_ms = angka()?,
+            "on_timeout" => self.saat_habis = match nilai {
+                "fail" => SaatHabis::Gagal,
+                "skip" => SaatHabis::Lewati,
+                "retry" => SaatHabis::Ulangi,
+                _ => return Err(format!("on_timeout {} harus fail, skip atau retry", nilai))
+            },
+            "retries" => self.maks_ulang = angka()? as u32,
+            _ => return Ok(false)
+        }
+        Ok(true)
+    }
+
+    pub fn tulis(&self, bagian: &mut Vec<String>) {
+        bagian.push(format!("timeout={}", self.batas_ms));
+        bagian.push(format!("on_timeout={}", match self.saat_habis {
+            SaatHabis::Gagal => "fail",
+            SaatHabis::Lewati => "skip",
+            SaatHabis::Ulangi => "retry"
+        }));
+        if self.saat_habis == SaatHabis::Ulangi {
+            bagian.push(format!("retries={}", self.maks_ulang));
+        }
+    }
+}
+
+// "a=1; b=2" menjadi pasangan kunci dan nilai
+pub fn pisah_opsi(s: &str) -> Result<Vec<(&str, &str)>, String> {
+    s.split(';').map(str::trim).filter(|bagian| !bagian.is_empty())
+        .map(|bagian| bagian.split_once('=').map(|(kunci, nilai)| (kunci.trim(), nilai.trim())).ok_or(format!("{} harus berbentuk <kunci>=<nilai>", bagian)))
+        .collect()
+}
+
+// Menunggu window dengan judul dan/atau class yang cocok, ditulis "title=Firefox; class=firefox; state=focused; timeout=10000; on_timeout=retry; retries=3"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TungguWindow {
+    pub judul: Option<String>,
+    pub kelas: Option<String>,
+    // false berarti cukup ada, tidak harus sedang aktif
+    pub aktif: bool,
+    pub batas: BatasTunggu
+}
+
+impl Default for TungguWindow {
+    fn default() -> Self {
+        TungguWindow { judul: None, kelas: None, aktif: true, batas: BatasTunggu::default() }
+    }
+}
+
+impl FromStr for TungguWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tunggu = TungguWindow::default();
+        for (kunci, nilai) in pisah_opsi(s)? {
+            match kunci {
+                "title" => tunggu.judul = Some(nilai.to_string()),
+                "class" => tunggu.kelas = Some(nilai.to_string()),
+                "state" => tunggu.aktif = match nilai {
+                    "focused" => true,
+                    "exists" => false,
+                    _ => return Err(format!("state {} harus focused atau exists", nilai))
+                },
+                _ if tunggu.batas.atur(kunci, nilai)? => {},
+                _ => return Err(format!("{} tidak dikenal di WaitWindow", kunci))
+            }
+        }
+
+        if tunggu.judul.is_none() && tunggu.kelas.is_none() {
+            return Err(String::from("WaitWindow butuh title atau class"));
+        }
+        Ok(tunggu)
+    }
+}
+
+impl fmt::Display for TungguWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bagian = Vec::new();
+        if let Some(judul) = &self.judul {
+            bagian.push(format!("title={}", judul));
+        }
+        if let Some(kelas) = &self.kelas {
+            bagian.push(format!("class={}", kelas));
+        }
+        bagian.push(format!("state={}", if self.aktif { "focused" } else { "exists" }));
+        self.batas.tulis(&mut bagian);
+        write!(f, "{}", bagian.join("; "))
+    }
+}
+
+impl TungguWindow {
+    // Cocok kalau judul dan class yang diisi sama-sama mengandung polanya, tanpa membedakan huruf besar kecil
+    pub fn cocok(&self, info: &InfoWindow) -> bool {
+        let mengandung = |teks: &str, pola: &Option<String>| pola.as_ref().is_none_or(|pola| teks.to_lowercase().contains(&pola.to_lowercase()));
+        mengandung(&info.judul, &self.judul) && mengandung(&info.kelas, &self.kelas)
+    }
+
+    pub fn ditemukan(&self) -> bool {
+        if self.aktif {
+            jendela::info_aktif().is_some_and(|info| self.cocok(&info))
+        } else {
+            jendela::daftar_window().iter().any(|info| self.cocok(info))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SaranTungguWindow {
+    // WaitWindow disisipkan sebelum event ini
+    pub index: usize,
+    pub nilai: String
+}
+
+// Klik yang judul window-nya (direkam kalau rekam_judul_window aktif) berbeda dari klik sebelumnya
+// kemungkinan terjadi setelah window baru muncul, jadi disarankan menunggu window itu dulu
+pub fn saran_tunggu_window(data_macro: &[DataKey]) -> Vec<SaranTungguWindow> {
+    let mut saran = Vec::new();
+    let mut judul_sebelumnya: Option<&str> = None;
+    for (index, val) in data_macro.iter().enumerate() {
+        let (Aksi::Input(EventType::ButtonPress(..)), Some(judul)) = (&val.aksi, val.window.as_deref()) else {
+            continue
+        };
+
+        let sudah_ditunggu = index > 0 && matches!(data_macro[index - 1].aksi, Aksi::TungguWindow(..));
+        if judul_sebelumnya.is_some_and(|sebelumnya| sebelumnya != judul) && !judul.is_empty() && !sudah_ditunggu {
+            let tunggu = TungguWindow { judul: Some(judul.to_string()), batas: BatasTunggu { batas_ms: 10_000, ..Default::default() }, ..Default::default() };
+            saran.push(SaranTungguWindow { index, nilai: tunggu.to_string() });
+        }
+        judul_sebelumnya = Some(judul);
+    }
+
+    saran
+}
+
+pub fn apakah_kontrol(aksi: &Aksi) -> bool {
+    !matches!(aksi, Aksi::Input(..) | Aksi::Penanda)
+}
+
+pub fn tipe_aksi(aksi: &Aksi) -> Option<&'static str> {
+    match aksi {
+        Aksi::Ulang(..) => Some(TIPE_ULANG),
+        Aksi::Tunggu { .. } => Some(TIPE_TUNGGU),
+        Aksi::Jika(..) => Some(TIPE_JIKA),
+        Aksi::Lainnya => Some(TIPE_LAINNYA),
+        Aksi::Selesai => Some(TIPE_SELESAI),
+        Aksi::Putus => Some(TIPE_PUTUS),
+        Aksi::Lanjutkan => Some(TIPE_LANJUTKAN),
+        Aksi::Atur { .. } => Some(TIPE_ATUR),
+        Aksi::Skrip(..) => Some(TIPE_SKRIP),
+        Aksi::Ketik(..) => Some(TIPE_KETIK),
+        Aksi::AturPapanKlip(..) => Some(TIPE_ATUR_PAPAN_KLIP),
+        Aksi::Tempel(..) => Some(TIPE_TEMPEL),
+        Aksi::BacaPapanKlip(..) => Some(TIPE_BACA_PAPAN_KLIP),
+        Aksi::Perintah(..) => Some(TIPE_PERINTAH),
+        Aksi::Variabel { .. } => Some(TIPE_VARIABEL),
+        Aksi::Panggil(..) => Some(TIPE_PANGGIL),
+        Aksi::TungguWindow(..) => Some(TIPE_TUNGGU_WINDOW),
+        Aksi::TungguLayar(TungguLayar { target: TargetLayar::Piksel { .. }, .. }) => Some(TIPE_TUNGGU_PIKSEL),
+        Aksi::TungguLayar(TungguLayar { target: TargetLayar::Gambar { .. }, .. }) => Some(TIPE_TUNGGU_GAMBAR),
+        Aksi::Input(..) | Aksi::Penanda => None
+    }
+}
+
+// Isi kolom value untuk aksi kontrol
+pub fn nilai_aksi(aksi: &Aksi) -> String {
+    match aksi {
+        Aksi::Ulang(jumlah) => jumlah.to_string(),
+        Aksi::Tunggu { min_ms, maks_ms } if min_ms == maks_ms => min_ms.to_string(),
+        Aksi::Tunggu { min_ms, maks_ms } => format!("{}-{}", min_ms, maks_ms),
+        Aksi::Jika(kondisi) => kondisi.to_string(),
+        Aksi::Atur { nama, nilai } => format!("{}={}", nama, nilai),
+        Aksi::Skrip(kode) => kode.clone(),
+        Aksi::Ketik(teks) | Aksi::AturPapanKlip(teks) | Aksi::Tempel(teks) | Aksi::BacaPapanKlip(teks) => teks.clone(),
+        Aksi::Variabel { nama, bawaan: Some(bawaan) } => format!("{}={}", nama, bawaan),
+        Aksi::Variabel { nama, bawaan: None } => nama.clone(),
+        Aksi::Panggil(panggilan) => panggilan.to_string(),
+        Aksi::TungguWindow(tunggu) => tunggu.to_string(),
+        Aksi::TungguLayar(tunggu) => tunggu.to_string(),
+        Aksi::Perintah(perintah) => perintah.to_string(),
+        _ => String::new()
+    }
+}
+
+fn parse_tunggu(nilai: &str) -> Result<Aksi, String> {
+    let angka = |teks: &str| teks.trim().parse::<u64>().map_err(|_| format!("Lama tunggu {} bukan angka milidetik", nilai));
+    let (min_ms, maks_ms) = match nilai.split_once('-') {
+        Some((min, maks)) => (angka(min)?, angka(maks)?),
+        None => (angka(nilai)?, angka(nilai)?)
+    };
+    if min_ms > maks_ms {
+        return Err(format!("Lama tunggu {} terbalik", nilai));
+    }
+
+    Ok(Aksi::Tunggu { min_ms, maks_ms })
+}
+
+// Ok(None) kalau tipe bukan aksi kontrol
+pub fn parse_aksi(tipe: &str, nilai: &str) -> Result<Option<Aksi>, String> {
+    let aksi = match tipe {
+        TIPE_ULANG => Aksi::Ulang(nilai.trim().parse::<u32>().map_err(|_| format!("Jumlah ulang {} bukan angka", nilai))?),
+        TIPE_TUNGGU => parse_tunggu(nilai)?,
+        TIPE_JIKA => Aksi::Jika(nilai.parse()?),
+        TIPE_LAINNYA => Aksi::Lainnya,
+        TIPE_SELESAI => Aksi::Selesai,
+        TIPE_PUTUS => Aksi::Putus,
+        TIPE_LANJUTKAN => Aksi::Lanjutkan,
+        TIPE_ATUR => match nilai.split_once('=') {
+            Some((nama, isi)) if !nama.trim().is_empty() => {
+                variabel::periksa(isi)?;
+                Aksi::Atur { nama: nama.trim().to_string(), nilai: isi.to_string() }
+            },
+            _ => return Err(format!("Set {} harus berbentuk <nama>=<nilai>", nilai))
+        },
+        TIPE_SKRIP if nilai.trim().is_empty() => return Err(String::from("Script tidak boleh kosong")),
+        TIPE_SKRIP => Aksi::Skrip(nilai.to_string()),
+        TIPE_KETIK => {
+            variabel::periksa(nilai)?;
+            Aksi::Ketik(nilai.to_string())
+        },
+        TIPE_ATUR_PAPAN_KLIP => {
+            variabel::periksa(nilai)?;
+            Aksi::AturPapanKlip(nilai.to_string())
+        },
+        TIPE_TEMPEL => {
+            variabel::periksa(nilai)?;
+            Aksi::Tempel(nilai.to_string())
+        },
+        TIPE_BACA_PAPAN_KLIP if variabel::apakah_nama_valid(nilai.trim()) => Aksi::BacaPapanKlip(nilai.trim().to_string()),
+        TIPE_BACA_PAPAN_KLIP => return Err(format!("ClipboardGet butuh nama variabel, bukan {}", nilai)),
+        TIPE_PERINTAH => Aksi::Perintah(nilai.parse()?),
+        TIPE_VARIABEL => match nilai.split_once('=') {
+            Some(..) => {
+                let (nama, bawaan) = variabel::parse_pasangan(nilai)?;
+                Aksi::Variabel { nama, bawaan: Some(bawaan) }
+            },
+            None if variabel::apakah_nama_valid(nilai.trim()) => Aksi::Variabel { nama: nilai.trim().to_string(), bawaan: None },
+            None => return Err(format!("Nama variabel {} tidak valid", nilai))
+        },
+        TIPE_PANGGIL => Aksi::Panggil(nilai.parse()?),
+        TIPE_TUNGGU_WINDOW => Aksi::TungguWindow(nilai.parse()?),
+        TIPE_TUNGGU_PIKSEL => Aksi::TungguLayar(TungguLayar::parse_piksel(nilai)?),
+        TIPE_TUNGGU_GAMBAR => Aksi::TungguLayar(TungguLayar::parse_gambar(nilai)?),
+        _ => return Ok(None)
+    };
+
+    Ok(Some(aksi))
+}
+
+#[derive(Debug, Clone)]
+pub enum Langkah {
+    // Event input, penanda, Wait, WaitWindow, WaitPixel, WaitImage, Set, Script, Type, ClipboardSet, Paste, ClipboardGet, Run, Var, Call, Break dan Continue. Isinya index ke data_macro
+    Aksi(usize),
+    // jumlah 0 berarti diulang terus sampai Break atau playback dihentikan
+    Ulang { index: usize, jumlah: u32, isi: Vec<Langkah>, akhir: usize },
+    Jika { index: usize, kondisi: Kondisi, isi: Vec<Langkah>, lainnya: Option<(usize, Vec<Langkah>)>, akhir: usize }
+}
+
+impl Langkah {
+    pub fn index(&self) -> usize {
+        match self {
+            Langkah::Aksi(index) | Langkah::Ulang { index, .. } | Langkah::Jika { index, .. } => *index
+        }
+    }
+}
+
+enum Blok {
+    Ulang { index: usize, jumlah: u32 },
+    Jika { index: usize, kondisi: Kondisi, isi: Option<Vec<Langkah>>, lainnya: Option<usize> }
+}
+
+// Menyusun event [mulai, akhir) menjadi pohon, gagal kalau ada blok yang tidak berpasangan
+pub fn susun(data_macro: &[DataKey], mulai: usize, akhir: usize) -> Result<Vec<Langkah>, String> {
+    let mut tumpukan: Vec<(Blok, Vec<Langkah>)> = Vec::new();
+    let mut hasil: Vec<Langkah> = Vec::new();
+    let header = variabel::panjang_header(data_macro);
+
+    for (index, val) in data_macro.iter().enumerate().take(akhir).skip(mulai) {
+        let dalam_ulang = tumpukan.iter().any(|(blok, _)| matches!(blok, Blok::Ulang { .. }));
+
+        match &val.aksi {
+            Aksi::Ulang(jumlah) => tumpukan.push((Blok::Ulang { index, jumlah: *jumlah }, Vec::new())),
+            Aksi::Jika(kondisi) => tumpukan.push((Blok::Jika { index, kondisi: kondisi.clone(), isi: None, lainnya: None }, Vec::new())),
+            Aksi::Lainnya => match tumpukan.last_mut() {
+                Some((Blok::Jika { isi, lainnya: lainnya @ None, .. }, isi_sekarang)) => {
+                    *isi = Some(std::mem::take(isi_sekarang));
+                    *lainnya = Some(index);
+                },
+                _ => return Err(format!("Else di event {} tidak berada di dalam If", index))
+            },
+            Aksi::Selesai => {
+                let (blok, isi_blok) = tumpukan.pop().ok_or(format!("End di event {} tidak menutup blok apa pun", index))?;
+                let langkah = match blok {
+                    Blok::Ulang { index: awal, jumlah } => Langkah::Ulang { index: awal, jumlah, isi: isi_blok, akhir: index },
+                    Blok::Jika { index: awal, kondisi, isi: None, .. } => Langkah::Jika { index: awal, kondisi, isi: isi_blok, lainnya: None, akhir: index },
+                    Blok::Jika { index: awal, kondisi, isi: Some(isi), lainnya } => Langkah::Jika { index: awal, kondisi, isi, lainnya: lainnya.map(|lainnya| (lainnya, isi_blok)), akhir: index }
+                };
+                match tumpukan.last_mut() {
+                    Some((_, isi)) => isi.push(langkah),
+                    None => hasil.push(langkah)
+                }
+            },
+            Aksi::Variabel { .. } if index >= header => return Err(format!("Var di event {} harus berada di awal macro", index)),
+            Aksi::Putus | Aksi::Lanjutkan if !dalam_ulang => return Err(format!("{} di event {} berada di luar Repeat", val.tipe(), index)),
+            _ => match tumpukan.last_mut() {
+                Some((_, isi)) => isi.push(Langkah::Aksi(index)),
+                None => hasil.push(Langkah::Aksi(index))
+            }
+        }
+    }
+
+    if let Some((blok, _)) = tumpukan.last() {
+        let (tipe, index) = match blok {
+            Blok::Ulang { index, .. } => (TIPE_ULANG, index),
+            Blok::Jika { index, .. } => (TIPE_JIKA, index)
+        };
+        return Err(format!("{} di event {} tidak ditutup dengan End", tipe, index));
+    }
+
+    Ok(hasil)
+}
+
+// Index End yang menutup blok Repeat/If di index
+pub fn akhir_blok(data_macro: &[DataKey], index: usize) -> Option<usize> {
+    let mut kedalaman = 0;
+    for (posisi, val) in data_macro.iter().enumerate().skip(index) {
+        match val.aksi {
+            Aksi::Ulang(..) | Aksi::Jika(..) => kedalaman += 1,
+            Aksi::Selesai => {
+                kedalaman -= 1;
+                if kedalaman == 0 {
+                    return Some(posisi);
+                }
+            },
+            _ => {}
+        }
+        if kedalaman == 0 {
+            return None;
+        }
+    }
+
+    None
+}
+
+pub fn lama_tunggu(min_ms: u64, maks_ms: u64) -> Duration {
+    let ms = if min_ms >= maks_ms { min_ms } else { rand::thread_rng().gen_range(min_ms..=maks_ms) };
+    Duration::from_millis(ms)
+}
+
+// Tombol yang sedang ditahan menurut listener, termasuk input yang dikirim player sendiri
+static TOMBOL_DITEKAN: Lazy<Mutex<Vec<Key>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+// Dipanggil listener untuk setiap event, supaya kondisi "key" bisa diperiksa
+pub fn catat_tombol(event_type: &EventType) {
+    let mut ditekan = TOMBOL_DITEKAN.lock().unwrap();
+    match *event_type {
+        EventType::KeyPress(key) if !ditekan.contains(&key) => ditekan.push(key),
+        EventType::KeyRelease(key) => ditekan.retain(|val| *val != key),
+        _ => {}
+    }
+}
+
+pub fn apakah_ditekan(key: Key) -> bool {
+    TOMBOL_DITEKAN.lock().unwrap().contains(&key)
+}
+
+// Angka dibandingkan sebagai angka, selain itu sebagai teks. Variabel yang belum di-set dianggap kosong
+fn bandingkan(kiri: &str, operator: Operator, kanan: &str) -> bool {
+    let urutan = match (kiri.trim().parse::<f64>(), kanan.trim().parse::<f64>()) {
+        (Ok(kiri), Ok(kanan)) => kiri.partial_cmp(&kanan),
+        _ => Some(kiri.cmp(kanan))
+    };
+    let Some(urutan) = urutan else {
+        return false;
+    };
+
+    match operator {
+        Operator::SamaDengan => urutan.is_eq(),
+        Operator::TidakSama => urutan.is_ne(),
+        Operator::Kurang => urutan.is_lt(),
+        Operator::KurangSama => urutan.is_le(),
+        Operator::Lebih => urutan.is_gt(),
+        Operator::LebihSama => urutan.is_ge()
+    }
+}
+
+pub fn evaluasi(kondisi: &Kondisi, variabel: &HashMap<String, String>) -> bool {
+    let hasil = match &kondisi.jenis {
+        JenisKondisi::Variabel { nama, operator, nilai } => bandingkan(variabel.get(nama).map(String::as_str).unwrap_or(""), *operator, nilai),
+        JenisKondisi::Tombol(key) => apakah_ditekan(*key),
+        JenisKondisi::JudulWindow(pola) => jendela::judul_aktif().is_some_and(|judul| judul.to_lowercase().contains(&pola.to_lowercase()))
+    };
+
+    hasil != kondisi.negasi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format;
+
+    fn susun_teks(teks: &str) -> Result<Vec<Langkah>, String> {
+        let data_macro = format::parse_macro(teks);
+        susun(&data_macro, 0, data_macro.len())
+    }
+
+    // Pohon ditulis ringkas: index untuk aksi, R<index>x<jumlah>[isi]<end> untuk Repeat, I<index>[isi|<else>:isi]<end> untuk If
+    fn bentuk(langkah: &[Langkah]) -> String {
+        langkah.iter().map(|val| match val {
+            Langkah::Aksi(index) => index.to_string(),
+            Langkah::Ulang { index, jumlah, isi, akhir } => format!("R{}x{}[{}]{}", index, jumlah, bentuk(isi), akhir),
+            Langkah::Jika { index, isi, lainnya: None, akhir, .. } => format!("I{}[{}]{}", index, bentuk(isi), akhir),
+            Langkah::Jika { index, isi, lainnya: Some((lainnya, isi_lainnya)), akhir, .. } => format!("I{}[{}|{}:{}]{}", index, bentuk(isi), lainnya, bentuk(isi_lainnya), akhir)
+        }).collect::<Vec<String>>().join(" ")
+    }
+
+    #[test]
+    fn susun_blok_bersarang() {
+        let teks = "Repeat|||2|||0\n\
+                    Set|||a=1|||0\n\
+                    If|||var a == 1|||0\n\
+                    Repeat|||0|||0\n\
+                    Break||||||0\n\
+                    End||||||0\n\
+                    Else||||||0\n\
+                    Continue||||||0\n\
+                    End||||||0\n\
+                    End||||||0\n\
+                    Set|||b=2|||0";
+        assert_eq!(bentuk(&susun_teks(teks).unwrap()), "R0x2[1 I2[R3x0[4]5|6:7]8]9 10");
+    }
+
+    #[test]
+    fn susun_tanpa_kontrol() {
+        let teks = "Key Press|||ShiftLeft|||0\nMarker|||awal|||0.1\nKey Release|||ShiftLeft|||0.2";
+        assert_eq!(bentuk(&susun_teks(teks).unwrap()), "0 1 2");
+    }
+
+    #[test]
+    fn susun_sebagian() {
+        let data_macro = format::parse_macro("Set|||a=1|||0\nRepeat|||3|||0\nSet|||b=1|||0\nEnd||||||0");
+        assert_eq!(bentuk(&susun(&data_macro, 1, 4).unwrap()), "R1x3[2]3");
+        assert!(susun(&data_macro, 0, 3).is_err());
+    }
+
+    #[test]
+    fn susun_blok_tidak_berpasangan() {
+        assert_eq!(susun_teks("End||||||0").unwrap_err(), "End di event 0 tidak menutup blok apa pun");
+        assert_eq!(susun_teks("Repeat|||2|||0\nEnd||||||0\nEnd||||||0").unwrap_err(), "End di event 2 tidak menutup blok apa pun");
+        assert_eq!(susun_teks("Repeat|||2|||0\nSet|||a=1|||0").unwrap_err(), "Repeat di event 0 tidak ditutup dengan End");
+        // Yang dilaporkan blok terdalam yang belum ditutup
+        assert_eq!(susun_teks("Repeat|||2|||0\nIf|||var a == 1|||0\nEnd||||||0\nIf|||var a == 2|||0").unwrap_err(), "If di event 3 tidak ditutup dengan End");
+    }
+
+    #[test]
+    fn susun_else_salah_tempat() {
+        assert_eq!(susun_teks("Else||||||0").unwrap_err(), "Else di event 0 tidak berada di dalam If");
+        assert_eq!(susun_teks("If|||var a == 1|||0\nElse||||||0\nElse||||||0\nEnd||||||0").unwrap_err(), "Else di event 2 tidak berada di dalam If");
+        assert_eq!(susun_teks("If|||var a == 1|||0\nRepeat|||2|||0\nElse||||||0\nEnd||||||0\nEnd||||||0").unwrap_err(), "Else di event 2 tidak berada di dalam If");
+    }
+
+    #[test]
+    fn susun_break_continue_di_luar_repeat() {
+        assert_eq!(susun_teks("Break||||||0").unwrap_err(), "Break di event 0 berada di luar Repeat");
+        assert_eq!(susun_teks("If|||var a == 1|||0\nContinue||||||0\nEnd||||||0").unwrap_err(), "Continue di event 1 berada di luar Repeat");
+        assert_eq!(susun_teks("Repeat|||0|||0\nEnd||||||0\nBreak||||||0").unwrap_err(), "Break di event 2 berada di luar Repeat");
+        // Di dalam If yang berada di dalam Repeat boleh
+        assert_eq!(bentuk(&susun_teks("Repeat|||0|||0\nIf|||var a == 1|||0\nBreak||||||0\nEnd||||||0\nEnd||||||0").unwrap()), "R0x0[I1[2]3]4");
+    }
+
+    #[test]
+    fn susun_var_hanya_di_header() {
+        assert!(susun_teks("Var|||nama|||0\nVar|||kota=Bandung|||0\nType|||{nama}|||0").is_ok());
+        assert_eq!(susun_teks("Type|||halo|||0\nVar|||nama|||0").unwrap_err(), "Var di event 1 harus berada di awal macro");
+    }
+
+    #[test]
+    fn akhir_blok_bersarang() {
+        let data_macro = format::parse_macro("Repeat|||2|||0\nIf|||var a == 1|||0\nEnd||||||0\nEnd||||||0\nSet|||a=1|||0");
+        assert_eq!(akhir_blok(&data_macro, 0), Some(3));
+        assert_eq!(akhir_blok(&data_macro, 1), Some(2));
+        assert_eq!(akhir_blok(&data_macro, 4), None);
+        assert_eq!(akhir_blok(&data_macro[..3], 0), None);
+    }
+
+    #[test]
+    fn parse_aksi_kontrol() {
+        assert!(matches!(parse_aksi("Repeat", "3"), Ok(Some(Aksi::Ulang(3)))));
+        assert!(parse_aksi("Repeat", "tiga").is_err());
+        assert!(matches!(parse_aksi("Wait", "500"), Ok(Some(Aksi::Tunggu { min_ms: 500, maks_ms: 500 }))));
+        assert!(matches!(parse_aksi("Wait", "200-800"), Ok(Some(Aksi::Tunggu { min_ms: 200, maks_ms: 800 }))));
+        assert!(parse_aksi("Wait", "800-200").is_err());
+        assert!(matches!(parse_aksi("Set", "hitung={iteration}"), Ok(Some(Aksi::Atur { nama, nilai })) if nama == "hitung" && nilai == "{iteration}"));
+        assert!(parse_aksi("Set", "=1").is_err());
+        assert!(parse_aksi("Script", "  ").is_err());
+        assert!(matches!(parse_aksi("Key Press", "ShiftLeft"), Ok(None)));
+    }
+
+    #[test]
+    fn nilai_aksi_bolak_balik() {
+        for (tipe, nilai) in [("Repeat", "0"), ("Wait", "250"), ("Wait", "200-800"), ("If", "!var hitung >= 5"), ("Set", "a=1"), ("Var", "kota=Bandung"), ("Var", "nama")] {
+            let aksi = parse_aksi(tipe, nilai).unwrap().unwrap();
+            assert_eq!(tipe_aksi(&aksi), Some(tipe));
+            assert_eq!(nilai_aksi(&aksi), nilai);
+        }
+    }
+
+    #[test]
+    fn parse_aksi_papan_klip() {
+        assert!(matches!(parse_aksi("ClipboardSet", "Dear {name},"), Ok(Some(Aksi::AturPapanKlip(teks))) if teks == "Dear {name},"));
+        assert!(matches!(parse_aksi("Paste", " {address} "), Ok(Some(Aksi::Tempel(teks))) if teks == " {address} "));
+        assert!(matches!(parse_aksi("ClipboardGet", " salinan "), Ok(Some(Aksi::BacaPapanKlip(nama))) if nama == "salinan"));
+        assert!(parse_aksi("ClipboardSet", "Dear {name").is_err());
+        assert!(parse_aksi("Paste", "{alamat rumah}").is_err());
+        for nama in ["", "nama lengkap", "{salinan}"] {
+            assert!(parse_aksi("ClipboardGet", nama).is_err(), "{}", nama);
+        }
+
+        for (tipe, nilai) in [("ClipboardSet", "Halo {{dunia}}"), ("Paste", "{alamat}"), ("ClipboardGet", "salinan")] {
+            let aksi = parse_aksi(tipe, nilai).unwrap().unwrap();
+            assert_eq!(tipe_aksi(&aksi), Some(tipe));
+            assert_eq!(nilai_aksi(&aksi), nilai);
+        }
+    }
+
+    #[test]
+    fn kondisi_parse_dan_tulis() {
+        let kondisi: Kondisi = "var hitung < 5".parse().unwrap();
+        assert_eq!(kondisi, Kondisi { jenis: JenisKondisi::Variabel { nama: String::from("hitung"), operator: Operator::Kurang, nilai: String::from("5") }, negasi: false });
+        let kondisi: Kondisi = "! key ShiftLeft".parse().unwrap();
+        assert_eq!(kondisi, Kondisi { jenis: JenisKondisi::Tombol(Key::ShiftLeft), negasi: true });
+        assert_eq!(kondisi.to_string(), "!key ShiftLeft");
+        for teks in ["var nama == Budi Santoso", "window Mozilla Firefox", "!var a != b"] {
+            assert_eq!(teks.parse::<Kondisi>().unwrap().to_string(), teks);
+        }
+
+        assert!("var hitung".parse::<Kondisi>().is_err());
+        assert!("var hitung ~ 5".parse::<Kondisi>().is_err());
+        assert!("key TombolAneh".parse::<Kondisi>().is_err());
+        assert!("cuaca cerah".parse::<Kondisi>().is_err());
+        assert!("window".parse::<Kondisi>().is_err());
+    }
+
+    #[test]
+    fn evaluasi_variabel() {
+        let variabel = HashMap::from([(String::from("hitung"), String::from("10")), (String::from("nama"), String::from("budi"))]);
+        let cek = |teks: &str| evaluasi(&teks.parse().unwrap(), &variabel);
+        // Angka dibandingkan sebagai angka, bukan teks ("10" < "9" kalau teks)
+        assert!(cek("var hitung > 9"));
+        assert!(cek("var hitung == 10.0"));
+        assert!(cek("var nama > ani"));
+        assert!(cek("var nama != Budi"));
+        assert!(cek("!var hitung < 5"));
+        // Variabel yang belum di-set dianggap kosong
+        assert!(cek("var kosong < a"));
+        assert!(!cek("var kosong == 0"));
+    }
+
+    #[test]
+    fn tunggu_window_bolak_balik() {
+        let tunggu: TungguWindow = "title=Firefox; class=firefox; state=exists; timeout=5000; on_timeout=retry; retries=2".parse().unwrap();
+        assert_eq!(tunggu, TungguWindow {
+            judul: Some(String::from("Firefox")),
+            kelas: Some(String::from("firefox")),
+            aktif: false,
+            batas: BatasTunggu { batas_ms: 5000, saat_habis: SaatHabis::Ulangi, maks_ulang: 2 }
+        });
+        assert_eq!(tunggu.to_string(), "title=Firefox; class=firefox; state=exists; timeout=5000; on_timeout=retry; retries=2");
+
+        // Nilai bawaan ikut ditulis supaya barisnya lengkap
+        let tunggu: TungguWindow = "class=gedit".parse().unwrap();
+        assert_eq!(tunggu.to_string(), "class=gedit; state=focused; timeout=30000; on_timeout=fail");
+        assert_eq!(tunggu.to_string().parse::<TungguWindow>(), Ok(tunggu));
+
+        assert_eq!("state=focused; timeout=100".parse::<TungguWindow>(), Err(String::from("WaitWindow butuh title atau class")));
+        assert_eq!("title=Firefox; state=aktif".parse::<TungguWindow>(), Err(String::from("state aktif harus focused atau exists")));
+        assert_eq!("title=Firefox; warna=merah".parse::<TungguWindow>(), Err(String::from("warna tidak dikenal di WaitWindow")));
+        assert!("title=Firefox; timeout=lama".parse::<TungguWindow>().is_err());
+        assert!("title".parse::<TungguWindow>().is_err());
+    }
+
+    #[test]
+    fn tunggu_window_cocok() {
+        let info = InfoWindow { judul: String::from("Invoice - Mozilla Firefox"), kelas: String::from("firefox") };
+        let tunggu = |teks: &str| teks.parse::<TungguWindow>().unwrap();
+        assert!(tunggu("title=invoice").cocok(&info));
+        assert!(tunggu("class=FIREFOX").cocok(&info));
+        assert!(tunggu("title=Mozilla; class=fire").cocok(&info));
+        assert!(!tunggu("title=Mozilla; class=chrome").cocok(&info));
+        assert!(!tunggu("title=Laporan").cocok(&info));
+    }
+
+    #[test]
+    fn saran_saat_judul_berubah() {
+        let mut data_macro = format::parse_macro(concat!(
+            "Button Press Left|||1, 1|||0|||||||||Login\nButton Release Left|||1, 1|||0.1\n",
+            "Button Press Left|||2, 2|||1|||||||||Login\nButton Press Left|||3, 3|||2|||||||||Dashboard\n",
+            "Button Press Left|||4, 4|||3\nButton Press Left|||5, 5|||4|||||||||Dashboard\n",
+            "WaitWindow|||title=Laporan|||5\nButton Press Left|||6, 6|||6|||||||||Laporan"
+        ));
+
+        // Klik tanpa judul dilewati, klik yang sudah didahului WaitWindow tidak disarankan lagi
+        let saran = saran_tunggu_window(&data_macro);
+        assert_eq!(saran.iter().map(|val| (val.index, val.nilai.as_str())).collect::<Vec<_>>(), vec![(3, "title=Dashboard; state=focused; timeout=10000; on_timeout=fail")]);
+
+        data_macro.remove(6);
+        assert_eq!(saran_tunggu_window(&data_macro).iter().map(|val| val.index).collect::<Vec<_>>(), vec![3, 6]);
+    }
+}
@@ -0,0 +1,413 @@
+// CLI tanpa window untuk menjalankan macro dari script (kiosk, mesin lab). Memakai engine yang sama dengan aplikasi Tauri
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use enigo::Enigo;
+use rdev::{listen, EventType};
+use serde::Serialize;
+use macro_recroder::alur;
+use macro_recroder::event::{durasi_macro, Aksi, DataKey};
+use macro_recroder::format::{self, Format};
+use macro_recroder::layar::{self, Gambar, LayarFile, TargetLayar, TungguLayar};
+use macro_recroder::player::{self, HasilPlayback, OpsiPlayback};
+use macro_recroder::sumber_data::OpsiData;
+use macro_recroder::{panggilan, pemulihan, pengaturan, recorder, skrip, variabel, PengirimEvent, APAKAH_LAGI_MAIN, APAKAH_RECORD};
+
+const KELUAR_GAGAL: u8 = 1;
+const KELUAR_PENGGUNAAN: u8 = 2;
+const KELUAR_FILE: u8 = 3;
+const KELUAR_MACRO_TIDAK_VALID: u8 = 4;
+const KELUAR_DIHENTIKAN: u8 = 5;
+
+const BANTUAN: &str = "Penggunaan: recordflow <perintah> [argumen]
+
+Perintah:
+  record -o <file> [--duration <detik>]      Rekam input sampai F6 ditekan atau durasi habis
+  play <file> [--speed <x>] [--loop <n>] [--var <nama>=<nilai>]... [--data <csv> [--results <csv>]] [--screen <png>]
+       [--humanize] [--seed <n>]
+                                              Mainkan macro atau skrip .rhai, --loop 0 mengulang terus. F7 menghentikan playback.
+                                              Variabel yang belum diisi ditanyakan kalau dijalankan di terminal.
+                                              --data memainkan macro sekali per baris CSV, hasilnya ditulis ke --results
+                                              --screen memakai file PNG sebagai layar untuk WaitPixel dan WaitImage
+                                              --humanize menggeser waktu dan posisi klik secara acak dan menggerakkan
+                                              mouse melengkung, --seed membuat acakannya sama di setiap playback
+  convert <masuk> <keluar> [--format <f>]    Ubah format macro (rf, json, rhai), default dari ekstensi file keluar
+  info <file> [--json]                        Tampilkan ringkasan macro
+  validate <file>                             Periksa setiap baris macro
+
+Kode keluar: 0 sukses, 1 gagal, 2 argumen salah, 3 file tidak bisa dibaca/ditulis, 4 macro tidak valid, 5 playback dihentikan";
+
+// Opsi yang butuh nilai setelahnya
+const OPSI_DENGAN_NILAI: [&str; 10] = ["-o", "--output", "--speed", "--loop", "--format", "--duration", "--data", "--results", "--screen", "--seed"];
+
+struct Kesalahan {
+    kode: u8,
+    pesan: String
+}
+
+impl Kesalahan {
+    fn baru(kode: u8, pesan: impl Into<String>) -> Self {
+        Kesalahan { kode, pesan: pesan.into() }
+    }
+}
+
+struct Argumen {
+    posisi: Vec<String>,
+    opsi: HashMap<String, Option<String>>,
+    // --var boleh ditulis berkali-kali
+    variabel: Vec<String>
+}
+
+impl Argumen {
+    fn parse(args: &[String]) -> Result<Argumen, Kesalahan> {
+        let mut posisi = Vec::new();
+        let mut opsi = HashMap::new();
+        let mut variabel = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--var" {
+                variabel.push(iter.next().ok_or(Kesalahan::baru(KELUAR_PENGGUNAAN, "--var butuh nilai"))?.clone());
+            } else if OPSI_DENGAN_NILAI.contains(&arg.as_str()) {
+                let nilai = iter.next().ok_or(Kesalahan::baru(KELUAR_PENGGUNAAN, format!("{} butuh nilai", arg)))?;
+                opsi.insert(arg.clone(), Some(nilai.clone()));
+            } else if arg.starts_with('-') {
+                opsi.insert(arg.clone(), None);
+            } else {
+                posisi.push(arg.clone());
+            }
+        }
+
+        Ok(Argumen { posisi, opsi, variabel })
+    }
+
+    fn posisi(&self, index: usize, nama: &str) -> Result<&str, Kesalahan> {
+        self.posisi.get(index).map(|val| val.as_str()).ok_or(Kesalahan::baru(KELUAR_PENGGUNAAN, format!("{} belum diisi", nama)))
+    }
+
+    fn nilai(&self, nama: &[&str]) -> Option<&str> {
+        nama.iter().find_map(|nama| self.opsi.get(*nama).and_then(|nilai| nilai.as_deref()))
+    }
+
+    fn angka<T: std::str::FromStr>(&self, nama: &str) -> Result<Option<T>, Kesalahan> {
+        match self.nilai(&[nama]) {
+            Some(nilai) => nilai.parse::<T>().map(Some).map_err(|_| Kesalahan::baru(KELUAR_PENGGUNAAN, format!("{} {} bukan angka yang valid", nama, nilai))),
+            None => Ok(None)
+        }
+    }
+}
+
+// Event dari player ditulis ke stderr supaya stdout tetap bersih untuk pipeline
+struct PengirimKonsol;
+impl PengirimEvent for PengirimKonsol {
+    fn kirim_event<S: Serialize + Clone>(&self, nama: &str, payload: S) {
+        if ["ProgresPlayback", "PlaybackPaused", "SkripGagal", "AksiGagal", "HasilBarisData", "DataPlaybackGagal", "LayarCocok"].contains(&nama) {
+            eprintln!("{} {}", nama, serde_json::to_string(&payload).unwrap_or_default());
+        }
+    }
+}
+
+fn baca_macro(path: &str) -> Result<Vec<DataKey>, Kesalahan> {
+    let contents = fs::read_to_string(path).map_err(|e| Kesalahan::baru(KELUAR_FILE, format!("{}: {}", path, e)))?;
+    match format::deteksi_format_file(Path::new(path), &contents) {
+        Format::Rf => Ok(format::parse_macro(&contents)),
+        Format::Json => format::parse_json(&contents).map_err(|e| Kesalahan::baru(KELUAR_MACRO_TIDAK_VALID, format!("{}: {}", path, e))),
+        Format::Rhai => Ok(format::parse_skrip(&contents))
+    }
+}
+
+fn tulis_macro(path: &str, data_macro: &[DataKey], format: Format) -> Result<(), Kesalahan> {
+    format::tulis_macro(Path::new(path), data_macro, format).map_err(|e| Kesalahan::baru(KELUAR_FILE, e))
+}
+
+fn record(argumen: &Argumen) -> Result<(), Kesalahan> {
+    let output = argumen.nilai(&["-o", "--output"]).ok_or(Kesalahan::baru(KELUAR_PENGGUNAAN, "record butuh -o <file>"))?.to_string();
+    let durasi = argumen.angka::<f32>("--duration")?;
+
+    let data_macro: Arc<Mutex<Vec<DataKey>>> = Arc::new(Mutex::new(Vec::new()));
+    let data_macro_listen = data_macro.clone();
+    let (pengirim_selesai, penerima_selesai) = channel::<Result<(), String>>();
+
+    unsafe {
+        APAKAH_RECORD = Some(true);
+    }
+    recorder::mulai_waktu_record();
+    pemulihan::mulai_jurnal();
+    let tombol = pengaturan::baca(|pengaturan| pengaturan.tombol);
+    eprintln!("Merekam ke {}, tekan {:?} untuk berhenti", output, tombol.record);
+
+    thread::spawn(move || {
+        let enigo = Enigo::new();
+        let pengirim_error = pengirim_selesai.clone();
+        if let Err(error) = listen(move |event| {
+            if unsafe { APAKAH_RECORD } != Some(true) {
+                return;
+            }
+
+            match event.event_type {
+                EventType::KeyRelease(key) if key == tombol.record => {
+                    let _ = pengirim_selesai.send(Ok(()));
+                },
+                EventType::KeyPress(key) if key == tombol.record => {},
+                event_type => {
+                    if let Some(data_key) = recorder::rekam_event(event_type, recorder::selisih_waktu(), &enigo) {
+                        pemulihan::catat_event(&data_key);
+                        data_macro_listen.lock().unwrap().push(data_key);
+                    }
+                }
+            }
+        }) {
+            let _ = pengirim_error.send(Err(format!("{:?}", error)));
+        }
+    });
+
+    let hasil = match durasi {
+        Some(durasi) => penerima_selesai.recv_timeout(Duration::from_secs_f32(durasi)).unwrap_or(Ok(())),
+        None => penerima_selesai.recv().unwrap_or(Ok(()))
+    };
+    unsafe {
+        APAKAH_RECORD = Some(false);
+    }
+    pemulihan::selesai_jurnal();
+    hasil.map_err(|e| Kesalahan::baru(KELUAR_GAGAL, format!("Tidak bisa membaca input: {}", e)))?;
+
+    let data_macro = data_macro.lock().unwrap().clone();
+    tulis_macro(&output, &data_macro, Format::dari_path(Path::new(&output)))?;
+    pemulihan::tandai_tersimpan();
+    eprintln!("{} event disimpan", data_macro.len());
+
+    Ok(())
+}
+
+// Variabel dari --var, yang masih kosong ditanyakan lewat stdin kalau ada terminal. Kolom CSV tidak perlu ditanyakan
+fn isi_variabel(argumen: &Argumen, data_macro: &[DataKey], kolom: &[String]) -> Result<HashMap<String, String>, Kesalahan> {
+    let mut nilai = HashMap::new();
+    for pasangan in argumen.variabel.iter() {
+        let (nama, isi) = variabel::parse_pasangan(pasangan).map_err(|e| Kesalahan::baru(KELUAR_PENGGUNAAN, e))?;
+        nilai.insert(nama, isi);
+    }
+
+    let mut terisi = nilai.clone();
+    terisi.extend(kolom.iter().map(|kolom| (kolom.clone(), String::new())));
+    let kurang = variabel::belum_diisi(data_macro, &terisi);
+    if kurang.is_empty() {
+        return Ok(nilai);
+    }
+    if !io::stdin().is_terminal() {
+        let nama: Vec<&str> = kurang.iter().map(|val| val.nama.as_str()).collect();
+        return Err(Kesalahan::baru(KELUAR_PENGGUNAAN, format!("Variabel {} belum diisi, pakai --var <nama>=<nilai>", nama.join(", "))));
+    }
+
+    for deklarasi in kurang {
+        eprint!("{}: ", deklarasi.nama);
+        let mut baris = String::new();
+        io::stdin().read_line(&mut baris).map_err(|e| Kesalahan::baru(KELUAR_GAGAL, e.to_string()))?;
+        nilai.insert(deklarasi.nama, baris.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    Ok(nilai)
+}
+
+fn play(argumen: &Argumen) -> Result<(), Kesalahan> {
+    let path = argumen.posisi(1, "file")?;
+    let data_macro = baca_macro(path)?;
+    if data_macro.is_empty() {
+        return Err(Kesalahan::baru(KELUAR_MACRO_TIDAK_VALID, format!("{} tidak berisi event", path)));
+    }
+
+    let mut opsi = OpsiPlayback::default();
+    if let Some(kecepatan) = argumen.angka::<f32>("--speed")? {
+        opsi.kecepatan = kecepatan;
+    }
+    opsi.jumlah_ulang = argumen.angka::<u32>("--loop")?;
+    // --seed tanpa --humanize tetap mengaktifkan humanize, opsi lainnya dari pengaturan kalau ada
+    let seed = argumen.angka::<u64>("--seed")?;
+    if seed.is_some() || argumen.opsi.contains_key("--humanize") {
+        let mut manusiawi = opsi.manusiawi.take().unwrap_or_default();
+        manusiawi.seed = seed.or(manusiawi.seed);
+        opsi.manusiawi = Some(manusiawi);
+    }
+    opsi.file_macro = Some(path.to_string());
+    opsi.data = argumen.nilai(&["--data"]).map(|file| OpsiData::baru(file.to_string(), argumen.nilai(&["--results"]).map(String::from)));
+    let kolom = match &opsi.data {
+        Some(data) => data.tabel().map_err(|e| Kesalahan::baru(KELUAR_FILE, e))?.kolom.clone(),
+        None => Vec::new()
+    };
+    opsi.variabel = isi_variabel(argumen, &data_macro, &kolom)?;
+    if let Some(file) = argumen.nilai(&["--screen"]) {
+        layar::atur_sumber(Arc::new(LayarFile { path: PathBuf::from(file) }));
+    }
+    player::rentang_playback(&data_macro, &opsi).map_err(|e| Kesalahan::baru(KELUAR_PENGGUNAAN, e))?;
+
+    // Tombol mainkan (F7) menghentikan dan tombol jeda (Pause) menjeda playback, sama seperti di aplikasi
+    let tombol = pengaturan::baca(|pengaturan| pengaturan.tombol);
+    thread::spawn(move || {
+        let _ = listen(move |event| {
+            alur::catat_tombol(&event.event_type);
+            if let EventType::KeyRelease(key) = event.event_type {
+                if key == tombol.mainkan {
+                    unsafe {
+                        APAKAH_LAGI_MAIN = Some(false);
+                    }
+                } else if key == tombol.jeda {
+                    player::atur_jeda(unsafe { player::APAKAH_DIJEDA } != Some(true));
+                }
+            }
+        });
+    });
+
+    unsafe {
+        APAKAH_LAGI_MAIN = Some(true);
+    }
+    match player::mainkan(&PengirimKonsol, &data_macro, &opsi) {
+        HasilPlayback::Selesai => Ok(()),
+        HasilPlayback::Dihentikan => Err(Kesalahan::baru(KELUAR_DIHENTIKAN, "Playback dihentikan")),
+        HasilPlayback::Gagal(pesan) => Err(Kesalahan::baru(KELUAR_GAGAL, pesan))
+    }
+}
+
+fn convert(argumen: &Argumen) -> Result<(), Kesalahan> {
+    let masuk = argumen.posisi(1, "file masuk")?;
+    let keluar = argumen.posisi(2, "file keluar")?;
+    let format = match argumen.nilai(&["--format"]) {
+        Some(format) => format.parse::<Format>().map_err(|e| Kesalahan::baru(KELUAR_PENGGUNAAN, e))?,
+        None => Format::dari_path(Path::new(keluar))
+    };
+
+    let data_macro = baca_macro(masuk)?;
+    tulis_macro(keluar, &data_macro, format)
+}
+
+#[derive(Serialize)]
+struct InfoMacro {
+    file: String,
+    format: String,
+    jumlah_event: usize,
+    durasi: f32,
+    jumlah_per_tipe: HashMap<String, usize>,
+    penanda: Vec<String>
+}
+
+fn info(argumen: &Argumen) -> Result<(), Kesalahan> {
+    let path = argumen.posisi(1, "file")?;
+    let contents = fs::read_to_string(path).map_err(|e| Kesalahan::baru(KELUAR_FILE, format!("{}: {}", path, e)))?;
+    let data_macro = baca_macro(path)?;
+
+    let mut jumlah_per_tipe: HashMap<String, usize> = HashMap::new();
+    for val in data_macro.iter() {
+        // Dikelompokkan tanpa nama tombolnya, misalnya "Key Press" dan "Button Release"
+        let tipe = match val.aksi {
+            Aksi::Input(EventType::KeyPress(..)) => "Key Press",
+            Aksi::Input(EventType::KeyRelease(..)) => "Key Release",
+            Aksi::Input(EventType::ButtonPress(..)) => "Button Press",
+            Aksi::Input(EventType::ButtonRelease(..)) => "Button Release",
+            Aksi::Input(EventType::MouseMove { .. }) => "Mouse Move",
+            Aksi::Input(EventType::Wheel { .. }) => "Mouse Wheel",
+            Aksi::Penanda => "Marker",
+            ref aksi => alur::tipe_aksi(aksi).unwrap_or_default()
+        };
+        *jumlah_per_tipe.entry(tipe.to_string()).or_insert(0) += 1;
+    }
+
+    let info = InfoMacro {
+        file: path.to_string(),
+        format: format!("{:?}", format::deteksi_format_file(Path::new(path), &contents)).to_lowercase(),
+        jumlah_event: data_macro.len(),
+        durasi: durasi_macro(&data_macro),
+        jumlah_per_tipe,
+        penanda: data_macro.iter().filter(|val| matches!(val.aksi, Aksi::Penanda)).map(|val| val.value.clone()).collect()
+    };
+
+    if argumen.opsi.contains_key("--json") {
+        println!("{}", serde_json::to_string_pretty(&info).unwrap());
+        return Ok(());
+    }
+
+    println!("File:    {}", info.file);
+    println!("Format:  {}", info.format);
+    println!("Event:   {}", info.jumlah_event);
+    println!("Durasi:  {:.3} detik", info.durasi);
+    let mut jumlah_per_tipe: Vec<(&String, &usize)> = info.jumlah_per_tipe.iter().collect();
+    jumlah_per_tipe.sort();
+    for (tipe, jumlah) in jumlah_per_tipe {
+        println!("  {:<15} {}", tipe, jumlah);
+    }
+    if !info.penanda.is_empty() {
+        println!("Penanda: {}", info.penanda.join(", "));
+    }
+
+    Ok(())
+}
+
+fn validate(argumen: &Argumen) -> Result<(), Kesalahan> {
+    let path = argumen.posisi(1, "file")?;
+    let contents = fs::read_to_string(path).map_err(|e| Kesalahan::baru(KELUAR_FILE, format!("{}: {}", path, e)))?;
+
+    let mut kesalahan: Vec<(usize, String)> = match format::deteksi_format_file(Path::new(path), &contents) {
+        Format::Rf => format::periksa_macro(&contents),
+        Format::Json => format::parse_json(&contents).err().map(|e| vec![(0, e)]).unwrap_or_default(),
+        Format::Rhai => skrip::periksa(&contents).err().map(|e| vec![(0, e)]).unwrap_or_default()
+    };
+    // Macro yang dipanggil lewat Call juga harus ada dan bisa dibaca
+    if kesalahan.is_empty() {
+        if let Err(e) = baca_macro(path).and_then(|data_macro| panggilan::muat(&data_macro, Some(Path::new(path))).map_err(|e| Kesalahan::baru(KELUAR_MACRO_TIDAK_VALID, e))) {
+            kesalahan.extend(e.pesan.lines().map(|baris| (0, baris.to_string())));
+        }
+    }
+    // Template WaitImage harus ada dan bisa dibaca
+    if kesalahan.is_empty() {
+        for val in baca_macro(path)?.iter() {
+            if let Aksi::TungguLayar(TungguLayar { target: TargetLayar::Gambar { file, .. }, .. }) = &val.aksi {
+                if let Err(e) = Gambar::baca_png(&layar::path_template(file, Some(path))) {
+                    kesalahan.push((0, e));
+                }
+            }
+        }
+    }
+
+    if !kesalahan.is_empty() {
+        for (baris, pesan) in kesalahan.iter() {
+            eprintln!("{}:{}: {}", path, baris, pesan);
+        }
+        return Err(Kesalahan::baru(KELUAR_MACRO_TIDAK_VALID, format!("{} baris tidak valid", kesalahan.len())));
+    }
+
+    println!("{}: valid", path);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let hasil = Argumen::parse(&args).and_then(|argumen| {
+        match argumen.posisi.first().map(|val| val.as_str()) {
+            Some("record") => record(&argumen),
+            Some("play") => play(&argumen),
+            Some("convert") => convert(&argumen),
+            Some("info") => info(&argumen),
+            Some("validate") => validate(&argumen),
+            Some("help") => {
+                println!("{}", BANTUAN);
+                Ok(())
+            },
+            Some(perintah) => Err(Kesalahan::baru(KELUAR_PENGGUNAAN, format!("Perintah {} tidak dikenal\n\n{}", perintah, BANTUAN))),
+            None if argumen.opsi.contains_key("--help") || argumen.opsi.contains_key("-h") => {
+                println!("{}", BANTUAN);
+                Ok(())
+            },
+            None => Err(Kesalahan::baru(KELUAR_PENGGUNAAN, BANTUAN))
+        }
+    });
+
+    match hasil {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(kesalahan) => {
+            eprintln!("{}", kesalahan.pesan);
+            ExitCode::from(kesalahan.kode)
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use std::str::FromStr;
+use std::string::ToString;
+use serde::{Deserialize, Serialize};
+use rdev::{Button, EventType, Key};
+
+use crate::alur::{self, Kondisi, TungguWindow};
+use crate::layar::TungguLayar;
+use crate::perintah::Perintah;
+use crate::panggilan::Panggilan;
+
+pub const TIPE_PENANDA: &str = "Marker";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RawDataKey {
+    pub id: usize,
+    pub tipe: String,
+    pub value: String,
+    pub waktu: f32,
+    pub label: Option<String>,
+    pub komentar: Option<String>,
+    pub window: Option<String>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Aksi {
+    Input(EventType),
+    // Penanda tidak mengirim input apa-apa, hanya titik navigasi di dalam macro. Namanya disimpan di value
+    Penanda,
+    // Aksi kontrol, lihat alur.rs. Parameternya juga ditulis di value
+    Ulang(u32),
+    Tunggu { min_ms: u64, maks_ms: u64 },
+    Jika(Kondisi),
+    Lainnya,
+    Selesai,
+    Putus,
+    Lanjutkan,
+    Atur { nama: String, nilai: String },
+    // Kode Rhai, lihat skrip.rs
+    Skrip(String),
+    // Mengetik teks, {nama} diganti nilai variabel. Lihat variabel.rs
+    Ketik(String),
+    // Mengisi clipboard, lalu untuk Tempel menekan Ctrl+V. Teksnya boleh berisi {nama}, lihat papan_klip.rs
+    AturPapanKlip(String),
+    Tempel(String),
+    // Nama variabel yang diisi dengan isi clipboard
+    BacaPapanKlip(String),
+    // Menjalankan program lain, lihat perintah.rs
+    Perintah(Perintah),
+    // Deklarasi variabel di header macro, tidak melakukan apa-apa waktu dimainkan
+    Variabel { nama: String, bawaan: Option<String> },
+    // Memainkan macro lain, lihat panggilan.rs
+    Panggil(Panggilan),
+    TungguWindow(TungguWindow),
+    // WaitPixel dan WaitImage, lihat layar.rs
+    TungguLayar(TungguLayar)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataKey {
+    pub aksi: Aksi,
+    pub value: String,
+    pub waktu: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub komentar: Option<String>,
+    // Judul window aktif waktu klik direkam, hanya kalau rekam_judul_window aktif
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<String>
+}
+
+impl DataKey {
+    pub fn input(event_type: EventType, value: String, waktu: f32) -> Self {
+        DataKey { aksi: Aksi::Input(event_type), value, waktu, label: None, komentar: None, window: None }
+    }
+
+    // Aksi kontrol dengan value yang sudah sesuai parameternya
+    pub fn kontrol(aksi: Aksi, waktu: f32) -> Self {
+        DataKey { value: alur::nilai_aksi(&aksi), aksi, waktu, label: None, komentar: None, window: None }
+    }
+
+    pub fn tipe(&self) -> String {
+        match self.aksi {
+            Aksi::Input(event_type) => UpdateEventType(event_type).to_string(),
+            Aksi::Penanda => TIPE_PENANDA.to_string(),
+            ref aksi => alur::tipe_aksi(aksi).unwrap_or_default().to_string()
+        }
+    }
+
+    pub fn ke_raw(&self, id: usize) -> RawDataKey {
+        RawDataKey { id, tipe: self.tipe(), value: self.value.clone(), waktu: self.waktu, label: self.label.clone(), komentar: self.komentar.clone(), window: self.window.clone() }
+    }
+}
+
+#[derive(Debug)]
+pub struct UpdateKey<'a>(pub &'a Key);
+impl std::fmt::Display for UpdateKey<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+impl FromStr for UpdateKey<'_> {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Alt" => Ok(UpdateKey(&Key::Alt)),
+            "AltGr" => Ok(UpdateKey(&Key::AltGr)),
+            "Backspace" => Ok(UpdateKey(&Key::Backspace)),
+            "CapsLock" => Ok(UpdateKey(&Key::CapsLock)),
+            "ControlLeft" => Ok(UpdateKey(&Key::ControlLeft)),
+            "ControlRight" => Ok(UpdateKey(&Key::ControlRight)),
+            "Delete" => Ok(UpdateKey(&Key::Delete)),
+            "DownArrow" => Ok(UpdateKey(&Key::DownArrow)),
+            "End" => Ok(UpdateKey(&Key::End)),
+            "Escape" => Ok(UpdateKey(&Key::Escape)),
+            "F1" => Ok(UpdateKey(&Key::F1)),
+            "F10" => Ok(UpdateKey(&Key::F10)),
+            "F11" => Ok(UpdateKey(&Key::F11)),
+            "F12" => Ok(UpdateKey(&Key::F12)),
+            "F2" => Ok(UpdateKey(&Key::F2)),
+            "F3" => Ok(UpdateKey(&Key::F3)),
+            "F4" => Ok(UpdateKey(&Key::F4)),
+            "F5" => Ok(UpdateKey(&Key::F5)),
+            "F6" => Ok(UpdateKey(&Key::F6)),
+            "F7" => Ok(UpdateKey(&Key::F7)),
+            "F8" => Ok(UpdateKey(&Key::F8)),
+            "F9" => Ok(UpdateKey(&Key::F9)),
+            "Home" => Ok(UpdateKey(&Key::Home)),
+            "LeftArrow" => Ok(UpdateKey(&Key::LeftArrow)),
+            "MetaLeft" => Ok(UpdateKey(&Key::MetaLeft)),
+            "MetaRight" => Ok(UpdateKey(&Key::MetaRight)),
+            "PageDown" => Ok(UpdateKey(&Key::PageDown)),
+            "PageUp" => Ok(UpdateKey(&Key::PageUp)),
+            "Return" => Ok(UpdateKey(&Key::Return)),
+            "RightArrow" => Ok(UpdateKey(&Key::RightArrow)),
+            "ShiftLeft" => Ok(UpdateKey(&Key::ShiftLeft)),
+            "ShiftRight" => Ok(UpdateKey(&Key::ShiftRight)),
+            "Space" => Ok(UpdateKey(&Key::Space)),
+            "Tab" => Ok(UpdateKey(&Key::Tab)),
+            "UpArrow" => Ok(UpdateKey(&Key::UpArrow)),
+            "PrintScreen" => Ok(UpdateKey(&Key::PrintScreen)),
+            "ScrollLock" => Ok(UpdateKey(&Key::ScrollLock)),
+            "Pause" => Ok(UpdateKey(&Key::Pause)),
+            "NumLock" => Ok(UpdateKey(&Key::NumLock)),
+            "BackQuote" => Ok(UpdateKey(&Key::BackQuote)),
+            "Num1" => Ok(UpdateKey(&Key::Num1)),
+            "Num2" => Ok(UpdateKey(&Key::Num2)),
+            "Num3" => Ok(UpdateKey(&Key::Num3)),
+            "Num4" => Ok(UpdateKey(&Key::Num4)),
+            "Num5" => Ok(UpdateKey(&Key::Num5)),
+            "Num6" => Ok(UpdateKey(&Key::Num6)),
+            "Num7" => Ok(UpdateKey(&Key::Num7)),
+            "Num8" => Ok(UpdateKey(&Key::Num8)),
+            "Num9" => Ok(UpdateKey(&Key::Num9)),
+            "Num0" => Ok(UpdateKey(&Key::Num0)),
+            "Minus" => Ok(UpdateKey(&Key::Minus)),
+            "Equal" => Ok(UpdateKey(&Key::Equal)),
+            "KeyQ" => Ok(UpdateKey(&Key::KeyQ)),
+            "KeyW" => Ok(UpdateKey(&Key::KeyW)),
+            "KeyE" => Ok(UpdateKey(&Key::KeyE)),
+            "KeyR" => Ok(UpdateKey(&Key::KeyR)),
+            "KeyT" => Ok(UpdateKey(&Key::KeyT)),
+            "KeyY" => Ok(UpdateKey(&Key::KeyY)),
+            "KeyU" => Ok(UpdateKey(&Key::KeyU)),
+            "KeyI" => Ok(UpdateKey(&Key::KeyI)),
+            "KeyO" => Ok(UpdateKey(&Key::KeyO)),
+            "KeyP" => Ok(UpdateKey(&Key::KeyP)),
+            "LeftBracket" => Ok(UpdateKey(&Key::LeftBracket)),
+            "RightBracket" => Ok(UpdateKey(&Key::RightBracket)),
+            "KeyA" => Ok(UpdateKey(&Key::KeyA)),
+            "KeyS" => Ok(UpdateKey(&Key::KeyS)),
+            "KeyD" => Ok(UpdateKey(&Key::KeyD)),
+            "KeyF" => Ok(UpdateKey(&Key::KeyF)),
+            "KeyG" => Ok(UpdateKey(&Key::KeyG)),
+            "KeyH" => Ok(UpdateKey(&Key::KeyH)),
+            "KeyJ" => Ok(UpdateKey(&Key::KeyJ)),
+            "KeyK" => Ok(UpdateKey(&Key::KeyK)),
+            "KeyL" => Ok(UpdateKey(&Key::KeyL)),
+            "SemiColon" => Ok(UpdateKey(&Key::SemiColon)),
+            "Quote" => Ok(UpdateKey(&Key::Quote)),
+            "BackSlash" => Ok(UpdateKey(&Key::BackSlash)),
+            "IntlBackslash" => Ok(UpdateKey(&Key::IntlBackslash)),
+            "KeyZ" => Ok(UpdateKey(&Key::KeyZ)),
+            "KeyX" => Ok(UpdateKey(&Key::KeyX)),
+            "KeyC" => Ok(UpdateKey(&Key::KeyC)),
+            "KeyV" => Ok(UpdateKey(&Key::KeyV)),
+            "KeyB" => Ok(UpdateKey(&Key::KeyB)),
+            "KeyN" => Ok(UpdateKey(&Key::KeyN)),
+            "KeyM" => Ok(UpdateKey(&Key::KeyM)),
+            "Comma" => Ok(UpdateKey(&Key::Comma)),
+            "Dot" => Ok(UpdateKey(&Key::Dot)),
+            "Slash" => Ok(UpdateKey(&Key::Slash)),
+            "Insert" => Ok(UpdateKey(&Key::Insert)),
+            "KpReturn" => Ok(UpdateKey(&Key::KpReturn)),
+            "KpMinus" => Ok(UpdateKey(&Key::KpMinus)),
+            "KpPlus" => Ok(UpdateKey(&Key::KpPlus)),
+            "KpMultiply" => Ok(UpdateKey(&Key::KpMultiply)),
+            "KpDivide" => Ok(UpdateKey(&Key::KpDivide)),
+            "Kp0" => Ok(UpdateKey(&Key::Kp0)),
+            "Kp1" => Ok(UpdateKey(&Key::Kp1)),
+            "Kp2" => Ok(UpdateKey(&Key::Kp2)),
+            "Kp3" => Ok(UpdateKey(&Key::Kp3)),
+            "Kp4" => Ok(UpdateKey(&Key::Kp4)),
+            "Kp5" => Ok(UpdateKey(&Key::Kp5)),
+            "Kp6" => Ok(UpdateKey(&Key::Kp6)),
+            "Kp7" => Ok(UpdateKey(&Key::Kp7)),
+            "Kp8" => Ok(UpdateKey(&Key::Kp8)),
+            "Kp9" => Ok(UpdateKey(&Key::Kp9)),
+            "KpDelete" => Ok(UpdateKey(&Key::KpDelete)),
+            "Function" => Ok(UpdateKey(&Key::Function)),
+            _ => {
+                if s.contains("Unknown") {
+                    let hasil = s.replace("Unknown(", "").replace(")", "").parse::<u32>();
+                    match hasil {
+                        Ok(_n) => {
+                            // Akan diperbaiki nanti
+                            // let n_clone = &Clone::clone(&n);
+                            return Ok(UpdateKey(&Key::Unknown(1)));
+                        },
+                        Err(..) => {
+                            return Err("Unknown Key Value");
+                        }
+                    }
+                }
+
+                Err("Key is not valid!")
+            }
+        }
+    }
+}
+
+pub struct UpdateButton<'a>(pub &'a Button);
+impl std::fmt::Display for UpdateButton<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Button::Left => write!(f, "Left"),
+            Button::Middle => write!(f, "Middle"),
+            Button::Right => write!(f, "Right"),
+            Button::Unknown(t) => write!(f, "Button: {}", t),
+        }
+    }
+}
+impl FromStr for UpdateButton<'_> {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Left" => Ok(UpdateButton(&Button::Left)),
+            "Right" => Ok(UpdateButton(&Button::Right)),
+            "Middle" => Ok(UpdateButton(&Button::Middle)),
+            _ => {
+                // Akan diperbaiki nanti
+                Ok(UpdateButton(&Button::Unknown(1)))
+            },
+        }
+    }
+}
+
+pub struct UpdateEventType(pub EventType);
+impl std::fmt::Display for UpdateEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            EventType::ButtonPress(button) => write!(f, "Button Press {}", UpdateButton(button)),
+            EventType::ButtonRelease(button) => write!(f, "Button Release {}", UpdateButton(button)),
+            EventType::KeyPress(key) => write!(f, "Key Press {}", UpdateKey(key)),
+            EventType::KeyRelease(key) => write!(f, "Key Release {}", UpdateKey(key)),
+            EventType::MouseMove { x, y } => write!(f, "Mouse Move {} {}", x, y),
+            EventType::Wheel { .. } => write!(f, "Mouse Wheel")
+        }
+    }
+}
+
+// Mengembalikan index event penanda dengan nama tersebut (penanda pertama kalau ada yang namanya sama)
+pub fn cari_penanda(data_macro: &[DataKey], nama: &str) -> Option<usize> {
+    data_macro.iter().position(|val| matches!(val.aksi, Aksi::Penanda) && val.value == nama)
+}
+
+// Menggeser waktu semua event sehingga event pertama mulai dari 0
+pub fn rebase_waktu(data_macro: &mut [DataKey]) {
+    if let Some(waktu_awal) = data_macro.first().map(|val| val.waktu) {
+        for val in data_macro.iter_mut() {
+            val.waktu -= waktu_awal;
+        }
+    }
+}
+
+// Lama macro dalam detik, dihitung dari event terakhir
+pub fn durasi_macro(data_macro: &[DataKey]) -> f32 {
+    data_macro.iter().map(|val| val.waktu).fold(0.0, f32::max)
+}
@@ -0,0 +1,306 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use rdev::EventType;
+
+use crate::alur;
+use crate::event::{Aksi, DataKey, UpdateButton, UpdateKey, TIPE_PENANDA};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    // Format asli "tipe|||value|||waktu" per baris
+    Rf,
+    Json,
+    // File .rhai berisi satu skrip, dibaca sebagai macro dengan satu aksi Script
+    Rhai
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rf" => Ok(Format::Rf),
+            "json" => Ok(Format::Json),
+            "rhai" => Ok(Format::Rhai),
+            _ => Err(format!("Format {} tidak dikenal", s))
+        }
+    }
+}
+
+impl Format {
+    pub fn dari_path(path: &Path) -> Format {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+            Some(ext) if ext.eq_ignore_ascii_case("rhai") => Format::Rhai,
+            _ => Format::Rf
+        }
+    }
+}
+
+pub const VERSI_JSON: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DokumenJson {
+    versi: u32,
+    events: Vec<DataKey>
+}
+
+// Label dan komentar bisa berisi karakter apa saja, jadi "|", "%" dan baris baru di-escape supaya tidak merusak format "|||"
+fn escape_teks(teks: &str) -> String {
+    teks.replace('%', "%25").replace('|', "%7C").replace('\n', "%0A").replace('\r', "%0D")
+}
+
+fn unescape_teks(teks: &str) -> String {
+    teks.replace("%0D", "\r").replace("%0A", "\n").replace("%7C", "|").replace("%25", "%")
+}
+
+fn ambil_kolom_opsional(raw_data_macro: &[&str], index: usize) -> Option<String> {
+    match raw_data_macro.get(index) {
+        Some(teks) if !teks.is_empty() => Some(unescape_teks(teks)),
+        _ => None
+    }
+}
+
+pub fn serialize_macro(data_macro: &[DataKey]) -> String {
+    let mut content_string = String::from("");
+    for val in data_macro {
+        let mut value_event = String::from("");
+
+        match val.aksi {
+            Aksi::Input(event_type) => {
+                if matches!(event_type, EventType::ButtonPress(..)) || matches!(event_type, EventType::ButtonRelease(..)) || matches!(event_type, EventType::MouseMove {..}) || matches!(event_type, EventType::Wheel {..}) {
+                    value_event = val.value.clone();
+                }
+
+                if let EventType::KeyPress(key) = event_type {
+                    value_event = UpdateKey(&key).to_string();
+                }
+                if let EventType::KeyRelease(key) = event_type {
+                    value_event = UpdateKey(&key).to_string();
+                }
+            },
+            _ => {
+                value_event = escape_teks(&val.value);
+            }
+        }
+
+        let mut string_val = format!("{}|||{}|||{}", val.tipe(), value_event, val.waktu);
+        // Kolom label, komentar dan judul window hanya ditulis kalau ada, supaya file lama dan baru tetap sama formatnya
+        if val.label.is_some() || val.komentar.is_some() || val.window.is_some() {
+            string_val.push_str(&format!("|||{}|||{}", escape_teks(val.label.as_deref().unwrap_or("")), escape_teks(val.komentar.as_deref().unwrap_or(""))));
+        }
+        if let Some(window) = &val.window {
+            string_val.push_str(&format!("|||{}", escape_teks(window)));
+        }
+        string_val.push('\n');
+        content_string.push_str(&string_val);
+    }
+
+    content_string
+}
+
+// Ok(None) untuk baris kosong, Err berisi alasan kenapa baris tidak bisa dibaca
+pub fn parse_baris(val: &str) -> Result<Option<DataKey>, String> {
+    let val = val.trim_end_matches('\r');
+    if val.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let raw_data_macro: Vec<&str> = val.split("|||").collect();
+    if raw_data_macro.len() < 3 {
+        return Err(String::from("Kolom kurang dari 3"));
+    }
+
+    let waktu = raw_data_macro[2].parse::<f32>().map_err(|_| format!("Waktu {} bukan angka", raw_data_macro[2]))?;
+    let label = ambil_kolom_opsional(&raw_data_macro, 3);
+    let komentar = ambil_kolom_opsional(&raw_data_macro, 4);
+    let window = ambil_kolom_opsional(&raw_data_macro, 5);
+
+    if raw_data_macro[0] == TIPE_PENANDA {
+        return Ok(Some(DataKey { aksi: Aksi::Penanda, value: unescape_teks(raw_data_macro[1]), waktu, label, komentar, window }));
+    }
+
+    if let Some(aksi) = alur::parse_aksi(raw_data_macro[0], &unescape_teks(raw_data_macro[1]))? {
+        return Ok(Some(DataKey { value: alur::nilai_aksi(&aksi), aksi, waktu, label, komentar, window }));
+    }
+
+    if raw_data_macro[0].contains("Key Press") || raw_data_macro[0].contains("Key Release") {
+        let k = UpdateKey::from_str(raw_data_macro[1]).map_err(|e| format!("{} ({})", e, raw_data_macro[1]))?;
+        let event_type = if raw_data_macro[0].contains("Key Press") {
+            EventType::KeyPress(*k.0)
+        } else {
+            EventType::KeyRelease(*k.0)
+        };
+
+        return Ok(Some(DataKey { aksi: Aksi::Input(event_type), value: k.to_string(), waktu, label, komentar, window }));
+    }
+
+    if raw_data_macro[0].starts_with("Mouse Move") {
+        let posisi: Vec<&str> = raw_data_macro[1].split(", ").collect();
+        let (x, y) = match posisi[..] {
+            [x, y] => (x.parse::<f64>(), y.parse::<f64>()),
+            _ => return Err(format!("Posisi mouse {} tidak valid", raw_data_macro[1]))
+        };
+        return match (x, y) {
+            (Ok(x), Ok(y)) => Ok(Some(DataKey { aksi: Aksi::Input(EventType::MouseMove { x, y }), value: raw_data_macro[1].to_string(), waktu, label, komentar, window })),
+            _ => Err(format!("Posisi mouse {} tidak valid", raw_data_macro[1]))
+        };
+    }
+
+    if raw_data_macro[0].contains("Button Press") || raw_data_macro[0].contains("Button Release") || raw_data_macro[0].contains("Mouse Wheel") {
+        let split_str_0: Vec<&str> = raw_data_macro[0].split(" ").collect();
+        let mut tombol_ditekan = String::from("");
+
+        if raw_data_macro[0].contains("Button Press") || raw_data_macro[0].contains("Button Release") {
+            if split_str_0.len() != 3 {
+                return Err(format!("Button {} tidak valid", raw_data_macro[0]));
+            }
+
+            tombol_ditekan = split_str_0[2].to_string();
+        }
+
+        if raw_data_macro[0].contains("Mouse Wheel") {
+            tombol_ditekan = String::from("Wheel");
+        }
+
+        let b = UpdateButton::from_str(&tombol_ditekan)?;
+        let event_type = if raw_data_macro[0].contains("Button Press") {
+            EventType::ButtonPress(*b.0)
+        } else if raw_data_macro[0].contains("Button Release") {
+            EventType::ButtonRelease(*b.0)
+        } else {
+            let raw_split_value: Vec<&str> = raw_data_macro[1].split(", ").collect();
+            if raw_split_value.len() < 2 {
+                return Err(format!("Nilai wheel {} tidak valid", raw_data_macro[1]));
+            }
+            let value_0 = raw_split_value[0].parse::<i64>().map_err(|_| format!("Nilai wheel {} tidak valid", raw_data_macro[1]))?;
+            let value_1 = raw_split_value[1].parse::<i64>().map_err(|_| format!("Nilai wheel {} tidak valid", raw_data_macro[1]))?;
+
+            EventType::Wheel { delta_x: value_0, delta_y: value_1 }
+        };
+
+        return Ok(Some(DataKey { aksi: Aksi::Input(event_type), value: raw_data_macro[1].to_string(), waktu, label, komentar, window }));
+    }
+
+    Err(format!("Tipe event {} tidak dikenal", raw_data_macro[0]))
+}
+
+// Baris yang tidak valid dilewati, sama seperti waktu membuka file dari menu
+pub fn parse_macro(contents: &str) -> Vec<DataKey> {
+    contents.split("\n").filter_map(|val| parse_baris(val).ok().flatten()).collect()
+}
+
+// Mengembalikan semua kesalahan beserta nomor barisnya (mulai dari 1)
+pub fn periksa_macro(contents: &str) -> Vec<(usize, String)> {
+    contents.split("\n").enumerate().filter_map(|(index, val)| parse_baris(val).err().map(|e| (index + 1, e))).collect()
+}
+
+pub fn serialize_json(data_macro: &[DataKey]) -> Result<String, String> {
+    let dokumen = DokumenJson { versi: VERSI_JSON, events: data_macro.to_vec() };
+    serde_json::to_string_pretty(&dokumen).map_err(|e| e.to_string())
+}
+
+pub fn parse_json(contents: &str) -> Result<Vec<DataKey>, String> {
+    let dokumen: DokumenJson = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    if dokumen.versi > VERSI_JSON {
+        return Err(format!("Versi file {} lebih baru dari yang didukung ({})", dokumen.versi, VERSI_JSON));
+    }
+
+    Ok(dokumen.events)
+}
+
+// Format dideteksi dari isinya, jadi file JSON dengan ekstensi .rf juga tetap bisa dibuka
+pub fn deteksi_format(contents: &str) -> Format {
+    if contents.trim_start().starts_with('{') {
+        Format::Json
+    } else {
+        Format::Rf
+    }
+}
+
+// Skrip tidak bisa dikenali dari isinya, jadi file .rhai dikenali dari ekstensinya
+pub fn deteksi_format_file(path: &Path, contents: &str) -> Format {
+    match Format::dari_path(path) {
+        Format::Rhai => Format::Rhai,
+        _ => deteksi_format(contents)
+    }
+}
+
+pub fn parse_skrip(contents: &str) -> Vec<DataKey> {
+    if contents.trim().is_empty() {
+        return Vec::new();
+    }
+
+    vec![DataKey::kontrol(Aksi::Skrip(contents.to_string()), 0.0)]
+}
+
+pub fn baca_macro(path: &Path) -> Result<Vec<DataKey>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    match deteksi_format_file(path, &contents) {
+        Format::Rf => Ok(parse_macro(&contents)),
+        Format::Json => parse_json(&contents),
+        Format::Rhai => Ok(parse_skrip(&contents))
+    }
+}
+
+pub fn tulis_macro(path: &Path, data_macro: &[DataKey], format: Format) -> Result<(), String> {
+    let contents = match format {
+        Format::Rf => serialize_macro(data_macro),
+        Format::Json => serialize_json(data_macro)?,
+        Format::Rhai => match data_macro {
+            [DataKey { aksi: Aksi::Skrip(kode), .. }] => kode.clone(),
+            _ => return Err(String::from("Hanya macro yang berisi satu Script yang bisa disimpan sebagai .rhai"))
+        }
+    };
+
+    fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use rdev::{Button, Key};
+    use super::*;
+
+    #[test]
+    fn parse_baris_dengan_label_dan_komentar() {
+        let data_key = parse_baris("Key Press KeyA|||KeyA|||1.5|||ketik|||isi nama").unwrap().unwrap();
+        assert!(matches!(data_key.aksi, Aksi::Input(EventType::KeyPress(Key::KeyA))));
+        assert_eq!((data_key.waktu, data_key.label.as_deref(), data_key.komentar.as_deref(), data_key.window), (1.5, Some("ketik"), Some("isi nama"), None));
+
+        // Kolom kosong dibaca sebagai tidak ada
+        let data_key = parse_baris("Button Press Left|||10, 20|||2|||||||||Form").unwrap().unwrap();
+        assert!(matches!(data_key.aksi, Aksi::Input(EventType::ButtonPress(Button::Left))));
+        assert_eq!((data_key.label, data_key.komentar, data_key.window.as_deref()), (None, None, Some("Form")));
+
+        let data_key = parse_baris("Marker|||login|||3\r").unwrap().unwrap();
+        assert!(matches!(data_key.aksi, Aksi::Penanda));
+        assert_eq!(data_key.value, "login");
+
+        assert!(parse_baris("  ").unwrap().is_none());
+        assert_eq!(parse_baris("Key Press KeyA|||KeyA").unwrap_err(), "Kolom kurang dari 3");
+        assert_eq!(parse_baris("Key Press KeyA|||KeyA|||x").unwrap_err(), "Waktu x bukan angka");
+        assert_eq!(parse_baris("Lompat|||a|||0").unwrap_err(), "Tipe event Lompat tidak dikenal");
+    }
+
+    #[test]
+    fn serialize_macro_bolak_balik() {
+        let teks = "Marker|||login|||0\nMouse Move 10 20|||10, 20|||0.25\nKey Press KeyA|||KeyA|||1|||ketik|||\nMouse Wheel|||0, -1|||2|||||||||Form\n";
+        assert_eq!(serialize_macro(&parse_macro(teks)), teks);
+
+        // "|||", "%" dan baris baru di label dan komentar di-escape, lalu dibaca kembali apa adanya
+        let mut data_key = DataKey::input(EventType::KeyPress(Key::Return), String::from("Return"), 0.5);
+        data_key.label = Some(String::from("a|||b 100%"));
+        data_key.komentar = Some(String::from("baris 1\r\nbaris 2"));
+        let teks = serialize_macro(&[data_key]);
+        assert_eq!(teks, "Key Press Return|||Return|||0.5|||a%7C%7C%7Cb 100%25|||baris 1%0D%0Abaris 2\n");
+
+        let hasil = parse_macro(&teks);
+        assert_eq!(hasil.len(), 1);
+        assert_eq!(hasil[0].label.as_deref(), Some("a|||b 100%"));
+        assert_eq!(hasil[0].komentar.as_deref(), Some("baris 1\r\nbaris 2"));
+        // "%7C" yang ditulis pengguna tetap "%7C", bukan "|"
+        assert_eq!(unescape_teks(&escape_teks("%7C")), "%7C");
+    }
+}
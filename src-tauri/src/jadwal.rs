@@ -0,0 +1,429 @@
+// Penjadwal playback: memainkan file macro pada waktu tertentu atau sesuai ekspresi cron (menit jam tanggal bulan hari).
+// Jadwal dan riwayatnya disimpan di jadwal.json di folder config, waktu cron memakai zona waktu lokal.
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use chrono::{DateTime, Datelike, Local, Timelike};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::player::{self, HasilPlayback, OpsiPlayback};
+use crate::{format, pengaturan, PengirimEvent, APAKAH_LAGI_MAIN, APAKAH_RECORD};
+
+const NAMA_FILE: &str = "jadwal.json";
+const MAKS_RIWAYAT: usize = 200;
+
+// Dari frontend dikirim sebagai { jenis: "Cron", nilai: "0 2 * * *" } atau { jenis: "Sekali", nilai: <unix detik> }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "jenis", content = "nilai")]
+pub enum Pemicu {
+    Cron(String),
+    Sekali(i64)
+}
+
+// Yang dilakukan kalau jadwal jatuh tempo tapi ada playback atau recording yang sedang berjalan
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum JikaSibuk {
+    #[default]
+    Lewati,
+    Antre
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Jadwal {
+    // Diisi oleh tambah(), nilai dari frontend diabaikan
+    #[serde(default)]
+    pub id: u64,
+    pub nama: String,
+    pub path: String,
+    pub pemicu: Pemicu,
+    #[serde(default)]
+    pub opsi: OpsiPlayback,
+    #[serde(default)]
+    pub jika_sibuk: JikaSibuk,
+    #[serde(default = "default_aktif")]
+    pub aktif: bool
+}
+
+fn default_aktif() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StatusJalan {
+    Selesai,
+    Dilewati,
+    Diantre,
+    Gagal,
+    // Playback dihentikan pengguna sebelum selesai
+    Dihentikan
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Riwayat {
+    pub id_jadwal: u64,
+    pub nama: String,
+    // Unix detik
+    pub waktu: i64,
+    pub status: StatusJalan,
+    pub pesan: Option<String>
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DataJadwal {
+    id_berikutnya: u64,
+    jadwal: Vec<Jadwal>,
+    riwayat: Vec<Riwayat>
+}
+
+static DATA_JADWAL: Lazy<Mutex<DataJadwal>> = Lazy::new(|| Mutex::new(muat()));
+static ANTREAN: Lazy<Mutex<VecDeque<u64>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+// Setiap field disimpan sebagai bitmask, bit ke-n menyala kalau nilai n cocok
+struct Cron {
+    menit: u64,
+    jam: u64,
+    tanggal: u64,
+    bulan: u64,
+    hari: u64,
+    // Seperti cron biasa: kalau tanggal dan hari sama-sama dibatasi, cukup salah satu yang cocok
+    tanggal_dibatasi: bool,
+    hari_dibatasi: bool
+}
+
+fn parse_field(teks: &str, min: u32, max: u32) -> Result<u64, String> {
+    let parse_angka = |angka: &str| -> Result<u32, String> {
+        match angka.parse::<u32>() {
+            Ok(angka) if angka >= min && angka <= max => Ok(angka),
+            _ => Err(format!("\"{}\" harus angka {}-{}", angka, min, max))
+        }
+    };
+
+    let mut bitmask = 0u64;
+    for bagian in teks.split(',') {
+        let (rentang, langkah) = match bagian.split_once('/') {
+            Some((rentang, langkah)) => match langkah.parse::<u32>() {
+                Ok(langkah) if langkah > 0 => (rentang, Some(langkah)),
+                _ => return Err(format!("Langkah \"{}\" tidak valid", langkah))
+            },
+            None => (bagian, None)
+        };
+
+        let (awal, akhir) = if rentang == "*" {
+            (min, max)
+        } else if let Some((awal, akhir)) = rentang.split_once('-') {
+            (parse_angka(awal)?, parse_angka(akhir)?)
+        } else {
+            let awal = parse_angka(rentang)?;
+            // "5/15" berarti mulai dari 5 lalu setiap 15
+            (awal, if langkah.is_some() { max } else { awal })
+        };
+        if awal > akhir {
+            return Err(format!("Rentang \"{}\" terbalik", rentang));
+        }
+
+        for nilai in (awal..=akhir).step_by(langkah.unwrap_or(1) as usize) {
+            bitmask |= 1 << nilai;
+        }
+    }
+
+    Ok(bitmask)
+}
+
+impl Cron {
+    fn parse(teks: &str) -> Result<Cron, String> {
+        let field: Vec<&str> = teks.split_whitespace().collect();
+        if field.len() != 5 {
+            return Err(format!("Ekspresi cron \"{}\" harus punya 5 bagian: menit jam tanggal bulan hari", teks));
+        }
+
+        let mut hari = parse_field(field[4], 0, 7)?;
+        // 0 dan 7 sama-sama hari Minggu
+        if hari & (1 << 7) != 0 {
+            hari |= 1;
+        }
+
+        Ok(Cron {
+            menit: parse_field(field[0], 0, 59)?,
+            jam: parse_field(field[1], 0, 23)?,
+            tanggal: parse_field(field[2], 1, 31)?,
+            bulan: parse_field(field[3], 1, 12)?,
+            hari,
+            tanggal_dibatasi: !field[2].starts_with('*'),
+            hari_dibatasi: !field[4].starts_with('*')
+        })
+    }
+
+    fn cocok(&self, waktu: &DateTime<Local>) -> bool {
+        let bit = |bitmask: u64, nilai: u32| bitmask & (1 << nilai) != 0;
+        let tanggal_cocok = bit(self.tanggal, waktu.day());
+        let hari_cocok = bit(self.hari, waktu.weekday().num_days_from_sunday());
+        let hari_ini_cocok = if self.tanggal_dibatasi && self.hari_dibatasi {
+            tanggal_cocok || hari_cocok
+        } else {
+            tanggal_cocok && hari_cocok
+        };
+
+        bit(self.menit, waktu.minute()) && bit(self.jam, waktu.hour()) && bit(self.bulan, waktu.month()) && hari_ini_cocok
+    }
+}
+
+fn muat() -> DataJadwal {
+    pengaturan::muat_json(NAMA_FILE)
+}
+
+fn simpan(data: &DataJadwal) -> Result<(), String> {
+    pengaturan::simpan_json(NAMA_FILE, data)
+}
+
+fn periksa(jadwal: &Jadwal) -> Result<(), String> {
+    if !Path::new(&jadwal.path).is_file() {
+        return Err(format!("File {} tidak ditemukan", jadwal.path));
+    }
+
+    match &jadwal.pemicu {
+        Pemicu::Cron(teks) => Cron::parse(teks).map(|_| ()),
+        Pemicu::Sekali(waktu) if jadwal.aktif && *waktu <= Local::now().timestamp() => Err(String::from("Waktu jadwal sudah lewat")),
+        Pemicu::Sekali(..) => Ok(())
+    }
+}
+
+pub fn daftar() -> Vec<Jadwal> {
+    DATA_JADWAL.lock().unwrap().jadwal.clone()
+}
+
+pub fn riwayat() -> Vec<Riwayat> {
+    DATA_JADWAL.lock().unwrap().riwayat.clone()
+}
+
+pub fn tambah(mut jadwal: Jadwal) -> Result<Jadwal, String> {
+    periksa(&jadwal)?;
+
+    let mut data = DATA_JADWAL.lock().unwrap();
+    data.id_berikutnya += 1;
+    jadwal.id = data.id_berikutnya;
+    data.jadwal.push(jadwal.clone());
+    simpan(&data)?;
+
+    Ok(jadwal)
+}
+
+pub fn ubah(jadwal: Jadwal) -> Result<Jadwal, String> {
+    periksa(&jadwal)?;
+
+    let mut data = DATA_JADWAL.lock().unwrap();
+    let lama = data.jadwal.iter_mut().find(|lama| lama.id == jadwal.id).ok_or(format!("Jadwal {} tidak ditemukan", jadwal.id))?;
+    *lama = jadwal.clone();
+    simpan(&data)?;
+
+    Ok(jadwal)
+}
+
+pub fn hapus(id: u64) -> Result<(), String> {
+    let mut data = DATA_JADWAL.lock().unwrap();
+    let jumlah = data.jadwal.len();
+    data.jadwal.retain(|jadwal| jadwal.id != id);
+    if data.jadwal.len() == jumlah {
+        return Err(format!("Jadwal {} tidak ditemukan", id));
+    }
+
+    ANTREAN.lock().unwrap().retain(|id_antre| *id_antre != id);
+    simpan(&data)
+}
+
+fn sibuk() -> bool {
+    unsafe { APAKAH_LAGI_MAIN.unwrap_or(false) || APAKAH_RECORD.unwrap_or(false) }
+}
+
+fn catat<P: PengirimEvent>(pengirim: &P, jadwal: &Jadwal, waktu: i64, status: StatusJalan, pesan: Option<String>) {
+    let riwayat = Riwayat { id_jadwal: jadwal.id, nama: jadwal.nama.clone(), waktu, status, pesan };
+
+    let mut data = DATA_JADWAL.lock().unwrap();
+    data.riwayat.push(riwayat.clone());
+    if data.riwayat.len() > MAKS_RIWAYAT {
+        let lebih = data.riwayat.len() - MAKS_RIWAYAT;
+        data.riwayat.drain(..lebih);
+    }
+    if let Err(e) = simpan(&data) {
+        println!("Riwayat jadwal tidak bisa disimpan: {}", e);
+    }
+    drop(data);
+
+    pengirim.kirim_event("RiwayatJadwal", riwayat);
+}
+
+// Playback jalan di thread sendiri supaya jadwal lain yang jatuh tempo selama itu tetap bisa dilewati atau diantre
+fn mulai<P: PengirimEvent + Send + Sync + 'static>(pengirim: &Arc<P>, jadwal: Jadwal) {
+    let waktu = Local::now().timestamp();
+    let data_macro = match format::baca_macro(Path::new(&jadwal.path)) {
+        Ok(data_macro) if data_macro.is_empty() => return catat(pengirim.as_ref(), &jadwal, waktu, StatusJalan::Gagal, Some(String::from("Macro kosong"))),
+        Ok(data_macro) => data_macro,
+        Err(e) => return catat(pengirim.as_ref(), &jadwal, waktu, StatusJalan::Gagal, Some(e))
+    };
+
+    // Tidak ada yang bisa menekan step waktu jadwal berjalan sendiri
+    let mut opsi = jadwal.opsi.clone();
+    opsi.debug = false;
+    opsi.breakpoint.clear();
+    opsi.file_macro.get_or_insert(jadwal.path.clone());
+    if let Err(e) = player::rentang_playback(&data_macro, &opsi) {
+        return catat(pengirim.as_ref(), &jadwal, waktu, StatusJalan::Gagal, Some(e));
+    }
+
+    unsafe {
+        APAKAH_LAGI_MAIN = Some(true);
+    }
+    let pengirim = pengirim.clone();
+    thread::spawn(move || {
+        let (status, pesan) = match player::mainkan(pengirim.as_ref(), &data_macro, &opsi) {
+            HasilPlayback::Selesai => (StatusJalan::Selesai, None),
+            HasilPlayback::Dihentikan => (StatusJalan::Dihentikan, None),
+            HasilPlayback::Gagal(pesan) => (StatusJalan::Gagal, Some(pesan))
+        };
+        catat(pengirim.as_ref(), &jadwal, waktu, status, pesan);
+    });
+}
+
+fn jatuh_tempo<P: PengirimEvent + Send + Sync + 'static>(pengirim: &Arc<P>, jadwal: Jadwal) {
+    if !sibuk() {
+        return mulai(pengirim, jadwal);
+    }
+
+    let waktu = Local::now().timestamp();
+    match jadwal.jika_sibuk {
+        JikaSibuk::Lewati => catat(pengirim.as_ref(), &jadwal, waktu, StatusJalan::Dilewati, Some(String::from("Playback atau recording lain sedang berjalan"))),
+        JikaSibuk::Antre => {
+            let mut antrean = ANTREAN.lock().unwrap();
+            if !antrean.contains(&jadwal.id) {
+                antrean.push_back(jadwal.id);
+            }
+            drop(antrean);
+            catat(pengirim.as_ref(), &jadwal, waktu, StatusJalan::Diantre, None);
+        }
+    }
+}
+
+pub fn jalankan<P: PengirimEvent + Send + Sync + 'static>(pengirim: P) {
+    let pengirim = Arc::new(pengirim);
+    thread::spawn(move || {
+        let mut menit_terakhir: Option<i64> = None;
+        loop {
+            let sekarang = Local::now();
+            let menit = sekarang.timestamp() / 60;
+            // Cron cukup dicek sekali per menit supaya tidak jalan berkali-kali di menit yang sama
+            let cek_cron = menit_terakhir != Some(menit);
+            menit_terakhir = Some(menit);
+
+            let mut daftar_jatuh_tempo: Vec<Jadwal> = Vec::new();
+            {
+                let mut data = DATA_JADWAL.lock().unwrap();
+                let mut berubah = false;
+                for jadwal in data.jadwal.iter_mut().filter(|jadwal| jadwal.aktif) {
+                    let waktunya = match &jadwal.pemicu {
+                        Pemicu::Cron(teks) => cek_cron && Cron::parse(teks).is_ok_and(|cron| cron.cocok(&sekarang)),
+                        Pemicu::Sekali(waktu) => sekarang.timestamp() >= *waktu
+                    };
+                    if !waktunya {
+                        continue
+                    }
+
+                    // Jadwal sekali jalan langsung dimatikan supaya tidak terulang setelah aplikasi dibuka lagi
+                    if let Pemicu::Sekali(..) = jadwal.pemicu {
+                        jadwal.aktif = false;
+                        berubah = true;
+                    }
+                    daftar_jatuh_tempo.push(jadwal.clone());
+                }
+
+                if berubah {
+                    if let Err(e) = simpan(&data) {
+                        println!("Jadwal tidak bisa disimpan: {}", e);
+                    }
+                }
+            }
+
+            for jadwal in daftar_jatuh_tempo {
+                jatuh_tempo(&pengirim, jadwal);
+            }
+
+            if !sibuk() {
+                let id = ANTREAN.lock().unwrap().pop_front();
+                let jadwal = id.and_then(|id| DATA_JADWAL.lock().unwrap().jadwal.iter().find(|jadwal| jadwal.id == id).cloned());
+                if let Some(jadwal) = jadwal {
+                    mulai(&pengirim, jadwal);
+                }
+            }
+
+            thread::sleep(Duration::from_secs(1));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use super::*;
+
+    fn waktu(tanggal: u32, jam: u32, menit: u32) -> DateTime<Local> {
+        // Juni 2024: tanggal 2 hari Minggu, tanggal 3 hari Senin
+        Local.with_ymd_and_hms(2024, 6, tanggal, jam, menit, 0).unwrap()
+    }
+
+    #[test]
+    fn parse_field_cron() {
+        assert_eq!(parse_field("*", 0, 7), Ok(0b1111_1111));
+        assert_eq!(parse_field("1,3,5", 0, 59), Ok(0b10_1010));
+        assert_eq!(parse_field("2-4", 0, 59), Ok(0b1_1100));
+        assert_eq!(parse_field("*/20", 0, 59), Ok(1 | 1 << 20 | 1 << 40));
+        assert_eq!(parse_field("5/20", 0, 59), Ok(1 << 5 | 1 << 25 | 1 << 45));
+        assert_eq!(parse_field("10-20/5,1", 0, 59), Ok(1 << 10 | 1 << 15 | 1 << 20 | 1 << 1));
+
+        assert_eq!(parse_field("5-2", 0, 59), Err(String::from("Rentang \"5-2\" terbalik")));
+        assert_eq!(parse_field("60", 0, 59), Err(String::from("\"60\" harus angka 0-59")));
+        assert_eq!(parse_field("0", 1, 31), Err(String::from("\"0\" harus angka 1-31")));
+        assert!(parse_field("*/0", 0, 59).is_err());
+        assert!(parse_field("a", 0, 59).is_err());
+        assert!(parse_field("", 0, 59).is_err());
+    }
+
+    #[test]
+    fn parse_cron() {
+        assert!(Cron::parse("0 2 * *").is_err());
+        assert!(Cron::parse("0 24 * * *").is_err());
+        assert!(Cron::parse("0 0 * 13 *").is_err());
+        assert!(Cron::parse("0 0 * * 8").is_err());
+
+        // 7 dan 0 sama-sama hari Minggu
+        assert_eq!(Cron::parse("0 0 * * 7").unwrap().hari & 1, 1);
+        assert!(Cron::parse("30 2 * * 7").unwrap().cocok(&waktu(2, 2, 30)));
+        assert!(!Cron::parse("30 2 * * 7").unwrap().cocok(&waktu(3, 2, 30)));
+    }
+
+    #[test]
+    fn cron_cocok() {
+        let cron = Cron::parse("*/15 9-17 * * 1-5").unwrap();
+        assert!(cron.cocok(&waktu(3, 9, 45)));
+        assert!(!cron.cocok(&waktu(3, 9, 50)));
+        assert!(!cron.cocok(&waktu(3, 18, 0)));
+        // Hari Minggu
+        assert!(!cron.cocok(&waktu(2, 9, 0)));
+
+        // Tanggal dan hari sama-sama dibatasi: cukup salah satu yang cocok
+        let cron = Cron::parse("0 12 15 * 1").unwrap();
+        assert!(cron.cocok(&waktu(15, 12, 0)));
+        assert!(cron.cocok(&waktu(3, 12, 0)));
+        assert!(!cron.cocok(&waktu(4, 12, 0)));
+
+        // Kalau hanya salah satu yang dibatasi, yang dibatasi harus cocok
+        let cron = Cron::parse("0 12 15 * *").unwrap();
+        assert!(cron.cocok(&waktu(15, 12, 0)));
+        assert!(!cron.cocok(&waktu(3, 12, 0)));
+        let cron = Cron::parse("0 12 * 6 1").unwrap();
+        assert!(cron.cocok(&waktu(3, 12, 0)));
+        assert!(!cron.cocok(&waktu(15, 12, 0)));
+        assert!(!Cron::parse("0 12 * 7 1").unwrap().cocok(&waktu(3, 12, 0)));
+    }
+}
@@ -0,0 +1,155 @@
+// Informasi window yang sedang aktif dan daftar window yang terbuka. Baru didukung di Linux/X11, di platform lain selalu kosong
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InfoWindow {
+    pub judul: String,
+    // Bagian class dari WM_CLASS, misalnya "firefox"
+    pub kelas: String
+}
+
+#[cfg(target_os = "linux")]
+mod x11_linux {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::{c_int, c_uchar, c_ulong};
+    use std::ptr;
+    use x11::xlib;
+
+    use super::InfoWindow;
+
+    struct Tampilan(*mut xlib::Display);
+    impl Drop for Tampilan {
+        fn drop(&mut self) {
+            unsafe {
+                xlib::XCloseDisplay(self.0);
+            }
+        }
+    }
+
+    fn atom(tampilan: &Tampilan, nama: &str) -> c_ulong {
+        let nama = CString::new(nama).unwrap();
+        unsafe { xlib::XInternAtom(tampilan.0, nama.as_ptr(), xlib::False) }
+    }
+
+    // Isi property dalam byte dan format-nya (8, 16 atau 32)
+    fn properti(tampilan: &Tampilan, window: c_ulong, nama: &str) -> Option<(Vec<u8>, c_int, c_ulong)> {
+        let mut tipe: c_ulong = 0;
+        let mut format: c_int = 0;
+        let mut jumlah: c_ulong = 0;
+        let mut sisa: c_ulong = 0;
+        let mut data: *mut c_uchar = ptr::null_mut();
+
+        let status = unsafe {
+            xlib::XGetWindowProperty(tampilan.0, window, atom(tampilan, nama), 0, 1024, xlib::False, xlib::AnyPropertyType as c_ulong,
+                &mut tipe, &mut format, &mut jumlah, &mut sisa, &mut data)
+        };
+        if status != xlib::Success as c_int || data.is_null() {
+            return None;
+        }
+
+        // Data format 32 disimpan Xlib sebagai long
+        let ukuran = match format {
+            8 => 1,
+            16 => std::mem::size_of::<std::os::raw::c_short>(),
+            32 => std::mem::size_of::<c_ulong>(),
+            _ => 0
+        };
+        let isi = unsafe { std::slice::from_raw_parts(data, jumlah as usize * ukuran).to_vec() };
+        unsafe {
+            xlib::XFree(data as *mut _);
+        }
+
+        Some((isi, format, jumlah))
+    }
+
+    // Property berisi daftar window (format 32) di root window
+    fn daftar_id(tampilan: &Tampilan, nama: &str) -> Vec<c_ulong> {
+        let root = unsafe { xlib::XDefaultRootWindow(tampilan.0) };
+        match properti(tampilan, root, nama) {
+            Some((isi, 32, _)) => isi.chunks_exact(std::mem::size_of::<c_ulong>())
+                .filter_map(|bagian| bagian.try_into().ok().map(c_ulong::from_ne_bytes))
+                .filter(|window| *window != 0)
+                .collect(),
+            _ => Vec::new()
+        }
+    }
+
+    fn window_aktif(tampilan: &Tampilan) -> Option<c_ulong> {
+        daftar_id(tampilan, "_NET_ACTIVE_WINDOW").first().copied()
+    }
+
+    fn judul(tampilan: &Tampilan, window: c_ulong) -> Option<String> {
+        if let Some((isi, 8, _)) = properti(tampilan, window, "_NET_WM_NAME") {
+            return Some(String::from_utf8_lossy(&isi).into_owned());
+        }
+
+        // Window lama yang belum memakai _NET_WM_NAME
+        let mut nama: *mut std::os::raw::c_char = ptr::null_mut();
+        unsafe {
+            if xlib::XFetchName(tampilan.0, window, &mut nama) == 0 || nama.is_null() {
+                return None;
+            }
+            let judul = CStr::from_ptr(nama).to_string_lossy().into_owned();
+            xlib::XFree(nama as *mut _);
+            Some(judul)
+        }
+    }
+
+    // WM_CLASS berisi "instance\0class\0", class yang dipakai kalau ada
+    fn kelas(tampilan: &Tampilan, window: c_ulong) -> String {
+        match properti(tampilan, window, "WM_CLASS") {
+            Some((isi, 8, _)) => {
+                let bagian: Vec<&[u8]> = isi.split(|byte| *byte == 0).filter(|bagian| !bagian.is_empty()).collect();
+                bagian.last().map(|kelas| String::from_utf8_lossy(kelas).into_owned()).unwrap_or_default()
+            },
+            _ => String::new()
+        }
+    }
+
+    fn info(tampilan: &Tampilan, window: c_ulong) -> InfoWindow {
+        InfoWindow { judul: judul(tampilan, window).unwrap_or_default(), kelas: kelas(tampilan, window) }
+    }
+
+    fn buka() -> Option<Tampilan> {
+        let tampilan = unsafe { xlib::XOpenDisplay(ptr::null()) };
+        if tampilan.is_null() { None } else { Some(Tampilan(tampilan)) }
+    }
+
+    pub fn judul_aktif() -> Option<String> {
+        let tampilan = buka()?;
+        let window = window_aktif(&tampilan)?;
+        judul(&tampilan, window)
+    }
+
+    pub fn info_aktif() -> Option<InfoWindow> {
+        let tampilan = buka()?;
+        let window = window_aktif(&tampilan)?;
+        Some(info(&tampilan, window))
+    }
+
+    // Window yang dikelola window manager, termasuk yang sedang diminimize
+    pub fn daftar_window() -> Vec<InfoWindow> {
+        let Some(tampilan) = buka() else {
+            return Vec::new();
+        };
+        daftar_id(&tampilan, "_NET_CLIENT_LIST").into_iter().map(|window| info(&tampilan, window)).collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use x11_linux::{daftar_window, info_aktif, judul_aktif};
+
+#[cfg(not(target_os = "linux"))]
+pub fn judul_aktif() -> Option<String> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn info_aktif() -> Option<InfoWindow> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn daftar_window() -> Vec<InfoWindow> {
+    Vec::new()
+}
@@ -0,0 +1,628 @@
+// Aksi WaitPixel dan WaitImage: menunggu warna di satu titik atau gambar template muncul di layar.
+// Layar diambil lewat SumberLayar, defaultnya layar X11 (hanya Linux). Untuk tes tanpa display, RECORDFLOW_SCREEN=<file.png>
+// atau --screen di CLI membuat layar dibaca dari file PNG, dan LayarMemori bisa diganti isinya dari kode.
+// Posisi yang cocok disimpan di variabel {match_x} dan {match_y}, jadi klik berikutnya bisa ditulis "Button Press Left|||{match_x}, {match_y}|||…".
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::alur::{self, BatasTunggu};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warna(pub u8, pub u8, pub u8);
+
+// "#ff8800" atau "255,136,0"
+impl FromStr for Warna {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let salah = || format!("Warna {} harus berbentuk #rrggbb atau r,g,b", s);
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return Err(salah());
+            }
+            let bagian = |posisi: usize| u8::from_str_radix(&hex[posisi..posisi + 2], 16).map_err(|_| salah());
+            return Ok(Warna(bagian(0)?, bagian(2)?, bagian(4)?));
+        }
+
+        match s.split(',').map(|bagian| bagian.trim().parse::<u8>()).collect::<Result<Vec<u8>, _>>() {
+            Ok(bagian) if bagian.len() == 3 => Ok(Warna(bagian[0], bagian[1], bagian[2])),
+            _ => Err(salah())
+        }
+    }
+}
+
+impl fmt::Display for Warna {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl Warna {
+    // Selisih terbesar dari ketiga channel
+    pub fn selisih(&self, lain: &Warna) -> u8 {
+        self.0.abs_diff(lain.0).max(self.1.abs_diff(lain.1)).max(self.2.abs_diff(lain.2))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Wilayah {
+    pub x: i32,
+    pub y: i32,
+    pub lebar: u32,
+    pub tinggi: u32
+}
+
+// "x,y,lebar,tinggi"
+impl FromStr for Wilayah {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bagian: Vec<&str> = s.split(',').map(str::trim).collect();
+        let salah = || format!("Region {} harus berbentuk x,y,lebar,tinggi", s);
+        let [x, y, lebar, tinggi] = bagian[..] else {
+            return Err(salah());
+        };
+
+        let wilayah = Wilayah {
+            x: x.parse().map_err(|_| salah())?,
+            y: y.parse().map_err(|_| salah())?,
+            lebar: lebar.parse().map_err(|_| salah())?,
+            tinggi: tinggi.parse().map_err(|_| salah())?
+        };
+        if wilayah.lebar == 0 || wilayah.tinggi == 0 {
+            return Err(format!("Region {} kosong", s));
+        }
+        Ok(wilayah)
+    }
+}
+
+impl fmt::Display for Wilayah {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.lebar, self.tinggi)
+    }
+}
+
+impl Wilayah {
+    // Bagian wilayah yang ada di layar berukuran lebar x tinggi, seluruh layar kalau None
+    fn batasi(wilayah: Option<Wilayah>, lebar: u32, tinggi: u32) -> Result<Wilayah, String> {
+        let Some(wilayah) = wilayah else {
+            return Ok(Wilayah { x: 0, y: 0, lebar, tinggi });
+        };
+
+        let kiri = wilayah.x.max(0) as i64;
+        let atas = wilayah.y.max(0) as i64;
+        let kanan = (wilayah.x as i64 + wilayah.lebar as i64).min(lebar as i64);
+        let bawah = (wilayah.y as i64 + wilayah.tinggi as i64).min(tinggi as i64);
+        if kanan <= kiri || bawah <= atas {
+            return Err(format!("Region {} di luar layar {}x{}", wilayah, lebar, tinggi));
+        }
+
+        Ok(Wilayah { x: kiri as i32, y: atas as i32, lebar: (kanan - kiri) as u32, tinggi: (bawah - atas) as u32 })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gambar {
+    pub lebar: u32,
+    pub tinggi: u32,
+    // RGBA per baris dari kiri atas. Piksel template dengan alpha di bawah 128 tidak ikut dicocokkan
+    pub piksel: Vec<[u8; 4]>
+}
+
+impl Gambar {
+    pub fn baru(lebar: u32, tinggi: u32, warna: Warna) -> Gambar {
+        Gambar { lebar, tinggi, piksel: vec![[warna.0, warna.1, warna.2, 255]; (lebar * tinggi) as usize] }
+    }
+
+    pub fn baca_png(path: &Path) -> Result<Gambar, String> {
+        let salah = |e: &dyn fmt::Display| format!("{}: {}", path.display(), e);
+        let file = File::open(path).map_err(|e| salah(&e))?;
+        let mut decoder = png::Decoder::new(file);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(|e| salah(&e))?;
+        let mut isi = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut isi).map_err(|e| salah(&e))?;
+        let isi = &isi[..info.buffer_size()];
+
+        let piksel = match info.color_type {
+            png::ColorType::Rgba => isi.chunks_exact(4).map(|val| [val[0], val[1], val[2], val[3]]).collect(),
+            png::ColorType::Rgb => isi.chunks_exact(3).map(|val| [val[0], val[1], val[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => isi.chunks_exact(2).map(|val| [val[0], val[0], val[0], val[1]]).collect(),
+            png::ColorType::Grayscale => isi.iter().map(|val| [*val, *val, *val, 255]).collect(),
+            png::ColorType::Indexed => return Err(salah(&"PNG berpalet tidak didukung"))
+        };
+
+        Ok(Gambar { lebar: info.width, tinggi: info.height, piksel })
+    }
+
+    pub fn warna(&self, x: u32, y: u32) -> Warna {
+        let [r, g, b, _] = self.piksel[(y * self.lebar + x) as usize];
+        Warna(r, g, b)
+    }
+
+    pub fn atur_warna(&mut self, x: u32, y: u32, warna: Warna) {
+        self.piksel[(y * self.lebar + x) as usize] = [warna.0, warna.1, warna.2, 255];
+    }
+
+    fn potong(&self, wilayah: &Wilayah) -> Gambar {
+        let mut piksel = Vec::with_capacity((wilayah.lebar * wilayah.tinggi) as usize);
+        for y in wilayah.y as u32..wilayah.y as u32 + wilayah.tinggi {
+            let awal = (y * self.lebar + wilayah.x as u32) as usize;
+            piksel.extend_from_slice(&self.piksel[awal..awal + wilayah.lebar as usize]);
+        }
+        Gambar { lebar: wilayah.lebar, tinggi: wilayah.tinggi, piksel }
+    }
+
+    // Tempel gambar lain di (x, y), dipakai untuk menyiapkan layar palsu
+    pub fn tempel(&mut self, lain: &Gambar, x: u32, y: u32) {
+        for ly in 0..lain.tinggi.min(self.tinggi.saturating_sub(y)) {
+            for lx in 0..lain.lebar.min(self.lebar.saturating_sub(x)) {
+                self.piksel[((y + ly) * self.lebar + x + lx) as usize] = lain.piksel[(ly * lain.lebar + lx) as usize];
+            }
+        }
+    }
+}
+
+pub trait SumberLayar: Send + Sync {
+    // Isi layar di wilayah (seluruh layar kalau None) yang sudah dipotong sesuai batas layar, beserta wilayah sebenarnya
+    fn tangkap(&self, wilayah: Option<Wilayah>) -> Result<(Wilayah, Gambar), String>;
+}
+
+// Layar palsu dari file PNG yang dibaca ulang setiap kali ditangkap, jadi tes bisa mengganti file-nya di tengah playback
+pub struct LayarFile {
+    pub path: PathBuf
+}
+
+impl SumberLayar for LayarFile {
+    fn tangkap(&self, wilayah: Option<Wilayah>) -> Result<(Wilayah, Gambar), String> {
+        let gambar = Gambar::baca_png(&self.path)?;
+        let wilayah = Wilayah::batasi(wilayah, gambar.lebar, gambar.tinggi)?;
+        Ok((wilayah, gambar.potong(&wilayah)))
+    }
+}
+
+// Layar palsu di memori, isinya bisa diganti dengan ganti() dari thread lain
+pub struct LayarMemori {
+    gambar: Mutex<Gambar>
+}
+
+impl LayarMemori {
+    pub fn baru(gambar: Gambar) -> LayarMemori {
+        LayarMemori { gambar: Mutex::new(gambar) }
+    }
+
+    pub fn ganti(&self, gambar: Gambar) {
+        *self.gambar.lock().unwrap() = gambar;
+    }
+}
+
+impl SumberLayar for LayarMemori {
+    fn tangkap(&self, wilayah: Option<Wilayah>) -> Result<(Wilayah, Gambar), String> {
+        let gambar = self.gambar.lock().unwrap();
+        let wilayah = Wilayah::batasi(wilayah, gambar.lebar, gambar.tinggi)?;
+        Ok((wilayah, gambar.potong(&wilayah)))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11_linux {
+    use std::os::raw::c_ulong;
+    use std::ptr;
+    use x11::xlib;
+
+    use super::{Gambar, Wilayah};
+
+    // Satu channel dari piksel X11 menurut mask-nya, diskalakan ke 0-255 (layar 16-bit memakai channel 5 dan 6 bit)
+    pub(super) fn channel(nilai: c_ulong, mask: c_ulong) -> u8 {
+        let bit = mask.count_ones();
+        let isi = (nilai & mask) >> mask.trailing_zeros();
+        if bit >= 8 {
+            return (isi >> (bit - 8)) as u8;
+        }
+        let maks = (1 << bit) - 1;
+        ((isi * 255 + maks / 2) / maks) as u8
+    }
+
+    pub fn tangkap(wilayah: Option<Wilayah>) -> Result<(Wilayah, Gambar), String> {
+        let tampilan = unsafe { xlib::XOpenDisplay(ptr::null()) };
+        if tampilan.is_null() {
+            return Err(String::from("Display X11 tidak bisa dibuka"));
+        }
+
+        let hasil = unsafe {
+            let layar = xlib::XDefaultScreen(tampilan);
+            let lebar = xlib::XDisplayWidth(tampilan, layar) as u32;
+            let tinggi = xlib::XDisplayHeight(tampilan, layar) as u32;
+            Wilayah::batasi(wilayah, lebar, tinggi).and_then(|wilayah| {
+                let root = xlib::XDefaultRootWindow(tampilan);
+                let image = xlib::XGetImage(tampilan, root, wilayah.x, wilayah.y, wilayah.lebar, wilayah.tinggi, xlib::XAllPlanes(), xlib::ZPixmap);
+                if image.is_null() {
+                    return Err(String::from("Layar tidak bisa ditangkap"));
+                }
+
+                let mask = ((*image).red_mask, (*image).green_mask, (*image).blue_mask);
+                // Visual tanpa mask warna (bukan TrueColor) tidak bisa dibaca per channel
+                if mask.0 == 0 || mask.1 == 0 || mask.2 == 0 {
+                    xlib::XDestroyImage(image);
+                    return Err(String::from("Layar tidak memakai visual TrueColor, tidak bisa ditangkap"));
+                }
+                let mut piksel = Vec::with_capacity((wilayah.lebar * wilayah.tinggi) as usize);
+                for y in 0..wilayah.tinggi as i32 {
+                    for x in 0..wilayah.lebar as i32 {
+                        let nilai = xlib::XGetPixel(image, x, y);
+                        piksel.push([channel(nilai, mask.0), channel(nilai, mask.1), channel(nilai, mask.2), 255]);
+                    }
+                }
+                xlib::XDestroyImage(image);
+
+                Ok((wilayah, Gambar { lebar: wilayah.lebar, tinggi: wilayah.tinggi, piksel }))
+            })
+        };
+        unsafe {
+            xlib::XCloseDisplay(tampilan);
+        }
+        hasil
+    }
+}
+
+struct LayarSistem;
+impl SumberLayar for LayarSistem {
+    #[cfg(target_os = "linux")]
+    fn tangkap(&self, wilayah: Option<Wilayah>) -> Result<(Wilayah, Gambar), String> {
+        x11_linux::tangkap(wilayah)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn tangkap(&self, _wilayah: Option<Wilayah>) -> Result<(Wilayah, Gambar), String> {
+        Err(String::from("Tangkap layar baru didukung di Linux/X11"))
+    }
+}
+
+static SUMBER: Lazy<RwLock<Arc<dyn SumberLayar>>> = Lazy::new(|| {
+    let sumber: Arc<dyn SumberLayar> = match std::env::var_os("RECORDFLOW_SCREEN").filter(|val| !val.is_empty()) {
+        Some(path) => Arc::new(LayarFile { path: PathBuf::from(path) }),
+        None => Arc::new(LayarSistem)
+    };
+    RwLock::new(sumber)
+});
+
+pub fn atur_sumber(sumber: Arc<dyn SumberLayar>) {
+    *SUMBER.write().unwrap() = sumber;
+}
+
+pub fn tangkap(wilayah: Option<Wilayah>) -> Result<(Wilayah, Gambar), String> {
+    let sumber = SUMBER.read().unwrap().clone();
+    sumber.tangkap(wilayah)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Cocok {
+    // Koordinat layar, untuk gambar adalah titik tengah template
+    pub x: i32,
+    pub y: i32,
+    // 1.0 berarti sama persis
+    pub keyakinan: f32
+}
+
+// Template yang paling mirip di layar dengan keyakinan minimal min_keyakinan.
+// Setiap posisi dicek dulu dengan beberapa titik contoh, yang selisihnya jauh tidak dihitung penuh
+pub fn cari_gambar(layar: &Gambar, template: &Gambar, min_keyakinan: f32) -> Option<Cocok> {
+    if template.lebar > layar.lebar || template.tinggi > layar.tinggi {
+        return None;
+    }
+
+    let titik: Vec<(u32, u32, [u8; 4])> = (0..template.tinggi)
+        .flat_map(|y| (0..template.lebar).map(move |x| (x, y)))
+        .map(|(x, y)| (x, y, template.piksel[(y * template.lebar + x) as usize]))
+        .filter(|(_, _, piksel)| piksel[3] >= 128)
+        .collect();
+    if titik.is_empty() {
+        return None;
+    }
+    let langkah_contoh = (titik.len() / 32).max(1);
+    let contoh: Vec<&(u32, u32, [u8; 4])> = titik.iter().step_by(langkah_contoh).collect();
+
+    // Selisih dijumlah dari tiga channel, maksimal 765 per titik
+    let selisih = |piksel: &[u8; 4], x: u32, y: u32| -> u64 {
+        let layar = layar.piksel[(y * layar.lebar + x) as usize];
+        (0..3).map(|channel| piksel[channel].abs_diff(layar[channel]) as u64).sum()
+    };
+    let batas_per_titik = (1.0 - min_keyakinan.clamp(0.0, 1.0)) as f64 * 765.0;
+    let batas_contoh = (batas_per_titik * 2.0 * contoh.len() as f64) as u64;
+    let mut batas_total = (batas_per_titik * titik.len() as f64) as u64;
+
+    let mut terbaik: Option<(u32, u32, u64)> = None;
+    'posisi: for y in 0..=layar.tinggi - template.tinggi {
+        'posisi_x: for x in 0..=layar.lebar - template.lebar {
+            let mut jumlah = 0;
+            for (tx, ty, piksel) in contoh.iter() {
+                jumlah += selisih(piksel, x + tx, y + ty);
+                if jumlah > batas_contoh {
+                    continue 'posisi_x;
+                }
+            }
+
+            let mut jumlah = 0;
+            for (tx, ty, piksel) in titik.iter() {
+                jumlah += selisih(piksel, x + tx, y + ty);
+                if jumlah > batas_total {
+                    continue 'posisi_x;
+                }
+            }
+            // Posisi berikutnya harus lebih mirip dari yang ini
+            batas_total = jumlah;
+            terbaik = Some((x, y, jumlah));
+            if jumlah == 0 {
+                break 'posisi;
+            }
+        }
+    }
+
+    terbaik.map(|(x, y, jumlah)| Cocok {
+        x: (x + template.lebar / 2) as i32,
+        y: (y + template.tinggi / 2) as i32,
+        keyakinan: 1.0 - jumlah as f32 / (765.0 * titik.len() as f32)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TargetLayar {
+    // Selisih terbesar per channel yang masih dianggap sama, 0 sampai 255
+    Piksel { x: i32, y: i32, warna: Warna, toleransi: u8 },
+    // file PNG relatif terhadap folder macro, region membatasi bagian layar yang dicari supaya lebih cepat
+    Gambar { file: String, wilayah: Option<Wilayah>, keyakinan: f32 }
+}
+
+// WaitPixel ditulis "x=100; y=200; color=#ff8800; tolerance=10", WaitImage ditulis "image=tombol.png; region=0,0,800,600; confidence=0.9",
+// keduanya bisa ditambah timeout, on_timeout dan retries seperti WaitWindow
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TungguLayar {
+    pub target: TargetLayar,
+    pub batas: BatasTunggu
+}
+
+impl TungguLayar {
+    pub fn parse_piksel(s: &str) -> Result<TungguLayar, String> {
+        let (mut x, mut y, mut warna, mut toleransi) = (None, None, None, 0);
+        let mut batas = BatasTunggu::default();
+        for (kunci, nilai) in alur::pisah_opsi(s)? {
+            let angka = || nilai.parse::<i32>().map_err(|_| format!("{} {} bukan angka", kunci, nilai));
+            match kunci {
+                "x" => x = Some(angka()?),
+                "y" => y = Some(angka()?),
+                "color" => warna = Some(nilai.parse()?),
+                "tolerance" => toleransi = nilai.parse().map_err(|_| format!("tolerance {} harus 0 sampai 255", nilai))?,
+                _ if batas.atur(kunci, nilai)? => {},
+                _ => return Err(format!("{} tidak dikenal di WaitPixel", kunci))
+            }
+        }
+
+        match (x, y, warna) {
+            (Some(x), Some(y), Some(warna)) => Ok(TungguLayar { target: TargetLayar::Piksel { x, y, warna, toleransi }, batas }),
+            _ => Err(String::from("WaitPixel butuh x, y dan color"))
+        }
+    }
+
+    pub fn parse_gambar(s: &str) -> Result<TungguLayar, String> {
+        let (mut file, mut wilayah, mut keyakinan) = (None, None, 0.9);
+        let mut batas = BatasTunggu::default();
+        for (kunci, nilai) in alur::pisah_opsi(s)? {
+            match kunci {
+                "image" => file = Some(nilai.to_string()),
+                "region" => wilayah = Some(nilai.parse()?),
+                "confidence" => keyakinan = match nilai.parse::<f32>() {
+                    Ok(keyakinan) if (0.0..=1.0).contains(&keyakinan) => keyakinan,
+                    _ => return Err(format!("confidence {} harus 0 sampai 1", nilai))
+                },
+                _ if batas.atur(kunci, nilai)? => {},
+                _ => return Err(format!("{} tidak dikenal di WaitImage", kunci))
+            }
+        }
+
+        match file {
+            Some(file) if !file.is_empty() => Ok(TungguLayar { target: TargetLayar::Gambar { file, wilayah, keyakinan }, batas }),
+            _ => Err(String::from("WaitImage butuh image"))
+        }
+    }
+}
+
+impl fmt::Display for TungguLayar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bagian = Vec::new();
+        match &self.target {
+            TargetLayar::Piksel { x, y, warna, toleransi } => {
+                bagian.push(format!("x={}; y={}; color={}", x, y, warna));
+                if *toleransi != 0 {
+                    bagian.push(format!("tolerance={}", toleransi));
+                }
+            },
+            TargetLayar::Gambar { file, wilayah, keyakinan } => {
+                bagian.push(format!("image={}", file));
+                if let Some(wilayah) = wilayah {
+                    bagian.push(format!("region={}", wilayah));
+                }
+                bagian.push(format!("confidence={}", keyakinan));
+            }
+        }
+        self.batas.tulis(&mut bagian);
+        write!(f, "{}", bagian.join("; "))
+    }
+}
+
+// Path absolut dipakai apa adanya, path relatif dicari dari folder file macro (atau folder kerja kalau tidak ada)
+pub fn path_template(file: &str, file_macro: Option<&str>) -> PathBuf {
+    let path = Path::new(file);
+    match file_macro.and_then(|file_macro| Path::new(file_macro).parent()) {
+        Some(folder) if path.is_relative() => folder.join(path),
+        _ => path.to_path_buf()
+    }
+}
+
+// Satu kali cek, None kalau belum cocok. template harus sudah dibaca untuk TargetLayar::Gambar
+pub fn cek(target: &TargetLayar, template: Option<&Gambar>) -> Result<Option<Cocok>, String> {
+    match target {
+        TargetLayar::Piksel { x, y, warna, toleransi } => {
+            let (_, gambar) = tangkap(Some(Wilayah { x: *x, y: *y, lebar: 1, tinggi: 1 }))?;
+            let selisih = gambar.warna(0, 0).selisih(warna);
+            if selisih > *toleransi {
+                return Ok(None);
+            }
+            Ok(Some(Cocok { x: *x, y: *y, keyakinan: 1.0 - selisih as f32 / 255.0 }))
+        },
+        TargetLayar::Gambar { file, wilayah, keyakinan } => {
+            let template = template.ok_or(format!("Template {} belum dibaca", file))?;
+            let (wilayah, layar) = tangkap(*wilayah)?;
+            Ok(cari_gambar(&layar, template, *keyakinan).map(|cocok| Cocok { x: cocok.x + wilayah.x, y: cocok.y + wilayah.y, ..cocok }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alur::SaatHabis;
+
+    // Template 4x3 dengan warna berbeda di setiap piksel supaya hanya cocok di satu posisi
+    fn template() -> Gambar {
+        let mut gambar = Gambar::baru(4, 3, Warna(0, 0, 0));
+        for y in 0..3 {
+            for x in 0..4 {
+                gambar.atur_warna(x, y, Warna(40 + x as u8 * 50, 30 + y as u8 * 60, 200));
+            }
+        }
+        gambar
+    }
+
+    fn layar_dengan(template: &Gambar, x: u32, y: u32) -> Gambar {
+        let mut layar = Gambar::baru(20, 10, Warna(128, 128, 128));
+        layar.tempel(template, x, y);
+        layar
+    }
+
+    #[test]
+    fn cari_gambar_sama_persis() {
+        let template = template();
+        let cocok = cari_gambar(&layar_dengan(&template, 7, 5), &template, 0.9).unwrap();
+        // Titik tengah template
+        assert_eq!((cocok.x, cocok.y, cocok.keyakinan), (9, 6, 1.0));
+
+        // Di pojok kanan bawah layar
+        let cocok = cari_gambar(&layar_dengan(&template, 16, 7), &template, 1.0).unwrap();
+        assert_eq!((cocok.x, cocok.y), (18, 8));
+    }
+
+    #[test]
+    fn cari_gambar_tidak_ada() {
+        let template = template();
+        assert_eq!(cari_gambar(&Gambar::baru(20, 10, Warna(128, 128, 128)), &template, 0.9), None);
+        // Template lebih besar dari layar
+        assert_eq!(cari_gambar(&Gambar::baru(3, 10, Warna(128, 128, 128)), &template, 0.0), None);
+        // Semua piksel template transparan
+        let transparan = Gambar { lebar: 2, tinggi: 2, piksel: vec![[0, 0, 0, 0]; 4] };
+        assert_eq!(cari_gambar(&template, &transparan, 0.0), None);
+    }
+
+    #[test]
+    fn cari_gambar_batas_keyakinan() {
+        let template = Gambar::baru(4, 3, Warna(200, 100, 50));
+        // Selisih 30 dari 765 di setiap piksel, keyakinannya sekitar 0.961
+        let layar = layar_dengan(&Gambar::baru(4, 3, Warna(170, 100, 50)), 3, 2);
+
+        let cocok = cari_gambar(&layar, &template, 0.95).unwrap();
+        assert_eq!((cocok.x, cocok.y), (5, 3));
+        assert!((cocok.keyakinan - (1.0 - 30.0 / 765.0)).abs() < 1e-6);
+        assert_eq!(cari_gambar(&layar, &template, 0.97), None);
+        assert_eq!(cari_gambar(&layar, &template, 1.0), None);
+    }
+
+    #[test]
+    fn cari_gambar_abaikan_transparan() {
+        let mut template = template();
+        // Piksel transparan boleh berbeda dengan layar
+        template.piksel[0] = [0, 0, 0, 0];
+        template.piksel[5] = [255, 255, 255, 100];
+        let cocok = cari_gambar(&layar_dengan(&self::template(), 2, 1), &template, 1.0).unwrap();
+        assert_eq!((cocok.x, cocok.y, cocok.keyakinan), (4, 2, 1.0));
+    }
+
+    #[test]
+    fn warna_dan_wilayah() {
+        assert_eq!("#FF8800".parse::<Warna>(), Ok(Warna(255, 136, 0)));
+        assert_eq!(" 255, 136 ,0".parse::<Warna>(), Ok(Warna(255, 136, 0)));
+        assert_eq!(Warna(255, 136, 0).to_string(), "#ff8800");
+        for salah in ["ff8800", "#ff880", "#gg8800", "255,136", "256,0,0"] {
+            assert!(salah.parse::<Warna>().is_err(), "{}", salah);
+        }
+        assert_eq!(Warna(10, 200, 30).selisih(&Warna(20, 190, 30)), 10);
+
+        assert_eq!("0, 0, 800, 600".parse::<Wilayah>(), Ok(Wilayah { x: 0, y: 0, lebar: 800, tinggi: 600 }));
+        assert!("0,0,800".parse::<Wilayah>().is_err());
+        assert!("0,0,0,600".parse::<Wilayah>().is_err());
+    }
+
+    #[test]
+    fn tunggu_piksel_bolak_balik() {
+        let teks = "x=100; y=200; color=#ff8800; tolerance=10; timeout=5000; on_timeout=retry; retries=2";
+        let tunggu = TungguLayar::parse_piksel(teks).unwrap();
+        assert_eq!(tunggu.target, TargetLayar::Piksel { x: 100, y: 200, warna: Warna(255, 136, 0), toleransi: 10 });
+        assert_eq!(tunggu.batas, BatasTunggu { batas_ms: 5000, saat_habis: SaatHabis::Ulangi, maks_ulang: 2 });
+        assert_eq!(tunggu.to_string(), teks);
+
+        // Nilai default ikut ditulis, hasilnya bisa di-parse lagi
+        let tunggu = TungguLayar::parse_piksel("x=-5; y=2; color=255,136,0").unwrap();
+        assert_eq!(tunggu.to_string(), "x=-5; y=2; color=#ff8800; timeout=30000; on_timeout=fail");
+        assert_eq!(TungguLayar::parse_piksel(&tunggu.to_string()), Ok(tunggu));
+
+        for salah in ["x=1; y=2", "x=1; color=#ffffff", "x=a; y=2; color=#ffffff", "x=1; y=2; color=#ffffff; tolerance=300", "x=1; y=2; color=#ffffff; image=a.png", "x=1; y=2; color=#ffffff; on_timeout=ulang"] {
+            assert!(TungguLayar::parse_piksel(salah).is_err(), "{}", salah);
+        }
+    }
+
+    #[test]
+    fn tunggu_gambar_bolak_balik() {
+        let teks = "image=tombol simpan.png; region=10,20,800,600; confidence=0.95; timeout=1000; on_timeout=skip";
+        let tunggu = TungguLayar::parse_gambar(teks).unwrap();
+        assert_eq!(tunggu.target, TargetLayar::Gambar {
+            file: String::from("tombol simpan.png"),
+            wilayah: Some(Wilayah { x: 10, y: 20, lebar: 800, tinggi: 600 }),
+            keyakinan: 0.95
+        });
+        assert_eq!(tunggu.to_string(), teks);
+
+        let tunggu = TungguLayar::parse_gambar("image=ok.png").unwrap();
+        assert_eq!(tunggu.to_string(), "image=ok.png; confidence=0.9; timeout=30000; on_timeout=fail");
+        assert_eq!(TungguLayar::parse_gambar(&tunggu.to_string()), Ok(tunggu));
+
+        for salah in ["region=0,0,10,10", "image=", "image=a.png; confidence=1.5", "image=a.png; region=0,0,10", "image=a.png; color=#ffffff"] {
+            assert!(TungguLayar::parse_gambar(salah).is_err(), "{}", salah);
+        }
+    }
+
+    #[test]
+    fn path_template_relatif() {
+        assert_eq!(path_template("tombol.png", Some("/macro/form.rf")), PathBuf::from("/macro/tombol.png"));
+        assert_eq!(path_template("/gambar/tombol.png", Some("/macro/form.rf")), PathBuf::from("/gambar/tombol.png"));
+        assert_eq!(path_template("tombol.png", None), PathBuf::from("tombol.png"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn channel_dari_mask() {
+        // 8-8-8
+        assert_eq!(x11_linux::channel(0x12_34_56, 0xff_00_00), 0x12);
+        assert_eq!(x11_linux::channel(0x12_34_56, 0x00_ff_00), 0x34);
+        assert_eq!(x11_linux::channel(0x12_34_56, 0x00_00_ff), 0x56);
+        // 5-6-5: nilai penuh jadi 255, nol tetap 0
+        assert_eq!(x11_linux::channel(0xffff, 0xf800), 255);
+        assert_eq!(x11_linux::channel(0xffff, 0x07e0), 255);
+        assert_eq!(x11_linux::channel(0xffff, 0x001f), 255);
+        assert_eq!(x11_linux::channel(0x0000, 0xf800), 0);
+        assert_eq!(x11_linux::channel(0b10000 << 11, 0xf800), 132);
+        assert_eq!(x11_linux::channel(0b100000 << 5, 0x07e0), 130);
+    }
+}
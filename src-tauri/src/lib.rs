@@ -0,0 +1,53 @@
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use serde::Serialize;
+use rdev::{simulate, EventType};
+
+pub mod alur;
+pub mod event;
+pub mod format;
+pub mod jadwal;
+pub mod jendela;
+pub mod layar;
+pub mod manusiawi;
+pub mod panggilan;
+pub mod papan_klip;
+pub mod pemulihan;
+pub mod pengaturan;
+pub mod perintah;
+pub mod pintasan;
+pub mod player;
+pub mod pustaka;
+pub mod recorder;
+pub mod siaran;
+pub mod singkatan;
+pub mod skrip;
+pub mod sumber_data;
+pub mod terbaru;
+pub mod variabel;
+pub mod websocket;
+
+use event::DataKey;
+
+pub static mut DATA_MACRO: once_cell::sync::Lazy<Arc<Mutex<Vec<DataKey>>>> = once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(Vec::new())));
+pub static mut APAKAH_RECORD: Option<bool> = Some(false);
+pub static mut APAKAH_LAGI_MAIN: Option<bool> = Some(false);
+pub static mut WAKTU_MULAI_RECORD: once_cell::sync::Lazy<SystemTime> = once_cell::sync::Lazy::new(SystemTime::now);
+
+// Tujuan event dari engine. Aplikasi Tauri meneruskannya ke window, CLI cukup menampilkannya di terminal
+pub trait PengirimEvent {
+    fn kirim_event<S: Serialize + Clone>(&self, nama: &str, payload: S);
+}
+
+// sumber: https://docs.rs/rdev/latest/rdev/
+pub fn send(event_type: &EventType) {
+    // let delay = time::Duration::from_millis(20);
+    match simulate(event_type) {
+        Ok(()) => (),
+        Err(..) => {
+            println!("We could not send {:?}", event_type);
+        }
+    }
+    // Let ths OS catchup (at least MacOS)
+    // thread::sleep(delay);
+}
@@ -2,215 +2,41 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![allow(const_item_mutation)]
 
-use std::str::FromStr;
-use std::time::{Duration, SystemTime};
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use std::{fs, thread};
-use std::string::ToString;
-use enigo::{ Enigo, MouseControllable };
+use std::thread;
+use enigo::Enigo;
 use tauri::{ CustomMenuItem, Manager, Menu, Submenu };
 use tauri::App;
 use serde::Serialize;
-use rdev::{listen, Button, EventType, Key, simulate};
-
-#[derive(Debug, Clone, Serialize)]
-struct RawDataKey {
-    tipe: String,
-    value: String,
-    waktu: f32
-}
-
-#[derive(Debug, Clone)]
-struct DataKey {
-    event_type: EventType,
-    value: String,
-    waktu: f32
-}
-
-
-#[derive(Debug)]
-struct UpdateKey<'a>(&'a Key);
-impl std::fmt::Display for UpdateKey<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.0)
-    }
-}
-impl FromStr for UpdateKey<'_> {
-    type Err = &'static str;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "Alt" => Ok(UpdateKey(&Key::Alt)),
-            "AltGr" => Ok(UpdateKey(&Key::AltGr)),
-            "Backspace" => Ok(UpdateKey(&Key::Backspace)),
-            "CapsLock" => Ok(UpdateKey(&Key::CapsLock)),
-            "ControlLeft" => Ok(UpdateKey(&Key::ControlLeft)),
-            "ControlRight" => Ok(UpdateKey(&Key::ControlRight)),
-            "Delete" => Ok(UpdateKey(&Key::Delete)),
-            "DownArrow" => Ok(UpdateKey(&Key::DownArrow)),
-            "End" => Ok(UpdateKey(&Key::End)),
-            "Escape" => Ok(UpdateKey(&Key::Escape)),
-            "F1" => Ok(UpdateKey(&Key::F1)),
-            "F10" => Ok(UpdateKey(&Key::F10)),
-            "F11" => Ok(UpdateKey(&Key::F11)),
-            "F12" => Ok(UpdateKey(&Key::F12)),
-            "F2" => Ok(UpdateKey(&Key::F2)),
-            "F3" => Ok(UpdateKey(&Key::F3)),
-            "F4" => Ok(UpdateKey(&Key::F4)),
-            "F5" => Ok(UpdateKey(&Key::F5)),
-            "F6" => Ok(UpdateKey(&Key::F6)),
-            "F7" => Ok(UpdateKey(&Key::F7)),
-            "F8" => Ok(UpdateKey(&Key::F8)),
-            "F9" => Ok(UpdateKey(&Key::F9)),
-            "Home" => Ok(UpdateKey(&Key::Home)),
-            "LeftArrow" => Ok(UpdateKey(&Key::LeftArrow)),
-            "MetaLeft" => Ok(UpdateKey(&Key::MetaLeft)),
-            "MetaRight" => Ok(UpdateKey(&Key::MetaRight)),
-            "PageDown" => Ok(UpdateKey(&Key::PageDown)),
-            "PageUp" => Ok(UpdateKey(&Key::PageUp)),
-            "Return" => Ok(UpdateKey(&Key::Return)),
-            "RightArrow" => Ok(UpdateKey(&Key::RightArrow)),
-            "ShiftLeft" => Ok(UpdateKey(&Key::ShiftLeft)),
-            "ShiftRight" => Ok(UpdateKey(&Key::ShiftRight)),
-            "Space" => Ok(UpdateKey(&Key::Space)),
-            "Tab" => Ok(UpdateKey(&Key::Tab)),
-            "UpArrow" => Ok(UpdateKey(&Key::UpArrow)),
-            "PrintScreen" => Ok(UpdateKey(&Key::PrintScreen)),
-            "ScrollLock" => Ok(UpdateKey(&Key::ScrollLock)),
-            "Pause" => Ok(UpdateKey(&Key::Pause)),
-            "NumLock" => Ok(UpdateKey(&Key::NumLock)),
-            "BackQuote" => Ok(UpdateKey(&Key::BackQuote)),
-            "Num1" => Ok(UpdateKey(&Key::Num1)),
-            "Num2" => Ok(UpdateKey(&Key::Num2)),
-            "Num3" => Ok(UpdateKey(&Key::Num3)),
-            "Num4" => Ok(UpdateKey(&Key::Num4)),
-            "Num5" => Ok(UpdateKey(&Key::Num5)),
-            "Num6" => Ok(UpdateKey(&Key::Num6)),
-            "Num7" => Ok(UpdateKey(&Key::Num7)),
-            "Num8" => Ok(UpdateKey(&Key::Num8)),
-            "Num9" => Ok(UpdateKey(&Key::Num9)),
-            "Num0" => Ok(UpdateKey(&Key::Num0)),
-            "Minus" => Ok(UpdateKey(&Key::Minus)),
-            "Equal" => Ok(UpdateKey(&Key::Equal)),
-            "KeyQ" => Ok(UpdateKey(&Key::KeyQ)),
-            "KeyW" => Ok(UpdateKey(&Key::KeyW)),
-            "KeyE" => Ok(UpdateKey(&Key::KeyE)),
-            "KeyR" => Ok(UpdateKey(&Key::KeyR)),
-            "KeyT" => Ok(UpdateKey(&Key::KeyT)),
-            "KeyY" => Ok(UpdateKey(&Key::KeyY)),
-            "KeyU" => Ok(UpdateKey(&Key::KeyU)),
-            "KeyI" => Ok(UpdateKey(&Key::KeyI)),
-            "KeyO" => Ok(UpdateKey(&Key::KeyO)),
-            "KeyP" => Ok(UpdateKey(&Key::KeyP)),
-            "LeftBracket" => Ok(UpdateKey(&Key::LeftBracket)),
-            "RightBracket" => Ok(UpdateKey(&Key::RightBracket)),
-            "KeyA" => Ok(UpdateKey(&Key::KeyA)),
-            "KeyS" => Ok(UpdateKey(&Key::KeyS)),
-            "KeyD" => Ok(UpdateKey(&Key::KeyD)),
-            "KeyF" => Ok(UpdateKey(&Key::KeyF)),
-            "KeyG" => Ok(UpdateKey(&Key::KeyG)),
-            "KeyH" => Ok(UpdateKey(&Key::KeyH)),
-            "KeyJ" => Ok(UpdateKey(&Key::KeyJ)),
-            "KeyK" => Ok(UpdateKey(&Key::KeyK)),
-            "KeyL" => Ok(UpdateKey(&Key::KeyL)),
-            "SemiColon" => Ok(UpdateKey(&Key::SemiColon)),
-            "Quote" => Ok(UpdateKey(&Key::Quote)),
-            "BackSlash" => Ok(UpdateKey(&Key::BackSlash)),
-            "IntlBackslash" => Ok(UpdateKey(&Key::IntlBackslash)),
-            "KeyZ" => Ok(UpdateKey(&Key::KeyZ)),
-            "KeyX" => Ok(UpdateKey(&Key::KeyX)),
-            "KeyC" => Ok(UpdateKey(&Key::KeyC)),
-            "KeyV" => Ok(UpdateKey(&Key::KeyV)),
-            "KeyB" => Ok(UpdateKey(&Key::KeyB)),
-            "KeyN" => Ok(UpdateKey(&Key::KeyN)),
-            "KeyM" => Ok(UpdateKey(&Key::KeyM)),
-            "Comma" => Ok(UpdateKey(&Key::Comma)),
-            "Dot" => Ok(UpdateKey(&Key::Dot)),
-            "Slash" => Ok(UpdateKey(&Key::Slash)),
-            "Insert" => Ok(UpdateKey(&Key::Insert)),
-            "KpReturn" => Ok(UpdateKey(&Key::KpReturn)),
-            "KpMinus" => Ok(UpdateKey(&Key::KpMinus)),
-            "KpPlus" => Ok(UpdateKey(&Key::KpPlus)),
-            "KpMultiply" => Ok(UpdateKey(&Key::KpMultiply)),
-            "KpDivide" => Ok(UpdateKey(&Key::KpDivide)),
-            "Kp0" => Ok(UpdateKey(&Key::Kp0)),
-            "Kp1" => Ok(UpdateKey(&Key::Kp1)),
-            "Kp2" => Ok(UpdateKey(&Key::Kp2)),
-            "Kp3" => Ok(UpdateKey(&Key::Kp3)),
-            "Kp4" => Ok(UpdateKey(&Key::Kp4)),
-            "Kp5" => Ok(UpdateKey(&Key::Kp5)),
-            "Kp6" => Ok(UpdateKey(&Key::Kp6)),
-            "Kp7" => Ok(UpdateKey(&Key::Kp7)),
-            "Kp8" => Ok(UpdateKey(&Key::Kp8)),
-            "Kp9" => Ok(UpdateKey(&Key::Kp9)),
-            "KpDelete" => Ok(UpdateKey(&Key::KpDelete)),
-            "Function" => Ok(UpdateKey(&Key::Function)),
-            _ => {
-                if s.contains("Unknown") {
-                    let hasil = s.replace("Unknown(", "").replace(")", "").parse::<u32>();
-                    match hasil {
-                        Ok(_n) => {
-                            // Akan diperbaiki nanti
-                            // let n_clone = &Clone::clone(&n);
-                            return Ok(UpdateKey(&Key::Unknown(1)));
-                        },
-                        Err(..) => {
-                            return Err("Unknown Key Value");
-                        }
-                    }
-                }
-
-                Err("Key is not valid!")
-            }
-        }
-    }
+use rdev::{listen, EventType};
+use macro_recroder::alur;
+use macro_recroder::event::{cari_penanda, rebase_waktu, Aksi, DataKey, RawDataKey};
+use macro_recroder::format::{self, Format};
+use macro_recroder::pintasan::{self, Pintasan, SaatBermain};
+use macro_recroder::player::{self, OpsiPlayback, PerintahDebug};
+use macro_recroder::pustaka::{self, MacroPustaka};
+use macro_recroder::singkatan::{self, AksiSingkatan, Singkatan};
+use macro_recroder::jadwal::{self, Jadwal, Riwayat};
+use macro_recroder::pemulihan::{self, InfoSesi};
+use macro_recroder::pengaturan::{self, Pengaturan};
+use macro_recroder::{recorder, terbaru, siaran, skrip, variabel, websocket, PengirimEvent, APAKAH_LAGI_MAIN, APAKAH_RECORD, DATA_MACRO};
+
+mod rpc;
+
+// Event dikirim ke window dan juga disiarkan ke klien di luar aplikasi
+fn kirim<S: Serialize + Clone>(window: &tauri::Window, nama: &str, payload: S) {
+    siaran::siarkan(nama, &payload);
+    window.emit_all(nama, payload).unwrap();
 }
 
-struct UpdateButton<'a>(&'a Button);
-impl std::fmt::Display for UpdateButton<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.0 {
-            Button::Left => write!(f, "Left"),
-            Button::Middle => write!(f, "Middle"),
-            Button::Right => write!(f, "Right"),
-            Button::Unknown(t) => write!(f, "{}", format!("Button: {}", t)),
-        }
+struct PengirimWindow(tauri::Window);
+impl PengirimEvent for PengirimWindow {
+    fn kirim_event<S: Serialize + Clone>(&self, nama: &str, payload: S) {
+        kirim(&self.0, nama, payload);
     }
 }
-impl FromStr for UpdateButton<'_> {
-    type Err = &'static str;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "Left" => Ok(UpdateButton(&Button::Left)),
-            "Right" => Ok(UpdateButton(&Button::Right)),
-            "Middle" => Ok(UpdateButton(&Button::Middle)),
-            _ => {
-                // Akan diperbaiki nanti
-                Ok(UpdateButton(&Button::Unknown(1)))
-            },
-        }
-    }
-}
-
-struct UpdateEventType(EventType);
-impl std::fmt::Display for UpdateEventType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.0 {
-            EventType::ButtonPress(button) => write!(f, "{}", format!("Button Press {}", UpdateButton(button))),
-            EventType::ButtonRelease(button) => write!(f, "{}", format!("Button Release {}", UpdateButton(button))),
-            EventType::KeyPress(key) => write!(f, "{}", format!("Key Press {}", UpdateKey(key))),
-            EventType::KeyRelease(key) => write!(f, "{}", format!("Key Release {}", UpdateKey(key))),
-            EventType::MouseMove { x, y } => write!(f, "Mouse Move {} {}", x, y),
-            EventType::Wheel { .. } => write!(f, "Mouse Wheel")
-        }
-    }
-}
-
-static mut DATA_MACRO: once_cell::sync::Lazy<Arc<Mutex<Vec<DataKey>>>> = once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(Vec::new())));
-static mut APAKAH_RECORD: Option<bool> = Some(false);
-static mut APAKAH_LAGI_MAIN: Option<bool> = Some(false);
-static mut WAKTU_MULAI_RECORD: once_cell::sync::Lazy<SystemTime> = once_cell::sync::Lazy::new(|| SystemTime::now());
 
 fn ganti_state_recorder(apakah_record: &mut bool, window: &tauri::Window) {
     *apakah_record = !*apakah_record;
@@ -219,8 +45,10 @@ fn ganti_state_recorder(apakah_record: &mut bool, window: &tauri::Window) {
         if *apakah_record {
             println!("MUJLAI RECORD");
             DATA_MACRO.lock().unwrap().clear();
-            WAKTU_MULAI_RECORD = once_cell::sync::Lazy::new(|| SystemTime::now());
+            recorder::mulai_waktu_record();
+            pemulihan::mulai_jurnal();
         } else {
+            pemulihan::selesai_jurnal();
             // let data_macro = DATA_MACRO.lock().unwrap();
 
             // for val in data_macro.iter() {
@@ -229,24 +57,42 @@ fn ganti_state_recorder(apakah_record: &mut bool, window: &tauri::Window) {
         }
     }
 
-    window.emit_all("StatusRecorder", *apakah_record).unwrap();
+    kirim(window, "StatusRecorder", *apakah_record);
 }
 
-// sumber: https://docs.rs/rdev/latest/rdev/
-fn send(event_type: &EventType) {
-    // let delay = time::Duration::from_millis(20);
-    match simulate(event_type) {
-        Ok(()) => (),
-        Err(..) => {
-            println!("We could not send {:?}", event_type);
+fn kirim_data_macro(window: &tauri::Window, data_macro: &[DataKey]) {
+    let semua_data: Vec<RawDataKey> = data_macro.iter().enumerate().map(|(id, val)| val.ke_raw(id)).collect();
+    kirim(window, "MuatUlangDataInput", &semua_data);
+}
+
+fn kirim_semua_input(window: &tauri::Window, data_macro: &[DataKey]) {
+    for (id, val) in data_macro.iter().enumerate() {
+        kirim(window, "KirimDataInput", &val.ke_raw(id));
+        // println!("{:?} {} {}", val.event_type, val.value, val.waktu);
+    }
+}
+
+fn judul_menu_terbaru(path: Option<&String>) -> String {
+    match path {
+        Some(path) => Path::new(path).file_name().map(|nama| nama.to_string_lossy().into_owned()).unwrap_or(path.clone()),
+        None => String::from("-")
+    }
+}
+
+// Menu Tauri tidak bisa ditambah item waktu berjalan, jadi slot yang sudah ada diganti judulnya
+fn perbarui_menu_terbaru(window: &tauri::Window) {
+    let daftar = terbaru::daftar();
+    let menu = window.menu_handle();
+    for index in 0..terbaru::MAKS_TERBARU {
+        if let Some(item) = menu.try_get_item(&format!("terbaru_{}", index)) {
+            let _ = item.set_title(judul_menu_terbaru(daftar.get(index)));
+            let _ = item.set_enabled(index < daftar.len());
         }
     }
-    // Let ths OS catchup (at least MacOS)
-    // thread::sleep(delay);
 }
 
 #[tauri::command]
-fn simpan_file(path: String) {
+fn simpan_file(window: tauri::Window, path: String) -> Result<(), String> {
     let mut data_macro_clone: Vec<DataKey> = Vec::new();
     unsafe {
         let data_macro = DATA_MACRO.lock().unwrap();
@@ -255,121 +101,227 @@ fn simpan_file(path: String) {
         }
     }
 
-    let mut content_string = String::from("");
-    for val in data_macro_clone {
-        let mut value_event = String::from("");
+    let path = Path::new(&path);
+    format::tulis_macro(path, &data_macro_clone, Format::dari_path(path))?;
 
-        if matches!(val.event_type, EventType::ButtonPress(..)) || matches!(val.event_type, EventType::ButtonRelease(..)) || matches!(val.event_type, EventType::MouseMove {..}) || matches!(val.event_type, EventType::Wheel {..}) {
-            value_event = val.value;
-        }
+    pemulihan::tandai_tersimpan();
+    terbaru::catat(path);
+    perbarui_menu_terbaru(&window);
+    Ok(())
+}
+
+#[tauri::command]
+fn buka_file(window: tauri::Window, path: String) -> Result<(), String> {
+    let data_macro_baru = format::baca_macro(Path::new(&path))?;
+
+    unsafe {
+        let mut data_macro = DATA_MACRO.lock().unwrap();
+        *data_macro = data_macro_baru;
+        kirim_semua_input(&window, &data_macro);
+    }
+
+    // Macro yang belum disimpan diganti isi file, jurnalnya ikut dibuang
+    pemulihan::tandai_tersimpan();
+    terbaru::catat(Path::new(&path));
+    perbarui_menu_terbaru(&window);
+    Ok(())
+}
+
+#[tauri::command]
+fn daftar_file_terbaru() -> Vec<String> {
+    terbaru::daftar()
+}
+
+#[tauri::command]
+fn kosongkan_file_terbaru(window: tauri::Window) {
+    terbaru::kosongkan();
+    perbarui_menu_terbaru(&window);
+}
 
-        if let EventType::KeyPress(key) = val.event_type {
-            value_event = UpdateKey(&key).to_string();
+#[tauri::command]
+fn sesi_belum_disimpan() -> Option<InfoSesi> {
+    pemulihan::sesi_tersisa()
+}
+
+// Isi jurnal dimuat sebagai macro yang sedang dibuka, jurnalnya baru dibuang setelah macro disimpan
+#[tauri::command]
+fn pulihkan_sesi(window: tauri::Window) -> Result<(), String> {
+    if unsafe { APAKAH_RECORD == Some(true) } {
+        return Err(String::from("Recording sedang berjalan"));
+    }
+    let data_macro_baru = pemulihan::pulihkan()?;
+
+    unsafe {
+        let mut data_macro = DATA_MACRO.lock().unwrap();
+        *data_macro = data_macro_baru;
+        kirim_data_macro(&window, &data_macro);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn buang_sesi() -> Result<(), String> {
+    pemulihan::buang()
+}
+
+#[tauri::command]
+fn atur_label(window: tauri::Window, index: usize, label: Option<String>, komentar: Option<String>) -> Result<(), String> {
+    unsafe {
+        let mut data_macro = DATA_MACRO.lock().unwrap();
+        let val = data_macro.get_mut(index).ok_or(format!("Event {} tidak ada", index))?;
+        val.label = label.filter(|teks| !teks.is_empty());
+        val.komentar = komentar.filter(|teks| !teks.is_empty());
+
+        kirim(&window, "PerbaruiDataInput", &val.ke_raw(index));
+    }
+
+    pemulihan::tandai_berubah();
+    Ok(())
+}
+
+// Penanda disisipkan sebelum event ke-index dengan waktu yang sama, index == jumlah event berarti di akhir macro
+#[tauri::command]
+fn tambah_penanda(window: tauri::Window, index: usize, nama: String, komentar: Option<String>) -> Result<(), String> {
+    if nama.is_empty() {
+        return Err(String::from("Nama penanda tidak boleh kosong"));
+    }
+
+    unsafe {
+        let mut data_macro = DATA_MACRO.lock().unwrap();
+        if index > data_macro.len() {
+            return Err(format!("Event {} tidak ada", index));
         }
-        if let EventType::KeyRelease(key) = val.event_type {
-            value_event = UpdateKey(&key).to_string();
+        if cari_penanda(&data_macro, &nama).is_some() {
+            return Err(format!("Penanda {} sudah ada", nama));
         }
 
-        let string_val = format!("{}|||{}|||{}\n", UpdateEventType(val.event_type).to_string(), value_event, val.waktu);
-        content_string.push_str(&string_val);
+        let waktu = match data_macro.get(index) {
+            Some(val) => val.waktu,
+            None => data_macro.last().map(|val| val.waktu).unwrap_or(0.0)
+        };
+        data_macro.insert(index, DataKey { aksi: Aksi::Penanda, value: nama, waktu, label: None, komentar: komentar.filter(|teks| !teks.is_empty()), window: None });
+
+        kirim_data_macro(&window, &data_macro);
     }
 
-    fs::write(path, content_string).unwrap();
+    pemulihan::tandai_berubah();
+    Ok(())
 }
 
 #[tauri::command]
-fn buka_file(window: tauri::Window, path: String) {
-    let contents = fs::read_to_string(path).expect("Should have been able to read the file");
-
+fn hapus_penanda(window: tauri::Window, nama: String) -> Result<(), String> {
     unsafe {
         let mut data_macro = DATA_MACRO.lock().unwrap();
-        data_macro.clear();
+        let index = cari_penanda(&data_macro, &nama).ok_or(format!("Penanda {} tidak ada", nama))?;
+        data_macro.remove(index);
 
-        for val in contents.split("\n") {
-            let raw_data_macro: Vec<&str> = val.split("|||").collect();
-            if raw_data_macro.len() <= 0 {
-                continue
-            }
+        kirim_data_macro(&window, &data_macro);
+    }
 
-            if raw_data_macro[0].contains("Key Press") || raw_data_macro[0].contains("Key Release") {
-                let raw_key = UpdateKey::from_str(raw_data_macro[1]);
-                match raw_key {
-                    Ok(k) => {
-                        match raw_data_macro[2].parse::<f32>() {
-                            Ok(n) => {
-                                let event_type: EventType;
-                                if raw_data_macro[0].contains("Key Press") {
-                                    event_type = EventType::KeyPress(*k.0);
-                                } else {
-                                    event_type = EventType::KeyRelease(*k.0);
-                                }
-
-                                data_macro.push(DataKey { event_type, value: k.to_string(), waktu: n })
-                            },
-                            Err(..) => continue
-                        }
-                    },
-                    Err(..) => continue
-                }
-            }
+    pemulihan::tandai_berubah();
+    Ok(())
+}
 
-            if raw_data_macro[0].contains("Button Press") || raw_data_macro[0].contains("Button Release") || raw_data_macro[0].contains("Mouse Wheel") {
-                let split_str_0: Vec<&str> = raw_data_macro[0].split(" ").collect();
-                let mut tombol_ditekan = String::from("");
-                
-                if raw_data_macro[0].contains("Button Press") || raw_data_macro[0].contains("Button Release") {
-                    if split_str_0.len() <= 0 || split_str_0.len() > 3 {
-                        continue
-                    }
+// Aksi kontrol (Repeat, Wait, If, Else, End, Break, Continue, Set, Script, Type, ClipboardSet, Paste, ClipboardGet, Run, Var, Call, WaitWindow, WaitPixel, WaitImage) disisipkan sebelum event ke-index seperti penanda.
+// nilai ditulis sama seperti di file, misalnya "3" untuk Repeat, "200-800" untuk Wait atau "var hitung < 5" untuk If
+#[tauri::command]
+fn tambah_aksi_kontrol(window: tauri::Window, index: usize, tipe: String, nilai: String, komentar: Option<String>) -> Result<(), String> {
+    let aksi = alur::parse_aksi(&tipe, &nilai)?.ok_or(format!("{} bukan aksi kontrol", tipe))?;
+    if let Aksi::Skrip(kode) = &aksi {
+        skrip::periksa(kode)?;
+    }
 
-                    tombol_ditekan = split_str_0[2].to_string();     
-                }
-                
-                if raw_data_macro[0].contains("Mouse Wheel") {
-                    tombol_ditekan = String::from("Wheel");
-                }
+    unsafe {
+        let mut data_macro = DATA_MACRO.lock().unwrap();
+        if index > data_macro.len() {
+            return Err(format!("Event {} tidak ada", index));
+        }
+        if matches!(aksi, Aksi::Variabel { .. }) && index > variabel::panjang_header(&data_macro) {
+            return Err(String::from("Var hanya bisa disisipkan di awal macro"));
+        }
 
-                let raw_button = UpdateButton::from_str(&tombol_ditekan);
-                match raw_button {
-                    Ok(b) => {
-                        match raw_data_macro[2].parse::<f32>() {
-                            Ok(n) => {
-                                let event_type: EventType;
-                                if raw_data_macro[0].contains("Button Press") {
-                                    event_type = EventType::ButtonPress(*b.0);
-                                } else if raw_data_macro[0].contains("Button Release") {
-                                    event_type = EventType::ButtonRelease(*b.0);
-                                } else {
-                                    let raw_split_value: Vec<&str> = raw_data_macro[1].split(", ").collect();
-                                    let value_0 = match raw_split_value[0].parse::<i64>() {
-                                        Ok(n) => n,
-                                        Err(..) => continue
-                                    };
-                                    let value_1 = match raw_split_value[1].parse::<i64>() {
-                                        Ok(n) => n,
-                                        Err(..) => continue
-                                    };
-
-                                    event_type = EventType::Wheel { delta_x: value_0, delta_y: value_1 };
-                                }
-
-                                data_macro.push(DataKey { event_type, value: raw_data_macro[1].to_string(), waktu: n })
-                            }
-                            Err(..) => continue
-                        }
-                    },
-                    Err(..) => continue
-                }
-            }
+        let waktu = match data_macro.get(index) {
+            Some(val) => val.waktu,
+            None => data_macro.last().map(|val| val.waktu).unwrap_or(0.0)
+        };
+        let mut data_key = DataKey::kontrol(aksi, waktu);
+        data_key.komentar = komentar.filter(|teks| !teks.is_empty());
+        data_macro.insert(index, data_key);
+
+        kirim_data_macro(&window, &data_macro);
+    }
+
+    pemulihan::tandai_berubah();
+    Ok(())
+}
+
+// Menjalankan skrip dari editor tanpa menyisipkannya ke macro
+#[tauri::command]
+async fn jalankan_skrip(window: tauri::Window, kode: String) -> Result<(), String> {
+    skrip::periksa(&kode)?;
+    unsafe {
+        if APAKAH_RECORD == Some(true) || APAKAH_LAGI_MAIN == Some(true) {
+            return Err(String::from("Recorder sedang merekam atau memainkan macro"));
         }
+        APAKAH_LAGI_MAIN = Some(true);
+    }
 
-        for val in data_macro.iter() {
-            let data_key = RawDataKey { tipe: UpdateEventType(val.event_type).to_string(), value: val.value.clone(), waktu: val.waktu.clone() };
-            window.emit_all("KirimDataInput", &data_key).unwrap();
-            // println!("{:?} {} {}", val.event_type, val.value, val.waktu);
+    player::mainkan(&PengirimWindow(window), &[DataKey::kontrol(Aksi::Skrip(kode), 0.0)], &OpsiPlayback::default());
+    Ok(())
+}
+
+// Variabel di header macro yang sedang dibuka, untuk form sebelum playback
+#[tauri::command]
+fn variabel_macro() -> Vec<variabel::Deklarasi> {
+    unsafe { variabel::deklarasi(&DATA_MACRO.lock().unwrap()) }
+}
+
+// Langkah WaitWindow yang disarankan dari judul window yang direkam bersama klik, disisipkan lewat tambah_aksi_kontrol
+#[tauri::command]
+fn saran_tunggu_window() -> Vec<alur::SaranTungguWindow> {
+    unsafe { alur::saran_tunggu_window(&DATA_MACRO.lock().unwrap()) }
+}
+
+#[tauri::command]
+fn hapus_aksi_kontrol(window: tauri::Window, index: usize) -> Result<(), String> {
+    unsafe {
+        let mut data_macro = DATA_MACRO.lock().unwrap();
+        match data_macro.get(index) {
+            Some(val) if alur::apakah_kontrol(&val.aksi) => {},
+            Some(..) => return Err(format!("Event {} bukan aksi kontrol", index)),
+            None => return Err(format!("Event {} tidak ada", index))
         }
+        data_macro.remove(index);
+
+        kirim_data_macro(&window, &data_macro);
+    }
+
+    pemulihan::tandai_berubah();
+    Ok(())
+}
+
+#[tauri::command]
+fn daftar_penanda() -> Vec<RawDataKey> {
+    unsafe {
+        let data_macro = DATA_MACRO.lock().unwrap();
+        data_macro.iter().enumerate().filter(|(_, val)| matches!(val.aksi, Aksi::Penanda)).map(|(id, val)| val.ke_raw(id)).collect()
     }
-    
+}
+
+// Memotong macro di penanda menjadi dua file, bagian kedua dimulai dari penandanya dengan waktu di-rebase ke 0
+#[tauri::command]
+fn pisah_di_penanda(nama: String, path_awal: String, path_akhir: String) -> Result<(), String> {
+    let data_macro_clone: Vec<DataKey> = unsafe { DATA_MACRO.lock().unwrap().clone() };
+    let index = cari_penanda(&data_macro_clone, &nama).ok_or(format!("Penanda {} tidak ada", nama))?;
+
+    let (bagian_awal, bagian_akhir) = data_macro_clone.split_at(index);
+    let mut bagian_akhir = bagian_akhir.to_vec();
+    rebase_waktu(&mut bagian_akhir);
+
+    format::tulis_macro(Path::new(&path_awal), bagian_awal, Format::dari_path(Path::new(&path_awal)))?;
+    format::tulis_macro(Path::new(&path_akhir), &bagian_akhir, Format::dari_path(Path::new(&path_akhir)))?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -387,22 +339,21 @@ fn mulai_record(window: tauri::Window) {
     }
 }
 
-#[tauri::command]
-async fn mainkan_recorder(window: tauri::Window) {
+// Memeriksa apakah playback bisa dimulai lalu menandai APAKAH_LAGI_MAIN.
+// Ok(None) kalau sedang record/main atau macro kosong, sama seperti tombol Play yang diam saja
+fn siapkan_playback(opsi: &OpsiPlayback) -> Result<Option<Vec<DataKey>>, String> {
     unsafe {
         if let Some(apakah_record) = APAKAH_RECORD {
             if apakah_record {
-                return
+                return Ok(None)
             }
         }
 
         if let Some(apakah_lagi_main) = APAKAH_LAGI_MAIN {
             if apakah_lagi_main {
-                return
+                return Ok(None)
             }
         }
-
-        APAKAH_LAGI_MAIN = Some(true);
     }
 
     let mut data_macro_clone: Vec<DataKey> = Vec::new();
@@ -414,46 +365,295 @@ async fn mainkan_recorder(window: tauri::Window) {
         }
     }
 
-    if data_macro_clone.len() <= 0 {
-        unsafe {
-            APAKAH_LAGI_MAIN = Some(false);
-        }
-        return;
+    if data_macro_clone.is_empty() {
+        return Ok(None);
     }
 
-    let waktu_di_tunggu = SystemTime::now();
-    for val in data_macro_clone.iter() {
-        let mut waktu_sekarang_tunggu = SystemTime::now();
-        while waktu_sekarang_tunggu.duration_since(waktu_di_tunggu).unwrap().as_secs_f32() < val.waktu {
-            waktu_sekarang_tunggu = SystemTime::now();
-        }
+    // Rentang dicek dulu sebelum mulai main supaya error bisa dikembalikan ke frontend
+    player::rentang_playback(&data_macro_clone, opsi)?;
 
-        if matches!(val.event_type, EventType::ButtonPress(..))|| matches!(val.event_type, EventType::ButtonRelease(..)) {
-            let posisi_mos: Vec<&str> = val.value.split(", ").collect();
-            send(&EventType::MouseMove { x: posisi_mos[0].parse::<f64>().unwrap(), y: posisi_mos[1].parse::<f64>().unwrap() });
-        }
+    unsafe {
+        APAKAH_LAGI_MAIN = Some(true);
+    }
+
+    Ok(Some(data_macro_clone))
+}
+
+#[tauri::command]
+async fn mainkan_recorder(window: tauri::Window, opsi: Option<OpsiPlayback>) -> Result<(), String> {
+    let opsi = opsi.unwrap_or_default();
+    if let Some(data_macro_clone) = siapkan_playback(&opsi)? {
+        player::mainkan(&PengirimWindow(window), &data_macro_clone, &opsi);
+    }
 
-        send(&val.event_type);
+    Ok(())
+}
+
+#[tauri::command]
+fn perintah_debug(perintah: PerintahDebug) -> Result<(), String> {
+    if !player::kirim_perintah_debug(perintah) {
+        return Err(String::from("Tidak ada playback debug yang berjalan"));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn jeda_playback() -> Result<(), String> {
+    if !player::atur_jeda(true) {
+        return Err(String::from("Tidak ada playback yang berjalan"));
     }
 
-    window.emit_all("SelesaiRecording", true).unwrap();
+    Ok(())
+}
 
-    thread::sleep(Duration::from_millis(500));
-    unsafe {   
-        APAKAH_LAGI_MAIN = Some(false);
+#[tauri::command]
+fn lanjutkan_playback() -> Result<(), String> {
+    if !player::atur_jeda(false) {
+        return Err(String::from("Tidak ada playback yang berjalan"));
     }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn daftar_jadwal() -> Vec<Jadwal> {
+    jadwal::daftar()
+}
+
+#[tauri::command]
+fn tambah_jadwal(jadwal: Jadwal) -> Result<Jadwal, String> {
+    jadwal::tambah(jadwal)
+}
+
+#[tauri::command]
+fn ubah_jadwal(jadwal: Jadwal) -> Result<Jadwal, String> {
+    jadwal::ubah(jadwal)
+}
+
+#[tauri::command]
+fn hapus_jadwal(id: u64) -> Result<(), String> {
+    jadwal::hapus(id)
+}
+
+#[tauri::command]
+fn riwayat_jadwal() -> Vec<Riwayat> {
+    jadwal::riwayat()
+}
+
+#[tauri::command]
+fn daftar_pustaka() -> Result<Vec<MacroPustaka>, String> {
+    pustaka::daftar()
+}
+
+#[tauri::command]
+fn cari_pustaka(kata: String, tag: Option<Vec<String>>) -> Result<Vec<MacroPustaka>, String> {
+    pustaka::cari(&kata, &tag.unwrap_or_default())
+}
+
+// Macro yang sedang dibuka disimpan ke pustaka
+#[tauri::command]
+fn simpan_ke_pustaka(nama: String, deskripsi: Option<String>, tag: Option<Vec<String>>) -> Result<MacroPustaka, String> {
+    let data_macro_clone: Vec<DataKey> = unsafe { DATA_MACRO.lock().unwrap().clone() };
+    if data_macro_clone.is_empty() {
+        return Err(String::from("Belum ada input yang direkam"));
+    }
+
+    let macro_pustaka = pustaka::simpan(&nama, deskripsi.unwrap_or_default(), tag.unwrap_or_default(), &data_macro_clone)?;
+    pemulihan::tandai_tersimpan();
+    Ok(macro_pustaka)
+}
+
+#[tauri::command]
+fn buka_dari_pustaka(window: tauri::Window, file: String) -> Result<(), String> {
+    let path = pustaka::path_macro(&file)?;
+    buka_file(window, path.to_string_lossy().into_owned())
+}
+
+#[tauri::command]
+fn ubah_info_pustaka(file: String, deskripsi: String, tag: Vec<String>) -> Result<MacroPustaka, String> {
+    pustaka::ubah_info(&file, deskripsi, tag)
+}
+
+#[tauri::command]
+fn ganti_nama_pustaka(file: String, nama: String) -> Result<MacroPustaka, String> {
+    let info = pustaka::ganti_nama(&file, &nama)?;
+    pintasan::ganti_file(&file, Some(&info.file))?;
+    Ok(info)
+}
+
+#[tauri::command]
+fn duplikat_pustaka(file: String, nama: String) -> Result<MacroPustaka, String> {
+    pustaka::duplikat(&file, &nama)
+}
+
+#[tauri::command]
+fn hapus_pustaka(file: String) -> Result<(), String> {
+    pustaka::hapus(&file)?;
+    pintasan::ganti_file(&file, None)
+}
+
+#[tauri::command]
+fn daftar_pintasan() -> Vec<Pintasan> {
+    pintasan::daftar()
+}
+
+#[tauri::command]
+fn atur_pintasan(pintasan: Pintasan) -> Result<Pintasan, String> {
+    pintasan::atur(pintasan)
+}
+
+#[tauri::command]
+fn hapus_pintasan(tombol: String) -> Result<(), String> {
+    pintasan::hapus(&tombol)
+}
+
+// saat_bermain menentukan apa yang terjadi kalau macro lain sedang dimainkan
+fn mainkan_dari_pustaka(window: tauri::Window, file: String, saat_bermain: SaatBermain) {
+    thread::spawn(move || {
+        let path = pustaka::path_macro(&file);
+        let data_macro = match path.as_ref().map_err(Clone::clone).and_then(|path| format::baca_macro(path)) {
+            Ok(data_macro) if !data_macro.is_empty() => data_macro,
+            Ok(..) => return,
+            Err(e) => {
+                println!("Macro {} tidak bisa dimainkan: {}", file, e);
+                return;
+            }
+        };
+        // Diperiksa dulu supaya macro dengan variabel kosong tidak memicu MintaVariabel untuk macro yang sedang dibuka
+        let opsi = OpsiPlayback { file_macro: path.ok().map(|path| path.to_string_lossy().into_owned()), ..Default::default() };
+        if let Err(e) = player::rentang_playback(&data_macro, &opsi) {
+            println!("Macro {} tidak bisa dimainkan: {}", file, e);
+            return;
+        }
+
+        unsafe {
+            if APAKAH_LAGI_MAIN == Some(true) {
+                match saat_bermain {
+                    SaatBermain::Abaikan => return,
+                    SaatBermain::Hentikan => {
+                        APAKAH_LAGI_MAIN = Some(false);
+                        return;
+                    },
+                    SaatBermain::Ganti => {
+                        APAKAH_LAGI_MAIN = Some(false);
+                        player::tunggu_selesai();
+                    },
+                    SaatBermain::Antre => player::tunggu_selesai()
+                }
+            }
+
+            // Recording bisa saja dimulai selama menunggu
+            if APAKAH_RECORD == Some(true) {
+                return;
+            }
+            APAKAH_LAGI_MAIN = Some(true);
+        }
+
+        player::mainkan(&PengirimWindow(window), &data_macro, &opsi);
+    });
+}
+
+#[tauri::command]
+fn daftar_singkatan() -> Vec<Singkatan> {
+    singkatan::daftar()
+}
+
+#[tauri::command]
+fn atur_singkatan(singkatan: Singkatan) -> Result<Singkatan, String> {
+    singkatan::atur(singkatan)
+}
+
+#[tauri::command]
+fn hapus_singkatan(pemicu: String) -> Result<(), String> {
+    singkatan::hapus(&pemicu)
+}
+
+fn jalankan_singkatan(window: tauri::Window, singkatan: Singkatan, jumlah_hapus: usize) {
+    thread::spawn(move || match singkatan.aksi {
+        AksiSingkatan::Teks(teks) => singkatan::suntik(jumlah_hapus, &teks),
+        AksiSingkatan::Macro(file) => {
+            singkatan::suntik(jumlah_hapus, "");
+            mainkan_dari_pustaka(window, file, SaatBermain::Abaikan);
+        }
+    });
 }
 
 fn create_app_menu() -> Menu {
+    let daftar_terbaru = terbaru::daftar();
+    let mut menu_terbaru = Menu::new();
+    for index in 0..terbaru::MAKS_TERBARU {
+        let item = CustomMenuItem::new(format!("terbaru_{}", index), judul_menu_terbaru(daftar_terbaru.get(index)));
+        menu_terbaru = menu_terbaru.add_item(if index < daftar_terbaru.len() { item } else { item.disabled() });
+    }
+    menu_terbaru = menu_terbaru.add_item(CustomMenuItem::new("terbaru_kosongkan".to_string(), "Clear Recent"));
+
     return Menu::new()
         .add_submenu(Submenu::new("File", Menu::new()
             .add_item(CustomMenuItem::new("simpan".to_string(), "Save"))
             .add_item(CustomMenuItem::new("buka".to_string(), "Load"))
+            .add_submenu(Submenu::new("Open Recent", menu_terbaru))
         ));
 }
 
+fn buka_file_terakhir() {
+    if !pengaturan::baca(|pengaturan| pengaturan.buka_macro_terakhir) {
+        return;
+    }
+
+    if let Some(path) = terbaru::terakhir() {
+        match format::baca_macro(Path::new(&path)) {
+            // Isinya baru dikirim ke frontend di on_page_load, waktu listener di halaman sudah siap
+            Ok(data_macro_baru) => unsafe {
+                *DATA_MACRO.lock().unwrap() = data_macro_baru;
+            },
+            Err(e) => println!("File terakhir tidak bisa dibuka: {}", e)
+        }
+    }
+}
+
+fn jalankan_websocket() {
+    let mut pengaturan = pengaturan::sekarang();
+    if !pengaturan.websocket.aktif {
+        return;
+    }
+
+    if pengaturan.websocket.token.is_empty() {
+        pengaturan.websocket.token = pengaturan::buat_token();
+        pengaturan = match pengaturan::perbarui(pengaturan) {
+            Ok(pengaturan) => pengaturan,
+            Err(e) => {
+                println!("Token websocket tidak bisa disimpan: {}", e);
+                return;
+            }
+        };
+    }
+
+    if let Err(e) = websocket::jalankan(&pengaturan.websocket) {
+        println!("Server websocket tidak bisa dijalankan: {}", e);
+    }
+}
+
+#[tauri::command]
+fn get_settings() -> Pengaturan {
+    pengaturan::sekarang()
+}
+
+#[tauri::command]
+fn update_settings(window: tauri::Window, pengaturan: Pengaturan) -> Result<Pengaturan, String> {
+    let pengaturan = pengaturan::perbarui(pengaturan)?;
+    kirim(&window, "PengaturanBerubah", &pengaturan);
+    Ok(pengaturan)
+}
+
 fn setup(app: &mut App) -> Result<(), Box<(dyn std::error::Error + 'static)>> { // Not entirely sure, but perhaps you could omit that error type  
     let main_window = app.get_window("main").unwrap().clone();
+    rpc::jalankan_jika_diaktifkan(main_window.clone());
+    jalankan_websocket();
+    jadwal::jalankan(PengirimWindow(main_window.clone()));
+    buka_file_terakhir();
+    perbarui_menu_terbaru(&main_window);
+    pemulihan::jalankan_autosave();
     let window_mutex = Arc::new(Mutex::new(main_window));
     let data_macro_murtex = unsafe { DATA_MACRO.clone() };
 
@@ -464,9 +664,43 @@ fn setup(app: &mut App) -> Result<(), Box<(dyn std::error::Error + 'static)>> {
             let mut data_macro = data_macro_murtex.lock().unwrap();
             //Idea: Memberi opsi untuk me-record mouse movement dan mengubah value button press/release menjadi posisi mouse
             
+            let tombol = pengaturan::baca(|pengaturan| pengaturan.tombol);
+            // Status tombol untuk kondisi "key" di macro
+            alur::catat_tombol(&event.event_type);
             unsafe {
+                let hasil_pintasan = pintasan::proses_event(&event.event_type);
+                if let Some(pintasan) = hasil_pintasan.pintasan {
+                    if APAKAH_RECORD == Some(true) {
+                        // Pintasan tidak memainkan macro selama recording, modifier-nya yang sudah terlanjur terekam dibuang
+                        let jumlah = data_macro.len();
+                        for key in hasil_pintasan.modifier_ditekan {
+                            if let Some(index) = data_macro.iter().rposition(|val| matches!(val.aksi, Aksi::Input(EventType::KeyPress(ditekan)) if ditekan == key)) {
+                                data_macro.remove(index);
+                            }
+                        }
+                        if data_macro.len() != jumlah {
+                            kirim_data_macro(&window, &data_macro);
+                            if let Err(e) = pemulihan::tulis_ulang(&data_macro) {
+                                println!("Jurnal tidak bisa ditulis ulang: {}", e);
+                            }
+                        }
+                    } else {
+                        mainkan_dari_pustaka(window.clone(), pintasan.file, pintasan.saat_bermain);
+                    }
+                }
+                if hasil_pintasan.telan {
+                    return;
+                }
+
+                // Input dari macro yang sedang dimainkan tidak boleh memicu singkatan
+                if APAKAH_RECORD == Some(true) || APAKAH_LAGI_MAIN == Some(true) {
+                    singkatan::kosongkan();
+                } else if let Some((singkatan, jumlah_hapus)) = singkatan::proses_event(&event.event_type, event.name.as_deref()) {
+                    jalankan_singkatan(window.clone(), singkatan, jumlah_hapus);
+                }
+
                 if let EventType::KeyRelease(key) = event.event_type {
-                    if key == Key::F6 {
+                    if key == tombol.record {
                         if let Some(apakah_lagi_main) = APAKAH_LAGI_MAIN {
                             if apakah_lagi_main {
                                 return
@@ -478,17 +712,18 @@ fn setup(app: &mut App) -> Result<(), Box<(dyn std::error::Error + 'static)>> {
                             APAKAH_RECORD = Some(apakah_record);
                             if apakah_record {
                                 data_macro.clear();
-                                WAKTU_MULAI_RECORD = once_cell::sync::Lazy::new(|| SystemTime::now());
+                                recorder::mulai_waktu_record();
+                                pemulihan::mulai_jurnal();
                             } else {
+                                pemulihan::selesai_jurnal();
                                 // for val in data_macro.iter() {
                                 //     println!("{} {} {}", val.tipe, val.value, val.waktu);
                                 // }
                             }
 
-                            window.emit_all("StatusRecorder", &apakah_record).unwrap();
+                            kirim(&window, "StatusRecorder", &apakah_record);
                         }
-                    } else if key == Key::F7  {
-                        println!("TEKAN F7!");
+                    } else if key == tombol.mainkan {
                         //KITA HARUS STOP PLAYING KETIKA USER KLIK LAGI
                         if let Some(apakah_record) = APAKAH_RECORD {
                             if apakah_record {
@@ -503,7 +738,14 @@ fn setup(app: &mut App) -> Result<(), Box<(dyn std::error::Error + 'static)>> {
                             }
                         }
                 
-                        if data_macro.len() <= 0 {
+                        if data_macro.is_empty() {
+                            return;
+                        }
+
+                        // Hotkey tidak membawa nilai variabel, frontend menanyakannya lalu memanggil mainkan_recorder
+                        let kurang = variabel::belum_diisi(&data_macro, &HashMap::new());
+                        if !kurang.is_empty() {
+                            kirim(&window, "MintaVariabel", &kurang);
                             return;
                         }
 
@@ -511,80 +753,25 @@ fn setup(app: &mut App) -> Result<(), Box<(dyn std::error::Error + 'static)>> {
                         let window_clone = window.clone();
                         thread::spawn(move || {
                             APAKAH_LAGI_MAIN = Some(true);
-                            
-                            let waktu_di_tunggu = SystemTime::now();
-                            for val in data_macro_clone.iter() {
-                                if let Some(apakah_lagi_main) = APAKAH_LAGI_MAIN {
-                                    if !apakah_lagi_main {
-                                        break
-                                    }
-                                }
-
-                                let mut waktu_sekarang_tunggu = SystemTime::now();
-                                while waktu_sekarang_tunggu.duration_since(waktu_di_tunggu).unwrap().as_secs_f32() < val.waktu {
-                                    waktu_sekarang_tunggu = SystemTime::now();
-                                }
-
-                                if matches!(val.event_type, EventType::ButtonPress(..))|| matches!(val.event_type, EventType::ButtonRelease(..)) {
-                                    let posisi_mos: Vec<&str> = val.value.split(", ").collect();
-                                    send(&EventType::MouseMove { x: posisi_mos[0].parse::<f64>().unwrap(), y: posisi_mos[1].parse::<f64>().unwrap() });
-                                }
-
-                                send(&val.event_type);
-                            }
-
-                            window_clone.emit_all("SelesaiRecording", true).unwrap();
-
-                            thread::sleep(Duration::from_millis(500));
-                            APAKAH_LAGI_MAIN = Some(false);
+                            player::mainkan(&PengirimWindow(window_clone), &data_macro_clone, &OpsiPlayback::default());
                         });
-                    } else {
-                        if let Some(apakah_record) = APAKAH_RECORD {
-                            if !apakah_record {
-                                return;
-                            }
-
-                            let waktu_sekarang = SystemTime::now();
-                            let selisih_waktu = match waktu_sekarang.duration_since(*WAKTU_MULAI_RECORD) {
-                                Ok(durasi) =>  durasi.as_secs_f32(),
-                                Err(..) => 0.0
-                            };
-
-                            let data_key = RawDataKey { tipe: UpdateEventType(event.event_type).to_string(), value: UpdateKey(&key).to_string(), waktu: selisih_waktu };
-                            window.emit_all("KirimDataInput", &data_key).unwrap();
-                            data_macro.push(DataKey { event_type: event.event_type, waktu: selisih_waktu, value: UpdateKey(&key).to_string() });
-                        }
+                    } else if key == tombol.jeda && APAKAH_LAGI_MAIN == Some(true) {
+                        player::atur_jeda(player::APAKAH_DIJEDA != Some(true));
+                    } else if (key == tombol.step || key == tombol.lanjut) && APAKAH_LAGI_MAIN == Some(true) {
+                        // step (F8) maju satu event, lanjut (F9) sampai breakpoint berikutnya
+                        player::kirim_perintah_debug(if key == tombol.step { PerintahDebug::Step } else { PerintahDebug::Lanjut });
                     }
                 }
 
                 if let Some(apakah_record) = APAKAH_RECORD {
-                    if matches!(event.event_type, EventType::MouseMove { .. }) || !apakah_record {
+                    if !apakah_record || matches!(event.event_type, EventType::KeyRelease(key) if key == tombol.record || key == tombol.mainkan) {
                        return; 
                     }
 
-                    let waktu_sekarang = SystemTime::now();
-                    let selisih_waktu = match waktu_sekarang.duration_since(*WAKTU_MULAI_RECORD) {
-                        Ok(durasi) =>  durasi.as_secs_f32(),
-                        Err(..) => 0.0
-                    };
-
-                    if matches!(event.event_type, EventType::ButtonPress(..)) || matches!(event.event_type, EventType::ButtonRelease(..)) {
-                        let posisi_mos = enigo.mouse_location();
-                        let data_key = RawDataKey { tipe: UpdateEventType(event.event_type).to_string(), value: format!("{}, {}", posisi_mos.0, posisi_mos.1), waktu: selisih_waktu };
-                        window.emit_all("KirimDataInput", &data_key).unwrap();
-                        data_macro.push(DataKey { event_type: event.event_type, waktu: selisih_waktu, value: format!("{}, {}", posisi_mos.0, posisi_mos.1) });
-                    }
-
-                    if let EventType::Wheel { delta_x, delta_y } = event.event_type {
-                        let data_key = RawDataKey { tipe: UpdateEventType(event.event_type).to_string(), value: format!("{}, {}", delta_x, delta_y), waktu: selisih_waktu };
-                        window.emit_all("KirimDataInput", &data_key).unwrap();
-                        data_macro.push(DataKey { event_type: event.event_type, waktu: selisih_waktu, value: format!("{}, {}", delta_x, delta_y) });
-                    }
-    
-                    if let EventType::KeyPress(key) = event.event_type {
-                        let data_key = RawDataKey { tipe: UpdateEventType(event.event_type).to_string(), value: UpdateKey(&key).to_string(), waktu: selisih_waktu };
-                        window.emit_all("KirimDataInput", &data_key).unwrap();
-                        data_macro.push(DataKey { event_type: event.event_type, waktu: selisih_waktu, value: UpdateKey(&key).to_string() });
+                    if let Some(data_key) = recorder::rekam_event(event.event_type, recorder::selisih_waktu(), &enigo) {
+                        kirim(&window, "KirimDataInput", &data_key.ke_raw(data_macro.len()));
+                        pemulihan::catat_event(&data_key);
+                        data_macro.push(data_key);
                     }
                 }
             }
@@ -600,11 +787,33 @@ fn setup(app: &mut App) -> Result<(), Box<(dyn std::error::Error + 'static)>> {
 fn main() {
     tauri::Builder::default()
         .setup(setup)
-        .invoke_handler(tauri::generate_handler![mulai_record, mainkan_recorder, simpan_file, buka_file])
+        .invoke_handler(tauri::generate_handler![mulai_record, mainkan_recorder, simpan_file, buka_file, atur_label, tambah_penanda, hapus_penanda, tambah_aksi_kontrol, hapus_aksi_kontrol, jalankan_skrip, variabel_macro, saran_tunggu_window, daftar_penanda, pisah_di_penanda, perintah_debug, jeda_playback, lanjutkan_playback, daftar_jadwal, tambah_jadwal, ubah_jadwal, hapus_jadwal, riwayat_jadwal, daftar_pustaka, cari_pustaka, simpan_ke_pustaka, buka_dari_pustaka, ubah_info_pustaka, ganti_nama_pustaka, duplikat_pustaka, hapus_pustaka, daftar_pintasan, atur_pintasan, hapus_pintasan, daftar_singkatan, atur_singkatan, hapus_singkatan, get_settings, update_settings, daftar_file_terbaru, kosongkan_file_terbaru, sesi_belum_disimpan, pulihkan_sesi, buang_sesi])
+        .on_page_load(|window, _| {
+            let data_macro = unsafe { DATA_MACRO.lock().unwrap().clone() };
+            kirim_semua_input(&window, &data_macro);
+            // Sesi recording yang belum disimpan waktu aplikasi terakhir ditutup
+            if let Some(info) = pemulihan::tawarkan_sekali() {
+                kirim(&window, "SesiBelumDisimpan", &info);
+            }
+        })
         .menu(create_app_menu())
         .on_menu_event(move | event | {
             println!("{subject}", subject=event.menu_item_id());
             match event.menu_item_id() {
+                "terbaru_kosongkan" => {
+                    terbaru::kosongkan();
+                    perbarui_menu_terbaru(event.window());
+                }
+                id if id.starts_with("terbaru_") => {
+                    let path = id["terbaru_".len()..].parse::<usize>().ok().and_then(terbaru::ambil);
+                    if let Some(path) = path {
+                        if let Err(e) = buka_file(event.window().clone(), path) {
+                            println!("File tidak bisa dibuka: {}", e);
+                            // File yang hilang dibuang dari menu
+                            perbarui_menu_terbaru(event.window());
+                        }
+                    }
+                }
                 "simpan" => {
                     event.window().emit_all("DapatinSimpananFile", true).unwrap();
                 }
@@ -0,0 +1,257 @@
+// Playback yang lebih mirip manusia: waktu setiap event input digeser acak (Gaussian, dibatasi), posisi klik digeser di dalam radius,
+// dan kursor bergerak melengkung (kurva Bézier) ke posisi klik dengan lama gerak menurut hukum Fitts, bukan langsung pindah.
+// Gerakan dimulai lebih awal supaya kliknya tetap tepat waktu. Dengan seed yang sama hasil acaknya selalu sama, jadi playback bisa diulang di tes.
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpsiManusiawi {
+    // Simpangan baku geser waktu setiap event input, 0 berarti waktunya tidak digeser
+    pub jitter_ms: f32,
+    // Geser waktu tidak pernah lebih dari ini, ke depan maupun ke belakang
+    pub maks_jitter_ms: f32,
+    // Posisi klik digeser acak paling jauh sejauh ini (piksel)
+    pub radius_klik: f64,
+    pub gerak_melengkung: bool,
+    // 2.0 berarti kursor bergerak dua kali lebih cepat dari perkiraan gerak tangan
+    pub kecepatan_mouse: f32,
+    pub seed: Option<u64>
+}
+
+impl Default for OpsiManusiawi {
+    fn default() -> Self {
+        OpsiManusiawi { jitter_ms: 25.0, maks_jitter_ms: 80.0, radius_klik: 3.0, gerak_melengkung: true, kecepatan_mouse: 1.0, seed: None }
+    }
+}
+
+impl OpsiManusiawi {
+    pub fn periksa(&self) -> Result<(), String> {
+        if !(self.jitter_ms.is_finite() && self.jitter_ms >= 0.0 && self.maks_jitter_ms.is_finite() && self.maks_jitter_ms >= 0.0) {
+            return Err(String::from("Jitter waktu tidak boleh negatif"));
+        }
+        if !(self.radius_klik.is_finite() && self.radius_klik >= 0.0) {
+            return Err(String::from("Radius klik tidak boleh negatif"));
+        }
+        if !(self.kecepatan_mouse.is_finite() && self.kecepatan_mouse > 0.0) {
+            return Err(String::from("Kecepatan mouse harus lebih dari 0"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TitikGerak {
+    pub x: f64,
+    pub y: f64,
+    // Detik sejak gerakan dimulai
+    pub waktu: f32
+}
+
+// Jarak antar titik gerakan
+const INTERVAL_GERAK: f64 = 0.01;
+// Jarak ke event input sebelumnya paling banyak berubah sebesar ini karena geser waktu
+const BAGIAN_JARAK_INPUT: f32 = 0.3;
+
+pub struct Manusiawi {
+    opsi: OpsiManusiawi,
+    rng: StdRng
+}
+
+impl Manusiawi {
+    pub fn baru(opsi: &OpsiManusiawi) -> Manusiawi {
+        let rng = match opsi.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy()
+        };
+        Manusiawi { opsi: opsi.clone(), rng }
+    }
+
+    // Untuk macro yang dimainkan dari Call atau play(), seed-nya diambil dari acakan ini supaya tidak mengulang urutan acak yang sama
+    pub fn turunan(&mut self) -> Manusiawi {
+        Manusiawi { opsi: self.opsi.clone(), rng: StdRng::seed_from_u64(self.rng.gen()) }
+    }
+
+    // Distribusi normal baku dengan Box-Muller
+    fn normal(&mut self) -> f64 {
+        let u1: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        let u2: f64 = self.rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+    }
+
+    // Detik, bisa negatif
+    pub fn geser_waktu(&mut self) -> f32 {
+        if self.opsi.jitter_ms <= 0.0 {
+            return 0.0;
+        }
+        let maks = self.opsi.maks_jitter_ms.max(0.0) as f64;
+        ((self.normal() * self.opsi.jitter_ms as f64).clamp(-maks, maks) / 1000.0) as f32
+    }
+
+    // Geser waktu event input yang berjarak `jarak` detik dari event input sebelumnya, yang sudah digeser `geser_sebelumnya`.
+    // Jarak keduanya tetap dalam ±30% jarak aslinya, jadi tekan-lepas yang cepat dan klik ganda tidak rusak atau tertukar
+    pub fn geser_waktu_setelah(&mut self, jarak: f32, geser_sebelumnya: f32) -> f32 {
+        let batas = jarak.max(0.0) * BAGIAN_JARAK_INPUT;
+        self.geser_waktu().clamp(geser_sebelumnya - batas, geser_sebelumnya + batas)
+    }
+
+    // Titik acak yang tersebar rata di dalam lingkaran, dibulatkan ke piksel
+    pub fn geser_klik(&mut self, (x, y): (f64, f64)) -> (f64, f64) {
+        if self.opsi.radius_klik <= 0.0 {
+            return (x, y);
+        }
+        let jarak = self.opsi.radius_klik * self.rng.gen::<f64>().sqrt();
+        let sudut = self.rng.gen::<f64>() * std::f64::consts::TAU;
+        ((x + jarak * sudut.cos()).round(), (y + jarak * sudut.sin()).round())
+    }
+
+    // Titik-titik gerakan kursor, yang terakhir tepat di tujuan. Kosong kalau kursor langsung dipindah
+    pub fn rencana_gerak(&mut self, dari: (f64, f64), ke: (f64, f64)) -> Vec<TitikGerak> {
+        let (dx, dy) = (ke.0 - dari.0, ke.1 - dari.1);
+        let jarak = dx.hypot(dy);
+        if !self.opsi.gerak_melengkung || jarak < 1.0 || self.opsi.kecepatan_mouse <= 0.0 {
+            return Vec::new();
+        }
+
+        // Hukum Fitts dengan target selebar 20 piksel, lalu divariasikan sedikit
+        let variasi = (1.0 + 0.15 * self.normal()).clamp(0.7, 1.3);
+        let durasi = ((0.1 + 0.1 * (1.0 + jarak / 20.0).log2()) * variasi / self.opsi.kecepatan_mouse as f64).max(0.05);
+
+        // Dua titik kendali di sisi yang kurang lebih sama, supaya lengkungnya halus seperti gerak pergelangan tangan
+        let (tegak_x, tegak_y) = (-dy / jarak, dx / jarak);
+        let batas_lengkung = jarak * 0.3;
+        let lengkung_1 = (self.normal() * jarak * 0.12).clamp(-batas_lengkung, batas_lengkung);
+        let lengkung_2 = (lengkung_1 * 0.6 + self.normal() * jarak * 0.04).clamp(-batas_lengkung, batas_lengkung);
+        let kendali_1 = (dari.0 + dx * 0.3 + tegak_x * lengkung_1, dari.1 + dy * 0.3 + tegak_y * lengkung_1);
+        let kendali_2 = (dari.0 + dx * 0.7 + tegak_x * lengkung_2, dari.1 + dy * 0.7 + tegak_y * lengkung_2);
+
+        let jumlah = ((durasi / INTERVAL_GERAK).ceil() as usize).max(2);
+        (1..=jumlah).map(|ke_berapa| {
+            let waktu = ke_berapa as f64 / jumlah as f64;
+            // Profil minimum-jerk: pelan di awal dan akhir, cepat di tengah
+            let t = waktu.powi(3) * (10.0 - 15.0 * waktu + 6.0 * waktu.powi(2));
+            let s = 1.0 - t;
+            let titik = |a: f64, b: f64, c: f64, d: f64| s.powi(3) * a + 3.0 * s.powi(2) * t * b + 3.0 * s * t.powi(2) * c + t.powi(3) * d;
+            let (x, y) = if ke_berapa == jumlah {
+                ke
+            } else {
+                (titik(dari.0, kendali_1.0, kendali_2.0, ke.0), titik(dari.1, kendali_1.1, kendali_2.1, ke.1))
+            };
+            TitikGerak { x, y, waktu: (waktu * durasi) as f32 }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opsi(seed: u64) -> OpsiManusiawi {
+        OpsiManusiawi { seed: Some(seed), ..Default::default() }
+    }
+
+    // Beberapa hasil dari setiap jenis acakan, urutannya sama dengan yang dipakai player
+    fn urutan(manusiawi: &mut Manusiawi) -> (Vec<f32>, Vec<(f64, f64)>, Vec<TitikGerak>) {
+        let waktu = (0..20).map(|_| manusiawi.geser_waktu()).collect();
+        let klik = (0..20).map(|_| manusiawi.geser_klik((100.0, 200.0))).collect();
+        let gerak = manusiawi.rencana_gerak((0.0, 0.0), (640.0, 360.0));
+        (waktu, klik, gerak)
+    }
+
+    #[test]
+    fn seed_sama_hasil_sama() {
+        let hasil = urutan(&mut Manusiawi::baru(&opsi(42)));
+        assert_eq!(urutan(&mut Manusiawi::baru(&opsi(42))), hasil);
+        assert_ne!(urutan(&mut Manusiawi::baru(&opsi(43))), hasil);
+
+        // Turunan dari seed yang sama juga sama, tetapi urutannya berbeda dari induknya dan dari turunan berikutnya
+        let (mut induk, mut induk_lain) = (Manusiawi::baru(&opsi(42)), Manusiawi::baru(&opsi(42)));
+        let turunan = urutan(&mut induk.turunan());
+        assert_eq!(urutan(&mut induk_lain.turunan()), turunan);
+        assert_ne!(turunan, hasil);
+        assert_ne!(urutan(&mut induk.turunan()), turunan);
+    }
+
+    #[test]
+    fn geser_waktu_dalam_batas() {
+        let mut manusiawi = Manusiawi::baru(&OpsiManusiawi { jitter_ms: 60.0, maks_jitter_ms: 80.0, ..opsi(7) });
+        let geser: Vec<f32> = (0..2000).map(|_| manusiawi.geser_waktu()).collect();
+        assert!(geser.iter().all(|geser| geser.abs() <= 0.080));
+        // Simpangan bakunya 60 ms, jadi sebagian pasti kena batas dan hasilnya tidak selalu sama
+        assert!(geser.iter().any(|geser| geser.abs() == 0.080));
+        assert!(geser.iter().any(|geser| *geser > 0.0) && geser.iter().any(|geser| *geser < 0.0));
+
+        let mut tanpa_jitter = Manusiawi::baru(&OpsiManusiawi { jitter_ms: 0.0, ..opsi(7) });
+        assert!((0..100).all(|_| tanpa_jitter.geser_waktu() == 0.0));
+    }
+
+    #[test]
+    fn geser_waktu_menjaga_jarak_input() {
+        let mut manusiawi = Manusiawi::baru(&OpsiManusiawi { jitter_ms: 200.0, maks_jitter_ms: 500.0, ..opsi(3) });
+        let mut geser_sebelumnya = manusiawi.geser_waktu();
+        for jarak in [0.05, 0.0, 0.12, 2.0, 0.03, -1.0].repeat(50) {
+            let geser = manusiawi.geser_waktu_setelah(jarak, geser_sebelumnya);
+            let batas = f32::max(jarak, 0.0) * 0.3 + 1e-6;
+            assert!((geser - geser_sebelumnya).abs() <= batas, "jarak {} geser {} sebelumnya {}", jarak, geser, geser_sebelumnya);
+            assert!(geser.abs() <= 0.5 + 1e-6);
+            geser_sebelumnya = geser;
+        }
+    }
+
+    #[test]
+    fn geser_klik_dalam_radius() {
+        let mut manusiawi = Manusiawi::baru(&OpsiManusiawi { radius_klik: 5.0, ..opsi(11) });
+        let klik: Vec<(f64, f64)> = (0..2000).map(|_| manusiawi.geser_klik((100.0, 200.0))).collect();
+        // Dibulatkan ke piksel, jadi bisa lebih sedikit dari setengah piksel di luar lingkaran
+        assert!(klik.iter().all(|(x, y)| (x - 100.0).hypot(y - 200.0) <= 5.0 + std::f64::consts::FRAC_1_SQRT_2));
+        assert!(klik.iter().all(|(x, y)| x.fract() == 0.0 && y.fract() == 0.0));
+        assert!(klik.iter().any(|posisi| *posisi != (100.0, 200.0)));
+
+        let mut tanpa_radius = Manusiawi::baru(&OpsiManusiawi { radius_klik: 0.0, ..opsi(11) });
+        assert_eq!(tanpa_radius.geser_klik((100.5, 200.5)), (100.5, 200.5));
+    }
+
+    #[test]
+    fn rencana_gerak_berakhir_di_tujuan() {
+        let mut manusiawi = Manusiawi::baru(&opsi(5));
+        for (dari, ke) in [((0.0, 0.0), (640.0, 360.0)), ((500.0, 500.0), (480.0, 510.0)), ((10.0, 900.0), (1900.0, 20.0))] {
+            let rencana = manusiawi.rencana_gerak(dari, ke);
+            assert!(rencana.len() >= 2);
+            let akhir = rencana.last().unwrap();
+            assert_eq!((akhir.x, akhir.y), ke);
+            assert!(rencana[0].waktu > 0.0);
+            assert!(rencana.windows(2).all(|pasangan| pasangan[1].waktu > pasangan[0].waktu));
+            // Lengkungnya tidak melenceng jauh dari garis lurus
+            let jarak = (ke.0 - dari.0).hypot(ke.1 - dari.1);
+            assert!(rencana.iter().all(|titik| (titik.x - dari.0).hypot(titik.y - dari.1) <= jarak * 1.5));
+        }
+
+        // Jarak di bawah satu piksel atau gerak_melengkung mati berarti kursor langsung dipindah
+        assert!(manusiawi.rencana_gerak((10.0, 10.0), (10.5, 10.0)).is_empty());
+        let mut lurus = Manusiawi::baru(&OpsiManusiawi { gerak_melengkung: false, ..opsi(5) });
+        assert!(lurus.rencana_gerak((0.0, 0.0), (640.0, 360.0)).is_empty());
+    }
+
+    #[test]
+    fn kecepatan_mouse_mempercepat_gerak() {
+        let durasi = |kecepatan_mouse: f32| {
+            let rencana = Manusiawi::baru(&OpsiManusiawi { kecepatan_mouse, ..opsi(9) }).rencana_gerak((0.0, 0.0), (800.0, 0.0));
+            rencana.last().unwrap().waktu
+        };
+        assert!((durasi(1.0) / durasi(2.0) - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn periksa_opsi() {
+        assert!(OpsiManusiawi::default().periksa().is_ok());
+        for salah in [
+            OpsiManusiawi { jitter_ms: -1.0, ..Default::default() },
+            OpsiManusiawi { maks_jitter_ms: f32::NAN, ..Default::default() },
+            OpsiManusiawi { radius_klik: -0.5, ..Default::default() },
+            OpsiManusiawi { kecepatan_mouse: 0.0, ..Default::default() }
+        ] {
+            assert!(salah.periksa().is_err(), "{:?}", salah);
+        }
+    }
+}
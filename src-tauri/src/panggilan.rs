@@ -0,0 +1,253 @@
+// Aksi Call: memainkan macro lain lalu melanjutkan macro ini, misalnya "Call|||login.rf?user={user}&tunggu=500".
+// Nama tanpa folder adalah id (nama file) di pustaka, path dengan folder dicari dari folder macro yang memanggil
+// (atau folder pustaka kalau macro-nya tidak dibuka dari file). Argumen menjadi variabel di macro yang dipanggil.
+// Semua macro yang dipanggil dibaca sebelum playback dimulai, jadi file yang hilang dan panggilan yang berputar langsung ketahuan.
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+use crate::event::{Aksi, DataKey};
+use crate::{alur, format, pustaka, variabel};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Panggilan {
+    pub file: String,
+    // Nilainya boleh berisi {nama} dari macro yang memanggil
+    pub argumen: Vec<(String, String)>
+}
+
+impl FromStr for Panggilan {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (file, argumen) = s.split_once('?').unwrap_or((s, ""));
+        let file = file.trim();
+        if file.is_empty() {
+            return Err(String::from("Call butuh nama macro"));
+        }
+
+        let mut hasil = Vec::new();
+        for pasangan in argumen.split('&').filter(|pasangan| !pasangan.is_empty()) {
+            let (nama, nilai) = variabel::parse_pasangan(pasangan)?;
+            variabel::periksa(&nilai)?;
+            hasil.push((nama, nilai));
+        }
+
+        Ok(Panggilan { file: file.to_string(), argumen: hasil })
+    }
+}
+
+impl fmt::Display for Panggilan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        for (urutan, (nama, nilai)) in self.argumen.iter().enumerate() {
+            write!(f, "{}{}={}", if urutan == 0 { '?' } else { '&' }, nama, nilai)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MacroDipanggil {
+    pub data_macro: Vec<DataKey>,
+    // Path macro yang dipanggil oleh setiap Call di macro ini, key-nya index Call
+    pub tujuan: HashMap<usize, PathBuf>
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DaftarPanggilan {
+    pub tujuan: HashMap<usize, PathBuf>,
+    pub macro_dipanggil: HashMap<PathBuf, MacroDipanggil>
+}
+
+fn cari_path(file: &str, folder: Option<&Path>) -> Result<PathBuf, String> {
+    let path = Path::new(file);
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else if file.contains(['/', '\\']) {
+        match folder {
+            Some(folder) => folder.join(path),
+            None => pustaka::direktori()?.join(path)
+        }
+    } else {
+        pustaka::path_macro(file)?
+    };
+
+    path.canonicalize().map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn nama_pendek(path: &Path) -> String {
+    path.file_name().map(|nama| nama.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string())
+}
+
+// jalur berisi macro yang sedang dibaca dari atas sampai macro ini, untuk mengenali panggilan yang berputar
+fn muat_isi(data_macro: &[DataKey], folder: Option<&Path>, jalur: &mut Vec<PathBuf>, daftar: &mut HashMap<PathBuf, MacroDipanggil>, kesalahan: &mut Vec<String>) -> HashMap<usize, PathBuf> {
+    let mut tujuan = HashMap::new();
+    let asal = jalur.last().map(|path| nama_pendek(path)).unwrap_or(String::from("macro ini"));
+
+    for (index, val) in data_macro.iter().enumerate() {
+        let Aksi::Panggil(panggilan) = &val.aksi else {
+            continue
+        };
+
+        let path = match cari_path(&panggilan.file, folder) {
+            Ok(path) => path,
+            Err(e) => {
+                kesalahan.push(format!("{}, Call di event {}: {}", asal, index, e));
+                continue
+            }
+        };
+        if let Some(posisi) = jalur.iter().position(|val| *val == path) {
+            let mut putaran: Vec<String> = jalur[posisi..].iter().map(|val| nama_pendek(val)).collect();
+            putaran.push(nama_pendek(&path));
+            kesalahan.push(format!("{}, Call di event {} berputar: {}", asal, index, putaran.join(" → ")));
+            continue
+        }
+        tujuan.insert(index, path.clone());
+
+        let isi = match daftar.get(&path) {
+            Some(dipanggil) => dipanggil.data_macro.clone(),
+            None => {
+                let isi = match format::baca_macro(&path).and_then(|isi| alur::susun(&isi, 0, isi.len()).map(|_| isi)) {
+                    Ok(isi) => isi,
+                    Err(e) => {
+                        kesalahan.push(format!("{}: {}", nama_pendek(&path), e));
+                        continue
+                    }
+                };
+
+                jalur.push(path.clone());
+                let tujuan_isi = muat_isi(&isi, path.parent(), jalur, daftar, kesalahan);
+                jalur.pop();
+                daftar.insert(path.clone(), MacroDipanggil { data_macro: isi.clone(), tujuan: tujuan_isi });
+                isi
+            }
+        };
+
+        // Variabel tanpa nilai bawaan harus diisi lewat argumen
+        let argumen: HashMap<String, String> = panggilan.argumen.iter().cloned().collect();
+        let kurang: Vec<String> = variabel::belum_diisi(&isi, &argumen).into_iter().map(|val| val.nama).collect();
+        if !kurang.is_empty() {
+            kesalahan.push(format!("{}, Call di event {}: {} butuh argumen {}", asal, index, nama_pendek(&path), kurang.join(", ")));
+        }
+    }
+
+    tujuan
+}
+
+// path adalah file macro yang dimainkan kalau ada, supaya macro yang memanggil dirinya sendiri juga ketahuan
+pub fn muat(data_macro: &[DataKey], path: Option<&Path>) -> Result<DaftarPanggilan, String> {
+    // Folder tetap dipakai walaupun file-nya belum ada, misalnya macro yang belum disimpan lagi
+    let folder = path.and_then(Path::parent).map(|folder| folder.canonicalize().unwrap_or(folder.to_path_buf()));
+    let mut jalur: Vec<PathBuf> = path.and_then(|path| path.canonicalize().ok()).into_iter().collect();
+    let mut macro_dipanggil = HashMap::new();
+    let mut kesalahan = Vec::new();
+
+    let tujuan = muat_isi(data_macro, folder.as_deref(), &mut jalur, &mut macro_dipanggil, &mut kesalahan);
+    if !kesalahan.is_empty() {
+        return Err(kesalahan.join("\n"));
+    }
+
+    Ok(DaftarPanggilan { tujuan, macro_dipanggil })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // Folder sementara berisi file macro, dihapus waktu tes selesai
+    struct FolderTes(PathBuf);
+
+    impl FolderTes {
+        fn baru(nama: &str, file: &[(&str, &str)]) -> FolderTes {
+            let folder = std::env::temp_dir().join(format!("recordflow-panggilan-{}-{}", nama, std::process::id()));
+            let _ = fs::remove_dir_all(&folder);
+            fs::create_dir_all(&folder).unwrap();
+            for (nama, isi) in file {
+                fs::write(folder.join(nama), isi).unwrap();
+            }
+            FolderTes(folder)
+        }
+
+        fn muat(&self, nama: &str) -> Result<DaftarPanggilan, String> {
+            let path = self.0.join(nama);
+            muat(&format::baca_macro(&path).unwrap(), Some(&path))
+        }
+    }
+
+    impl Drop for FolderTes {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn panggilan_parse_dan_tulis() {
+        let panggilan: Panggilan = "login.rf?user={user}&password=rahasia=123".parse().unwrap();
+        assert_eq!(panggilan.file, "login.rf");
+        assert_eq!(panggilan.argumen, [(String::from("user"), String::from("{user}")), (String::from("password"), String::from("rahasia=123"))]);
+        assert_eq!(panggilan.to_string(), "login.rf?user={user}&password=rahasia=123");
+
+        let tanpa_argumen: Panggilan = " ./bersama/keluar.rf ".parse().unwrap();
+        assert_eq!(tanpa_argumen, Panggilan { file: String::from("./bersama/keluar.rf"), argumen: Vec::new() });
+        assert_eq!(tanpa_argumen.to_string(), "./bersama/keluar.rf");
+        assert!("login.rf?".parse::<Panggilan>().unwrap().argumen.is_empty());
+    }
+
+    #[test]
+    fn panggilan_tidak_valid() {
+        assert_eq!("".parse::<Panggilan>().unwrap_err(), "Call butuh nama macro");
+        assert_eq!("?user=a".parse::<Panggilan>().unwrap_err(), "Call butuh nama macro");
+        assert!("login.rf?user".parse::<Panggilan>().is_err());
+        assert!("login.rf?nama lengkap=a".parse::<Panggilan>().is_err());
+        assert!("login.rf?user={user".parse::<Panggilan>().is_err());
+    }
+
+    #[test]
+    fn muat_macro_bersarang() {
+        let folder = FolderTes::baru("bersarang", &[
+            ("utama.rf", "Call|||./a.rf|||0\nCall|||./b.rf|||1"),
+            ("a.rf", "Call|||./dasar.rf?user=a|||0"),
+            ("b.rf", "Call|||./dasar.rf?user=b|||0"),
+            ("dasar.rf", "Var|||user|||0\nType|||{user}|||0")
+        ]);
+        let daftar = folder.muat("utama.rf").unwrap();
+        let path = |nama: &str| folder.0.join(nama).canonicalize().unwrap();
+        assert_eq!(daftar.tujuan, HashMap::from([(0, path("a.rf")), (1, path("b.rf"))]));
+        // dasar.rf dipanggil dua kali tapi hanya dibaca sekali, dan bukan panggilan yang berputar
+        assert_eq!(daftar.macro_dipanggil.len(), 3);
+        assert_eq!(daftar.macro_dipanggil[&path("a.rf")].tujuan, HashMap::from([(0, path("dasar.rf"))]));
+    }
+
+    #[test]
+    fn muat_panggilan_berputar() {
+        let folder = FolderTes::baru("berputar", &[
+            ("a.rf", "Call|||./b.rf|||0"),
+            ("b.rf", "Call|||./c.rf|||0"),
+            ("c.rf", "Call|||./a.rf|||0"),
+            ("sendiri.rf", "Call|||./sendiri.rf|||0")
+        ]);
+        assert_eq!(folder.muat("a.rf").unwrap_err(), "c.rf, Call di event 0 berputar: a.rf → b.rf → c.rf → a.rf");
+        assert_eq!(folder.muat("sendiri.rf").unwrap_err(), "sendiri.rf, Call di event 0 berputar: sendiri.rf → sendiri.rf");
+    }
+
+    #[test]
+    fn muat_file_dan_argumen_kurang() {
+        let folder = FolderTes::baru("kurang", &[
+            ("utama.rf", "Call|||./hilang.rf|||0\nCall|||./login.rf|||1\nCall|||./login.rf?user=budi|||2"),
+            ("login.rf", "Var|||user|||0\nVar|||tunggu=500|||0\nType|||{user}|||0"),
+            ("rusak.rf", "Call|||./blok.rf|||0"),
+            ("blok.rf", "Repeat|||2|||0")
+        ]);
+        let kesalahan = folder.muat("utama.rf").unwrap_err();
+        let baris: Vec<&str> = kesalahan.lines().collect();
+        assert_eq!(baris.len(), 2);
+        assert!(baris[0].starts_with("utama.rf, Call di event 0: "));
+        assert_eq!(baris[1], "utama.rf, Call di event 1: login.rf butuh argumen user");
+
+        assert_eq!(folder.muat("rusak.rf").unwrap_err(), "blok.rf: Repeat di event 0 tidak ditutup dengan End");
+    }
+}
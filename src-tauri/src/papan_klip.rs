@@ -0,0 +1,84 @@
+// Aksi clipboard: ClipboardSet mengisi clipboard, Paste mengisi lalu menekan Ctrl+V (Cmd+V di macOS), ClipboardGet menyimpan isinya ke variabel.
+// Teks panjang lebih cepat di-paste daripada diketik satu per satu dan tidak bergantung layout keyboard.
+// Isi clipboard pengguna disimpan sebelum playback dan dikembalikan setelahnya kalau pengaturan pulihkan_clipboard aktif (hanya teks).
+// Clipboard diakses lewat PenyediaPapanKlip, defaultnya clipboard sistem. PapanKlipMemori dipakai untuk tes tanpa display.
+use std::sync::{Arc, Mutex, RwLock};
+use enigo::{Enigo, Key, KeyboardControllable};
+use once_cell::sync::Lazy;
+
+use crate::event::Aksi;
+
+pub trait PenyediaPapanKlip: Send + Sync {
+    fn baca(&self) -> Result<String, String>;
+    fn tulis(&self, teks: &str) -> Result<(), String>;
+}
+
+// Di Linux isi clipboard hilang kalau Clipboard-nya di-drop, jadi satu instance disimpan selama aplikasi berjalan
+struct PapanKlipSistem {
+    clipboard: Mutex<Option<arboard::Clipboard>>
+}
+
+impl PapanKlipSistem {
+    fn pakai<T>(&self, f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>) -> Result<T, String> {
+        let mut clipboard = self.clipboard.lock().unwrap();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().map_err(|e| format!("Clipboard tidak bisa dibuka: {}", e))?);
+        }
+        clipboard.as_mut().map(f).unwrap().map_err(|e| format!("Clipboard: {}", e))
+    }
+}
+
+impl PenyediaPapanKlip for PapanKlipSistem {
+    fn baca(&self) -> Result<String, String> {
+        self.pakai(|clipboard| clipboard.get_text())
+    }
+
+    fn tulis(&self, teks: &str) -> Result<(), String> {
+        self.pakai(|clipboard| clipboard.set_text(teks))
+    }
+}
+
+#[derive(Default)]
+pub struct PapanKlipMemori {
+    isi: Mutex<Option<String>>
+}
+
+impl PenyediaPapanKlip for PapanKlipMemori {
+    fn baca(&self) -> Result<String, String> {
+        self.isi.lock().unwrap().clone().ok_or(String::from("Clipboard kosong"))
+    }
+
+    fn tulis(&self, teks: &str) -> Result<(), String> {
+        *self.isi.lock().unwrap() = Some(teks.to_string());
+        Ok(())
+    }
+}
+
+static PENYEDIA: Lazy<RwLock<Arc<dyn PenyediaPapanKlip>>> = Lazy::new(|| RwLock::new(Arc::new(PapanKlipSistem { clipboard: Mutex::new(None) })));
+
+pub fn atur_penyedia(penyedia: Arc<dyn PenyediaPapanKlip>) {
+    *PENYEDIA.write().unwrap() = penyedia;
+}
+
+pub fn baca() -> Result<String, String> {
+    let penyedia = PENYEDIA.read().unwrap().clone();
+    penyedia.baca()
+}
+
+pub fn tulis(teks: &str) -> Result<(), String> {
+    let penyedia = PENYEDIA.read().unwrap().clone();
+    penyedia.tulis(teks)
+}
+
+// Menekan shortcut paste di window yang aktif. Memakai huruf v sesuai layout, bukan posisi tombolnya
+pub fn tekan_paste() {
+    let pengubah = if cfg!(target_os = "macos") { Key::Meta } else { Key::Control };
+    let mut enigo = Enigo::new();
+    enigo.key_down(pengubah);
+    enigo.key_click(Key::Layout('v'));
+    enigo.key_up(pengubah);
+}
+
+pub fn apakah_aksi_papan_klip(aksi: &Aksi) -> bool {
+    matches!(aksi, Aksi::AturPapanKlip(..) | Aksi::Tempel(..) | Aksi::BacaPapanKlip(..))
+}
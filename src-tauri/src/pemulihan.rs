@@ -0,0 +1,224 @@
+// Jurnal pemulihan: macro yang belum disimpan ditulis ke jurnal.rf di folder config.
+// Selama recording setiap event langsung ditambahkan ke jurnal, di luar recording macro yang diubah ditulis ulang oleh autosave.
+// Jurnal dihapus setelah macro disimpan, jadi jurnal yang masih ada waktu aplikasi dibuka berarti sesi sebelumnya belum disimpan.
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::ptr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::event::{durasi_macro, DataKey};
+use crate::format;
+use crate::{pengaturan, APAKAH_RECORD, DATA_MACRO};
+
+const NAMA_FILE: &str = "jurnal.rf";
+
+#[derive(Default)]
+struct StatusJurnal {
+    // Terbuka selama recording berjalan
+    file: Option<File>,
+    // Macro diubah setelah terakhir kali ditulis ke jurnal
+    berubah: bool,
+    sudah_ditawarkan: bool
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InfoSesi {
+    pub jumlah_event: usize,
+    pub durasi: f32,
+    // Unix detik
+    pub diubah: u64
+}
+
+static STATUS_JURNAL: Lazy<Mutex<StatusJurnal>> = Lazy::new(|| Mutex::new(StatusJurnal::default()));
+
+fn path_jurnal() -> Result<PathBuf, String> {
+    #[cfg(not(test))]
+    let direktori = pengaturan::direktori_config().ok_or(String::from("Folder config tidak ditemukan"))?;
+    // Tes tidak boleh menimpa jurnal pengguna
+    #[cfg(test)]
+    let direktori = std::env::temp_dir().join(format!("recordflow-jurnal-{}", std::process::id()));
+    fs::create_dir_all(&direktori).map_err(|e| e.to_string())?;
+    Ok(direktori.join(NAMA_FILE))
+}
+
+fn buka_untuk_ditambah(path: &PathBuf) -> Result<File, String> {
+    OpenOptions::new().create(true).append(true).open(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Dipanggil waktu recording dimulai, isi jurnal lama dibuang seperti DATA_MACRO
+pub fn mulai_jurnal() {
+    let hasil = path_jurnal().and_then(|path| File::create(&path).map_err(|e| format!("{}: {}", path.display(), e)));
+    let mut status = STATUS_JURNAL.lock().unwrap();
+    status.berubah = false;
+    status.file = match hasil {
+        Ok(file) => Some(file),
+        Err(e) => {
+            println!("Jurnal recording tidak bisa dibuat: {}", e);
+            None
+        }
+    };
+}
+
+pub fn catat_event(val: &DataKey) {
+    let mut status = STATUS_JURNAL.lock().unwrap();
+    if let Some(file) = status.file.as_mut() {
+        if let Err(e) = file.write_all(format::serialize_macro(std::slice::from_ref(val)).as_bytes()) {
+            println!("Event tidak bisa ditulis ke jurnal: {}", e);
+        }
+    }
+}
+
+// Jurnal tetap disimpan sampai macro hasil recording disimpan
+pub fn selesai_jurnal() {
+    STATUS_JURNAL.lock().unwrap().file = None;
+}
+
+// Menulis ulang seluruh jurnal, misalnya setelah event yang sudah terekam dibuang
+pub fn tulis_ulang(data_macro: &[DataKey]) -> Result<(), String> {
+    let path = path_jurnal()?;
+    let mut status = STATUS_JURNAL.lock().unwrap();
+    fs::write(&path, format::serialize_macro(data_macro)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if status.file.is_some() {
+        status.file = Some(buka_untuk_ditambah(&path)?);
+    }
+    status.berubah = false;
+
+    Ok(())
+}
+
+pub fn tandai_berubah() {
+    STATUS_JURNAL.lock().unwrap().berubah = true;
+}
+
+// Macro sudah disimpan ke file atau diganti dengan isi file, jurnalnya tidak dibutuhkan lagi
+pub fn tandai_tersimpan() {
+    let mut status = STATUS_JURNAL.lock().unwrap();
+    status.berubah = false;
+    if status.file.is_none() {
+        if let Ok(path) = path_jurnal() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+pub fn sesi_tersisa() -> Option<InfoSesi> {
+    let path = path_jurnal().ok()?;
+    let data_macro = format::baca_macro(&path).ok().filter(|data_macro| !data_macro.is_empty())?;
+    let diubah = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()
+        .and_then(|waktu| waktu.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|durasi| durasi.as_secs())
+        .unwrap_or(0);
+
+    Some(InfoSesi { jumlah_event: data_macro.len(), durasi: durasi_macro(&data_macro), diubah })
+}
+
+// Sesi sebelumnya hanya ditawarkan sekali per aplikasi dibuka, bukan setiap halaman dimuat ulang
+pub fn tawarkan_sekali() -> Option<InfoSesi> {
+    {
+        let mut status = STATUS_JURNAL.lock().unwrap();
+        if status.sudah_ditawarkan || status.file.is_some() {
+            return None;
+        }
+        status.sudah_ditawarkan = true;
+    }
+
+    sesi_tersisa()
+}
+
+// Jurnal tidak dihapus waktu dipulihkan, isinya tetap belum disimpan ke file
+pub fn pulihkan() -> Result<Vec<DataKey>, String> {
+    let path = path_jurnal()?;
+    if !path.is_file() {
+        return Err(String::from("Tidak ada sesi yang bisa dipulihkan"));
+    }
+
+    format::baca_macro(&path)
+}
+
+pub fn buang() -> Result<(), String> {
+    let path = path_jurnal()?;
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("{}: {}", path.display(), e)),
+        _ => Ok(())
+    }
+}
+
+// Menulis macro yang berubah ke jurnal setiap autosave_detik, 0 berarti autosave mati
+pub fn jalankan_autosave() {
+    thread::spawn(|| {
+        let mut terakhir = Instant::now();
+        loop {
+            thread::sleep(Duration::from_secs(1));
+
+            let interval = pengaturan::baca(|pengaturan| pengaturan.autosave_detik);
+            if interval == 0 || terakhir.elapsed() < Duration::from_secs(interval) {
+                continue
+            }
+            terakhir = Instant::now();
+
+            // Selama recording jurnal sudah ditambah per event
+            let apakah_record = unsafe { APAKAH_RECORD.unwrap_or(false) };
+            if apakah_record || !STATUS_JURNAL.lock().unwrap().berubah {
+                continue
+            }
+
+            // Lewat pointer supaya tidak membuat referensi ke static mut
+            let data_macro = unsafe { (*ptr::addr_of!(DATA_MACRO)).clone() };
+            let data_macro = data_macro.lock().unwrap().clone();
+            if let Err(e) = tulis_ulang(&data_macro) {
+                println!("Autosave gagal: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn teks(data_macro: &[DataKey]) -> String {
+        format::serialize_macro(data_macro)
+    }
+
+    #[test]
+    fn jurnal_dipulihkan() {
+        let data_macro = format::parse_macro("Key Press KeyA|||KeyA|||0\nKey Release KeyA|||KeyA|||0.5\nMarker|||selesai|||2");
+
+        // Event ditulis langsung selama recording, jadi tetap ada walaupun aplikasi mati sebelum recording selesai
+        mulai_jurnal();
+        for val in data_macro.iter().take(2) {
+            catat_event(val);
+        }
+        assert_eq!(teks(&pulihkan().unwrap()), teks(&data_macro[..2]));
+        assert!(tawarkan_sekali().is_none());
+
+        catat_event(&data_macro[2]);
+        selesai_jurnal();
+        let info = sesi_tersisa().unwrap();
+        assert_eq!((info.jumlah_event, info.durasi), (3, 2.0));
+        assert_eq!(teks(&pulihkan().unwrap()), teks(&data_macro));
+
+        // Hanya ditawarkan sekali
+        assert_eq!(tawarkan_sekali().map(|info| info.jumlah_event), Some(3));
+        assert!(tawarkan_sekali().is_none());
+
+        // Autosave menulis ulang seluruh macro
+        tandai_berubah();
+        tulis_ulang(&data_macro[1..]).unwrap();
+        assert!(!STATUS_JURNAL.lock().unwrap().berubah);
+        assert_eq!(teks(&pulihkan().unwrap()), teks(&data_macro[1..]));
+
+        // Setelah disimpan tidak ada lagi yang bisa dipulihkan
+        tandai_tersimpan();
+        assert!(sesi_tersisa().is_none());
+        assert_eq!(pulihkan().unwrap_err(), "Tidak ada sesi yang bisa dipulihkan");
+        assert!(buang().is_ok());
+
+        let _ = fs::remove_dir_all(path_jurnal().unwrap().parent().unwrap());
+    }
+}
@@ -0,0 +1,311 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rdev::Key;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::manusiawi::OpsiManusiawi;
+
+const NAMA_APLIKASI: &str = "record_flow";
+const NAMA_FILE: &str = "pengaturan.json";
+// Naikkan setiap kali bentuk Pengaturan berubah, lalu tambahkan langkahnya di migrasi()
+pub const VERSI_PENGATURAN: u64 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PengaturanWebsocket {
+    pub aktif: bool,
+    pub port: u16,
+    // Klien harus mengirim token ini di query string (?token=...), dibuat otomatis kalau masih kosong
+    pub token: String
+}
+
+impl Default for PengaturanWebsocket {
+    fn default() -> Self {
+        PengaturanWebsocket { aktif: false, port: 7878, token: String::new() }
+    }
+}
+
+// Batas aksi Script supaya skrip yang macet tidak menahan playback selamanya. 0 berarti tanpa batas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PengaturanSkrip {
+    pub maks_operasi: u64,
+    // Lama wait() dan play() di dalam skrip tidak dihitung
+    pub maks_detik: u64
+}
+
+impl Default for PengaturanSkrip {
+    fn default() -> Self {
+        PengaturanSkrip { maks_operasi: 1_000_000, maks_detik: 30 }
+    }
+}
+
+// Tombol global recorder, ditulis dengan nama yang sama seperti di file macro ("F6", "Pause")
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PengaturanTombol {
+    pub record: Key,
+    pub mainkan: Key,
+    pub jeda: Key,
+    pub step: Key,
+    pub lanjut: Key
+}
+
+impl Default for PengaturanTombol {
+    fn default() -> Self {
+        PengaturanTombol { record: Key::F6, mainkan: Key::F7, jeda: Key::Pause, step: Key::F8, lanjut: Key::F9 }
+    }
+}
+
+impl PengaturanTombol {
+    pub fn semua(&self) -> [Key; 5] {
+        [self.record, self.mainkan, self.jeda, self.step, self.lanjut]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pengaturan {
+    pub versi: u64,
+    pub tombol: PengaturanTombol,
+    // Jeda setelah playback selesai sebelum recorder bisa dipakai lagi
+    pub jeda_setelah_main_ms: u64,
+    pub rekam_gerakan_mouse: bool,
+    // Menyimpan judul window aktif di setiap klik, dipakai untuk menyarankan langkah WaitWindow (hanya Linux/X11)
+    pub rekam_judul_window: bool,
+    // Dipakai kalau opsi playback tidak menyebutkan kecepatan
+    pub kecepatan_default: f32,
+    // Membuka file macro terakhir waktu aplikasi dijalankan
+    pub buka_macro_terakhir: bool,
+    // Interval autosave macro yang belum disimpan ke jurnal pemulihan, 0 berarti mati
+    pub autosave_detik: u64,
+    pub skrip: PengaturanSkrip,
+    // Isi clipboard dikembalikan setelah playback macro yang memakai ClipboardSet, Paste atau ClipboardGet
+    pub pulihkan_clipboard: bool,
+    // Aksi Run menjalankan program apa saja dengan hak akses aplikasi, jadi harus diaktifkan sendiri oleh pengguna
+    pub izinkan_perintah: bool,
+    // Opsi manusiawi untuk playback yang tidak menyebutkannya sendiri, None berarti playback persis seperti rekamannya
+    pub manusiawi: Option<OpsiManusiawi>,
+    // Perubahan websocket baru berlaku setelah aplikasi dibuka ulang
+    pub websocket: PengaturanWebsocket
+}
+
+impl Default for Pengaturan {
+    fn default() -> Self {
+        Pengaturan {
+            versi: VERSI_PENGATURAN,
+            tombol: PengaturanTombol::default(),
+            jeda_setelah_main_ms: 500,
+            rekam_gerakan_mouse: false,
+            rekam_judul_window: false,
+            kecepatan_default: 1.0,
+            buka_macro_terakhir: false,
+            autosave_detik: 60,
+            skrip: PengaturanSkrip::default(),
+            pulihkan_clipboard: true,
+            izinkan_perintah: false,
+            manusiawi: None,
+            websocket: PengaturanWebsocket::default()
+        }
+    }
+}
+
+static PENGATURAN: Lazy<RwLock<Pengaturan>> = Lazy::new(|| RwLock::new(muat()));
+
+// Folder config per platform, tanpa bergantung ke Tauri supaya CLI juga bisa memakainya
+pub fn direktori_config() -> Option<PathBuf> {
+    let env_path = |nama: &str| std::env::var_os(nama).filter(|val| !val.is_empty()).map(PathBuf::from);
+
+    let dasar = if cfg!(target_os = "windows") {
+        env_path("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_path("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_path("XDG_CONFIG_HOME").or_else(|| env_path("HOME").map(|home| home.join(".config")))
+    };
+
+    dasar.map(|dasar| dasar.join(NAMA_APLIKASI))
+}
+
+pub fn path_pengaturan() -> Option<PathBuf> {
+    direktori_config().map(|direktori| direktori.join(NAMA_FILE))
+}
+
+// File yang tidak ada atau rusak diganti dengan nilai default
+pub fn muat_json<T: DeserializeOwned + Default>(nama_file: &str) -> T {
+    let contents = match direktori_config().and_then(|direktori| fs::read_to_string(direktori.join(nama_file)).ok()) {
+        Some(contents) => contents,
+        None => return T::default()
+    };
+
+    match serde_json::from_str(&contents) {
+        Ok(isi) => isi,
+        Err(e) => {
+            println!("{} tidak bisa dibaca, memakai default: {}", nama_file, e);
+            T::default()
+        }
+    }
+}
+
+pub fn simpan_json<T: Serialize>(nama_file: &str, isi: &T) -> Result<(), String> {
+    let direktori = direktori_config().ok_or(String::from("Folder config tidak ditemukan"))?;
+    fs::create_dir_all(&direktori).map_err(|e| e.to_string())?;
+
+    let path = direktori.join(nama_file);
+    let contents = serde_json::to_string_pretty(isi).map_err(|e| e.to_string())?;
+    fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// File sebelum ada field versi dianggap versi 1
+fn migrasi(mut isi: Value) -> Value {
+    let versi = isi.get("versi").and_then(|versi| versi.as_u64()).unwrap_or(1);
+    // 1 -> 2: versi 1 hanya berisi websocket, field baru cukup diisi default oleh serde.
+    // Langkah berikutnya ditulis sebagai `if versi < 3 { ... }` supaya file dari versi berapa pun ikut dinaikkan
+    if versi < VERSI_PENGATURAN {
+        if let Some(objek) = isi.as_object_mut() {
+            objek.insert(String::from("versi"), Value::from(VERSI_PENGATURAN));
+        }
+    }
+
+    isi
+}
+
+pub fn muat() -> Pengaturan {
+    let isi: Value = muat_json(NAMA_FILE);
+    if isi.is_null() {
+        return Pengaturan::default();
+    }
+
+    let versi_file = isi.get("versi").and_then(|versi| versi.as_u64());
+    let pengaturan: Pengaturan = match serde_json::from_value(migrasi(isi)) {
+        Ok(pengaturan) => pengaturan,
+        Err(e) => {
+            println!("{} tidak bisa dibaca, memakai default: {}", NAMA_FILE, e);
+            return Pengaturan::default();
+        }
+    };
+
+    // File lama langsung ditulis ulang dalam bentuk baru
+    if versi_file != Some(pengaturan.versi) {
+        if let Err(e) = simpan(&pengaturan) {
+            println!("Pengaturan hasil migrasi tidak bisa disimpan: {}", e);
+        }
+    }
+    pengaturan
+}
+
+pub fn simpan(pengaturan: &Pengaturan) -> Result<(), String> {
+    simpan_json(NAMA_FILE, pengaturan)
+}
+
+// Token 64 karakter hex dari 32 byte acak OS
+pub fn buat_token() -> String {
+    let mut byte = [0u8; 32];
+    OsRng.fill_bytes(&mut byte);
+    byte.iter().map(|val| format!("{:02x}", val)).collect()
+}
+
+fn periksa(pengaturan: &Pengaturan) -> Result<(), String> {
+    if !(pengaturan.kecepatan_default.is_finite() && pengaturan.kecepatan_default > 0.0) {
+        return Err(String::from("Kecepatan default harus lebih dari 0"));
+    }
+    if let Some(manusiawi) = &pengaturan.manusiawi {
+        manusiawi.periksa()?;
+    }
+    if pengaturan.websocket.port == 0 {
+        return Err(String::from("Port websocket tidak valid"));
+    }
+
+    let tombol = pengaturan.tombol.semua();
+    for (index, key) in tombol.iter().enumerate() {
+        if tombol[..index].contains(key) {
+            return Err(format!("Tombol {:?} dipakai lebih dari sekali", key));
+        }
+    }
+
+    Ok(())
+}
+
+// Pengaturan yang sedang dipakai, dimuat dari file waktu pertama kali dibutuhkan
+pub fn sekarang() -> Pengaturan {
+    PENGATURAN.read().unwrap().clone()
+}
+
+// Untuk yang dipanggil di setiap event (listener), supaya tidak perlu meng-clone semua pengaturan
+pub fn baca<T>(ambil: impl FnOnce(&Pengaturan) -> T) -> T {
+    ambil(&PENGATURAN.read().unwrap())
+}
+
+// Mengganti pengaturan yang sedang dipakai tanpa menulis file
+#[cfg(test)]
+pub(crate) fn atur_sementara(ubah: impl FnOnce(&mut Pengaturan)) {
+    ubah(&mut PENGATURAN.write().unwrap());
+}
+
+// Disimpan ke file lalu langsung dipakai, tanpa perlu membuka ulang aplikasi
+pub fn perbarui(mut baru: Pengaturan) -> Result<Pengaturan, String> {
+    periksa(&baru)?;
+
+    let mut pengaturan = PENGATURAN.write().unwrap();
+    baru.versi = VERSI_PENGATURAN;
+    // Frontend tidak perlu tahu token untuk mengubah pengaturan lain
+    if baru.websocket.token.is_empty() {
+        baru.websocket.token = pengaturan.websocket.token.clone();
+    }
+
+    simpan(&baru)?;
+    *pengaturan = baru.clone();
+    Ok(baru)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_acak() {
+        let token = buat_token();
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|val| val.is_ascii_hexdigit()));
+        assert_ne!(token, buat_token());
+    }
+
+    #[test]
+    fn migrasi_dari_versi_lama() {
+        // Versi 1 belum punya field versi dan hanya berisi websocket
+        let isi = migrasi(serde_json::json!({ "websocket": { "aktif": true, "port": 9000, "token": "abc" } }));
+        assert_eq!(isi["versi"], VERSI_PENGATURAN);
+        let pengaturan: Pengaturan = serde_json::from_value(isi).unwrap();
+        assert_eq!((pengaturan.websocket.aktif, pengaturan.websocket.port, pengaturan.websocket.token.as_str()), (true, 9000, "abc"));
+        assert_eq!(pengaturan.tombol.semua(), PengaturanTombol::default().semua());
+        assert_eq!((pengaturan.autosave_detik, pengaturan.pulihkan_clipboard, pengaturan.izinkan_perintah), (60, true, false));
+
+        // File versi sekarang tidak diubah
+        let isi = serde_json::json!({ "versi": VERSI_PENGATURAN, "kecepatan_default": 2.0, "tombol": { "record": "F2" } });
+        assert_eq!(migrasi(isi.clone()), isi);
+        let pengaturan: Pengaturan = serde_json::from_value(isi).unwrap();
+        assert_eq!((pengaturan.kecepatan_default, pengaturan.tombol.record, pengaturan.tombol.mainkan), (2.0, Key::F2, Key::F7));
+    }
+
+    #[test]
+    fn periksa_pengaturan() {
+        assert!(periksa(&Pengaturan::default()).is_ok());
+
+        let salah = |ubah: fn(&mut Pengaturan)| {
+            let mut pengaturan = Pengaturan::default();
+            ubah(&mut pengaturan);
+            periksa(&pengaturan).unwrap_err()
+        };
+        assert_eq!(salah(|pengaturan| pengaturan.kecepatan_default = 0.0), "Kecepatan default harus lebih dari 0");
+        assert_eq!(salah(|pengaturan| pengaturan.kecepatan_default = f32::NAN), "Kecepatan default harus lebih dari 0");
+        assert_eq!(salah(|pengaturan| pengaturan.websocket.port = 0), "Port websocket tidak valid");
+        assert_eq!(salah(|pengaturan| pengaturan.tombol.lanjut = Key::F6), "Tombol F6 dipakai lebih dari sekali");
+        assert_eq!(salah(|pengaturan| pengaturan.manusiawi = Some(OpsiManusiawi { radius_klik: -1.0, ..Default::default() })), "Radius klik tidak boleh negatif");
+    }
+}
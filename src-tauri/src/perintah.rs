@@ -0,0 +1,436 @@
+// Aksi Run: menjalankan program lain di tengah macro, misalnya "Run|||cmd=./ambil-token.sh --user {user}; stdout=token; timeout=5000".
+// Program dijalankan langsung tanpa shell, {nama} diisi per argumen setelah cmd dipisah, jadi nilai variabel tidak bisa menambah argumen.
+// Kode keluar disimpan ke {exit_code} (atau nama dari exit_code=), stdout dan stderr ke variabel kalau disebutkan.
+// Harus diaktifkan dulu dengan pengaturan izinkan_perintah, macro yang berisi Run tidak mau dimainkan kalau belum.
+use std::fmt;
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::{pengaturan, variabel};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Perintah {
+    pub program: String,
+    pub argumen: Vec<String>,
+    // Folder kerja, default folder kerja aplikasi
+    pub folder: Option<String>,
+    pub env: Vec<(String, String)>,
+    // 0 berarti tanpa batas
+    pub batas_ms: u64,
+    // Nama variabel untuk stdout dan stderr, baris baru di akhir dibuang
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub kode_keluar: String,
+    // on_error=continue: kode keluar selain 0 tidak menghentikan playback, macro bisa memeriksa {exit_code} dengan If
+    pub lanjut_saat_gagal: bool
+}
+
+// Memisah di pemisah yang tidak berada di dalam tanda kutip
+fn pisah_di_luar_kutip(s: &str, pemisah: char) -> Vec<String> {
+    let mut hasil = Vec::new();
+    let mut sekarang = String::new();
+    let mut dalam_kutip = false;
+    let mut karakter = s.chars();
+    while let Some(c) = karakter.next() {
+        match c {
+            '\\' if dalam_kutip => {
+                sekarang.push(c);
+                if let Some(berikutnya) = karakter.next() {
+                    sekarang.push(berikutnya);
+                }
+            },
+            '"' => {
+                dalam_kutip = !dalam_kutip;
+                sekarang.push(c);
+            },
+            _ if c == pemisah && !dalam_kutip => hasil.push(std::mem::take(&mut sekarang)),
+            _ => sekarang.push(c)
+        }
+    }
+    hasil.push(sekarang);
+    hasil
+}
+
+// Memecah cmd menjadi argumen seperti shell sederhana: dipisah spasi, "…" untuk argumen berisi spasi, \" dan \\ di dalam kutip
+pub fn pecah_argumen(cmd: &str) -> Result<Vec<String>, String> {
+    let mut hasil = Vec::new();
+    let mut sekarang: Option<String> = None;
+    let mut dalam_kutip = false;
+    let mut karakter = cmd.chars();
+    while let Some(c) = karakter.next() {
+        match c {
+            '"' => {
+                dalam_kutip = !dalam_kutip;
+                sekarang.get_or_insert_with(String::new);
+            },
+            '\\' if dalam_kutip => match karakter.next() {
+                Some(berikutnya @ ('"' | '\\')) => sekarang.get_or_insert_with(String::new).push(berikutnya),
+                Some(berikutnya) => sekarang.get_or_insert_with(String::new).extend(['\\', berikutnya]),
+                None => sekarang.get_or_insert_with(String::new).push('\\')
+            },
+            _ if c.is_whitespace() && !dalam_kutip => hasil.extend(sekarang.take()),
+            _ => sekarang.get_or_insert_with(String::new).push(c)
+        }
+    }
+    if dalam_kutip {
+        return Err(format!("Tanda kutip di {} tidak ditutup", cmd));
+    }
+    hasil.extend(sekarang);
+    Ok(hasil)
+}
+
+fn kutip(argumen: &str) -> String {
+    if !argumen.is_empty() && !argumen.contains(|c: char| c.is_whitespace() || c == '"' || c == ';') {
+        return argumen.to_string();
+    }
+    format!("\"{}\"", argumen.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl FromStr for Perintah {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cmd = None;
+        let mut perintah = Perintah {
+            program: String::new(),
+            argumen: Vec::new(),
+            folder: None,
+            env: Vec::new(),
+            batas_ms: 30_000,
+            stdout: None,
+            stderr: None,
+            kode_keluar: String::from("exit_code"),
+            lanjut_saat_gagal: false
+        };
+        let nama_variabel = |kunci: &str, nilai: &str| {
+            if variabel::apakah_nama_valid(nilai) { Ok(nilai.to_string()) } else { Err(format!("{} {} bukan nama variabel yang valid", kunci, nilai)) }
+        };
+
+        for bagian in pisah_di_luar_kutip(s, ';').iter().map(|bagian| bagian.trim()).filter(|bagian| !bagian.is_empty()) {
+            let (kunci, nilai) = bagian.split_once('=').map(|(kunci, nilai)| (kunci.trim(), nilai.trim())).ok_or(format!("{} harus berbentuk <kunci>=<nilai>", bagian))?;
+            match kunci {
+                "cmd" => cmd = Some(pecah_argumen(nilai)?),
+                "cwd" => perintah.folder = Some(nilai.to_string()),
+                "env" => perintah.env.push(variabel::parse_pasangan(nilai).map_err(|_| format!("env {} harus berbentuk <NAMA>=<nilai>", nilai))?),
+                "timeout" => perintah.batas_ms = nilai.parse().map_err(|_| format!("timeout {} bukan angka", nilai))?,
+                "stdout" => perintah.stdout = Some(nama_variabel(kunci, nilai)?),
+                "stderr" => perintah.stderr = Some(nama_variabel(kunci, nilai)?),
+                "exit_code" => perintah.kode_keluar = nama_variabel(kunci, nilai)?,
+                "on_error" => perintah.lanjut_saat_gagal = match nilai {
+                    "fail" => false,
+                    "continue" => true,
+                    _ => return Err(format!("on_error {} harus fail atau continue", nilai))
+                },
+                _ => return Err(format!("{} tidak dikenal di Run", kunci))
+            }
+        }
+
+        let mut cmd = cmd.unwrap_or_default().into_iter();
+        perintah.program = cmd.next().ok_or(String::from("Run butuh cmd"))?;
+        perintah.argumen = cmd.collect();
+        for teks in [&perintah.program].into_iter().chain(perintah.argumen.iter()).chain(perintah.folder.iter()).chain(perintah.env.iter().map(|(_, nilai)| nilai)) {
+            variabel::periksa(teks)?;
+        }
+        Ok(perintah)
+    }
+}
+
+impl fmt::Display for Perintah {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cmd: Vec<String> = [&self.program].into_iter().chain(self.argumen.iter()).map(|val| kutip(val)).collect();
+        let mut bagian = vec![format!("cmd={}", cmd.join(" "))];
+        if let Some(folder) = &self.folder {
+            bagian.push(format!("cwd={}", folder));
+        }
+        for (nama, nilai) in self.env.iter() {
+            bagian.push(format!("env={}={}", nama, nilai));
+        }
+        bagian.push(format!("timeout={}", self.batas_ms));
+        if let Some(stdout) = &self.stdout {
+            bagian.push(format!("stdout={}", stdout));
+        }
+        if let Some(stderr) = &self.stderr {
+            bagian.push(format!("stderr={}", stderr));
+        }
+        if self.kode_keluar != "exit_code" {
+            bagian.push(format!("exit_code={}", self.kode_keluar));
+        }
+        bagian.push(format!("on_error={}", if self.lanjut_saat_gagal { "continue" } else { "fail" }));
+        write!(f, "{}", bagian.join("; "))
+    }
+}
+
+// Cara program berakhir, untuk kode keluar dan pesan kesalahan
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AkhirPerintah {
+    Keluar(i32),
+    // Dimatikan sinyal dari luar, hanya di Unix
+    Sinyal(i32),
+    // Dimatikan karena timeout habis
+    BatasWaktu,
+    // Dimatikan karena playback dihentikan
+    Dihentikan,
+    TidakJalan
+}
+
+impl AkhirPerintah {
+    fn dari_status(status: ExitStatus) -> AkhirPerintah {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(sinyal) = status.signal() {
+                return AkhirPerintah::Sinyal(sinyal);
+            }
+        }
+        AkhirPerintah::Keluar(status.code().unwrap_or(-1))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HasilPerintah {
+    pub akhir: AkhirPerintah,
+    pub stdout: String,
+    pub stderr: String
+}
+
+impl HasilPerintah {
+    pub fn sukses(&self) -> bool {
+        self.akhir == AkhirPerintah::Keluar(0)
+    }
+
+    // Variabel yang diisi setelah program selesai, kode keluar -1 kalau tidak keluar sendiri
+    pub fn variabel(&self, perintah: &Perintah) -> Vec<(String, String)> {
+        let kode = match self.akhir {
+            AkhirPerintah::Keluar(kode) => kode,
+            _ => -1
+        };
+        let mut hasil = vec![(perintah.kode_keluar.clone(), kode.to_string())];
+        hasil.extend(perintah.stdout.clone().map(|nama| (nama, self.stdout.clone())));
+        hasil.extend(perintah.stderr.clone().map(|nama| (nama, self.stderr.clone())));
+        hasil
+    }
+}
+
+fn buang_baris_baru(mut teks: String) -> String {
+    while teks.ends_with('\n') || teks.ends_with('\r') {
+        teks.pop();
+    }
+    teks
+}
+
+impl Perintah {
+    // isi mengganti {nama} di setiap bagian, lanjut mengembalikan false kalau playback dihentikan.
+    // Err kalau program tidak bisa dijalankan, program yang keluar dengan kode selain 0 tetap Ok
+    pub fn jalankan(&self, isi: impl Fn(&str) -> Result<String, String>, lanjut: impl Fn() -> bool) -> Result<HasilPerintah, String> {
+        let program = isi(&self.program)?;
+        let mut command = Command::new(&program);
+        for argumen in self.argumen.iter() {
+            command.arg(isi(argumen)?);
+        }
+        if let Some(folder) = &self.folder {
+            command.current_dir(isi(folder)?);
+        }
+        for (nama, nilai) in self.env.iter() {
+            command.env(nama, isi(nilai)?);
+        }
+        // Grup proses sendiri supaya anak dari program ini ikut dimatikan saat timeout atau dihentikan
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+
+        let mut anak = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()
+            .map_err(|e| format!("{} tidak bisa dijalankan: {}", program, e))?;
+
+        // Output dibaca di thread lain supaya program tidak macet karena pipe-nya penuh
+        let baca = |sumber: Option<Box<dyn Read + Send>>| {
+            let (kirim, terima) = mpsc::channel();
+            thread::spawn(move || {
+                let mut isi = Vec::new();
+                if let Some(mut sumber) = sumber {
+                    let _ = sumber.read_to_end(&mut isi);
+                }
+                let _ = kirim.send(buang_baris_baru(String::from_utf8_lossy(&isi).into_owned()));
+            });
+            terima
+        };
+        let stdout = baca(anak.stdout.take().map(|val| Box::new(val) as Box<dyn Read + Send>));
+        let stderr = baca(anak.stderr.take().map(|val| Box::new(val) as Box<dyn Read + Send>));
+
+        let mulai = Instant::now();
+        let akhir = loop {
+            match anak.try_wait() {
+                Ok(Some(status)) => break AkhirPerintah::dari_status(status),
+                Ok(None) => {},
+                Err(e) => return Err(format!("{}: {}", program, e))
+            }
+            let habis = self.batas_ms != 0 && mulai.elapsed() >= Duration::from_millis(self.batas_ms);
+            if habis || !lanjut() {
+                matikan(&mut anak);
+                break if habis { AkhirPerintah::BatasWaktu } else { AkhirPerintah::Dihentikan };
+            }
+            thread::sleep(Duration::from_millis(20));
+        };
+
+        // Kalau program dimatikan, pipe bisa masih dipegang proses lain, jadi output tidak ditunggu lama
+        let dimatikan = matches!(akhir, AkhirPerintah::BatasWaktu | AkhirPerintah::Dihentikan);
+        let ambil = |terima: mpsc::Receiver<String>| if dimatikan {
+            terima.recv_timeout(Duration::from_millis(200)).unwrap_or_default()
+        } else {
+            terima.recv().unwrap_or_default()
+        };
+        Ok(HasilPerintah { akhir, stdout: ambil(stdout), stderr: ambil(stderr) })
+    }
+}
+
+// Mematikan program beserta semua proses di grupnya
+fn matikan(anak: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(anak.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = anak.kill();
+    let _ = anak.wait();
+}
+
+// Pesan kesalahan untuk program yang tidak selesai atau keluar dengan kode selain 0, dengan baris terakhir stderr kalau ada
+pub fn pesan_gagal(perintah: &Perintah, hasil: &HasilPerintah) -> String {
+    let alasan = match hasil.akhir {
+        AkhirPerintah::Keluar(kode) => format!("keluar dengan kode {}", kode),
+        AkhirPerintah::Sinyal(sinyal) => format!("dimatikan oleh sinyal {}", sinyal),
+        AkhirPerintah::BatasWaktu => format!("dihentikan karena melewati timeout {} ms", perintah.batas_ms),
+        AkhirPerintah::Dihentikan => String::from("dihentikan karena playback dihentikan"),
+        AkhirPerintah::TidakJalan => String::from("tidak bisa dijalankan")
+    };
+    match hasil.stderr.lines().rev().find(|baris| !baris.trim().is_empty()) {
+        Some(baris) => format!("{} {}: {}", perintah.program, alasan, baris.trim()),
+        None => format!("{} {}", perintah.program, alasan)
+    }
+}
+
+pub fn apakah_diizinkan() -> bool {
+    pengaturan::baca(|pengaturan| pengaturan.izinkan_perintah)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    fn argumen(daftar: &[&str]) -> Vec<String> {
+        daftar.iter().map(|val| val.to_string()).collect()
+    }
+
+    fn hasil(akhir: AkhirPerintah, stderr: &str) -> HasilPerintah {
+        HasilPerintah { akhir, stdout: String::new(), stderr: stderr.to_string() }
+    }
+
+    #[test]
+    fn pecah_argumen_dengan_kutip() {
+        assert_eq!(pecah_argumen("  ./ambil.sh   --user  budi "), Ok(argumen(&["./ambil.sh", "--user", "budi"])));
+        assert_eq!(pecah_argumen(r#"echo "halo dunia" a"b c"d"#), Ok(argumen(&["echo", "halo dunia", "ab cd"])));
+        assert_eq!(pecah_argumen(r#"echo "" "a;b""#), Ok(argumen(&["echo", "", "a;b"])));
+        // \" dan \\ hanya di dalam kutip, backslash lain tetap ditulis
+        assert_eq!(pecah_argumen(r#"echo "kata \"kutip\" \\ \n" C:\temp"#), Ok(argumen(&["echo", r#"kata "kutip" \ \n"#, r"C:\temp"])));
+        assert_eq!(pecah_argumen(""), Ok(Vec::new()));
+
+        assert!(pecah_argumen(r#"echo "tidak ditutup"#).is_err());
+        assert!(pecah_argumen(r#"echo "akhir \""#).is_err());
+    }
+
+    #[test]
+    fn parse_perintah() {
+        let perintah: Perintah = r#"cmd=sh -c "echo a; echo b" {user}; cwd=/opt/tools; env=API_URL={url}; timeout=0; stdout=keluaran; stderr=galat; exit_code=kode; on_error=continue"#.parse().unwrap();
+        assert_eq!(perintah, Perintah {
+            program: String::from("sh"),
+            argumen: argumen(&["-c", "echo a; echo b", "{user}"]),
+            folder: Some(String::from("/opt/tools")),
+            env: vec![(String::from("API_URL"), String::from("{url}"))],
+            batas_ms: 0,
+            stdout: Some(String::from("keluaran")),
+            stderr: Some(String::from("galat")),
+            kode_keluar: String::from("kode"),
+            lanjut_saat_gagal: true
+        });
+
+        let perintah: Perintah = "cmd=./token.sh".parse().unwrap();
+        assert_eq!((perintah.batas_ms, perintah.kode_keluar.as_str(), perintah.lanjut_saat_gagal), (30_000, "exit_code", false));
+
+        for salah in ["", "timeout=5", "cmd=", r#"cmd=echo "a; timeout=5"#, "cmd=ls; timeout=lama", "cmd=ls; stdout=hasil akhir", "cmd=ls; env=tanpa_nilai",
+                      "cmd=ls; on_error=abaikan", "cmd=ls; shell=bash", "cmd=ls; tanpa_sama_dengan", "cmd=echo {nama"] {
+            assert!(salah.parse::<Perintah>().is_err(), "{}", salah);
+        }
+    }
+
+    #[test]
+    fn perintah_bolak_balik() {
+        for teks in [
+            "cmd=./ambil.sh --user {user}; timeout=5000; stdout=token; on_error=fail",
+            r#"cmd=sh -c "echo \"a; b\" \\ {x}" ""; cwd=/tmp; env=A=1; env=B={b}; timeout=0; stdout=o; stderr=e; exit_code=kode; on_error=continue"#
+        ] {
+            let perintah: Perintah = teks.parse().unwrap();
+            assert_eq!(perintah.to_string(), teks);
+            assert_eq!(perintah.to_string().parse::<Perintah>(), Ok(perintah));
+        }
+
+        let perintah = Perintah { argumen: argumen(&["a b", "x;y", "", r#"k"u\t"#]), ..("cmd=echo".parse().unwrap()) };
+        assert_eq!(perintah.to_string(), r#"cmd=echo "a b" "x;y" "" "k\"u\\t"; timeout=30000; on_error=fail"#);
+        assert_eq!(perintah.to_string().parse::<Perintah>(), Ok(perintah));
+    }
+
+    #[test]
+    fn variabel_dan_pesan_gagal() {
+        let perintah: Perintah = "cmd=./ambil.sh; timeout=5000; stdout=token; exit_code=kode".parse().unwrap();
+        let selesai = HasilPerintah { akhir: AkhirPerintah::Keluar(0), stdout: String::from("abc"), stderr: String::new() };
+        assert!(selesai.sukses());
+        assert_eq!(selesai.variabel(&perintah), [(String::from("kode"), String::from("0")), (String::from("token"), String::from("abc"))]);
+        for akhir in [AkhirPerintah::Sinyal(9), AkhirPerintah::BatasWaktu, AkhirPerintah::Dihentikan, AkhirPerintah::TidakJalan] {
+            assert!(!hasil(akhir, "").sukses());
+            assert_eq!(hasil(akhir, "").variabel(&perintah)[0].1, "-1");
+        }
+
+        assert_eq!(pesan_gagal(&perintah, &hasil(AkhirPerintah::Keluar(3), "peringatan\ntoken kedaluwarsa\n\n")), "./ambil.sh keluar dengan kode 3: token kedaluwarsa");
+        assert_eq!(pesan_gagal(&perintah, &hasil(AkhirPerintah::Sinyal(9), "")), "./ambil.sh dimatikan oleh sinyal 9");
+        assert_eq!(pesan_gagal(&perintah, &hasil(AkhirPerintah::BatasWaktu, "")), "./ambil.sh dihentikan karena melewati timeout 5000 ms");
+        assert_eq!(pesan_gagal(&perintah, &hasil(AkhirPerintah::Dihentikan, "")), "./ambil.sh dihentikan karena playback dihentikan");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn jalankan_program() {
+        let jalankan = |teks: &str, variabel: &[(&str, &str)], lanjut: bool| {
+            let variabel: HashMap<String, String> = variabel.iter().map(|(nama, nilai)| (nama.to_string(), nilai.to_string())).collect();
+            teks.parse::<Perintah>().unwrap().jalankan(|teks| variabel::isi(teks, &variabel, 1), || lanjut).unwrap()
+        };
+
+        // Nilai variabel tetap satu argumen walaupun berisi spasi, kutip atau ;
+        let hasil = jalankan(r#"cmd=printf "[%s]" {nilai} x"#, &[("nilai", r#"a b; "c""#)], true);
+        assert_eq!(hasil.akhir, AkhirPerintah::Keluar(0));
+        assert_eq!(hasil.stdout, r#"[a b; "c"][x]"#);
+
+        let hasil = jalankan(r#"cmd=sh -c "echo keluaran; echo salah >&2; exit 3""#, &[], true);
+        assert_eq!((hasil.akhir, hasil.stdout.as_str(), hasil.stderr.as_str()), (AkhirPerintah::Keluar(3), "keluaran", "salah"));
+
+        assert_eq!(jalankan(r#"cmd=sh -c "kill -9 $$""#, &[], true).akhir, AkhirPerintah::Sinyal(9));
+        assert_eq!(jalankan("cmd=sleep 5; timeout=100", &[], true).akhir, AkhirPerintah::BatasWaktu);
+        assert_eq!(jalankan("cmd=sleep 5; timeout=0", &[], false).akhir, AkhirPerintah::Dihentikan);
+
+        // Anak dari program ikut dimatikan dan pipe yang masih dipegangnya tidak ditunggu
+        let mulai = Instant::now();
+        let hasil = jalankan(r#"cmd=sh -c "sleep 3; echo x"; timeout=200"#, &[], true);
+        assert_eq!((hasil.akhir, hasil.stdout.as_str()), (AkhirPerintah::BatasWaktu, ""));
+        let mulai_dihentikan = Instant::now();
+        assert_eq!(jalankan(r#"cmd=sh -c "sleep 3; echo x"; timeout=0"#, &[], false).akhir, AkhirPerintah::Dihentikan);
+        assert!(mulai_dihentikan.elapsed() < Duration::from_secs(1));
+        assert!(mulai.elapsed() < Duration::from_secs(2));
+
+        let perintah: Perintah = "cmd=./tidak-ada-program".parse().unwrap();
+        assert!(perintah.jalankan(|teks| Ok(teks.to_string()), || true).is_err());
+    }
+}
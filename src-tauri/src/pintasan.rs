@@ -0,0 +1,274 @@
+// Tombol pintas global untuk memainkan macro dari pustaka, contoh "Ctrl+Alt+1" memainkan "fill timesheet".
+// Disimpan di pintasan.json di folder config. Tombol pintas tidak ikut terekam waktu recording berjalan.
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use rdev::{EventType, Key};
+use serde::{Deserialize, Serialize};
+
+use crate::event::UpdateKey;
+use crate::{pengaturan, pustaka};
+
+const NAMA_FILE: &str = "pintasan.json";
+
+// Yang dilakukan kalau pintasan ditekan waktu macro lain sedang dimainkan
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SaatBermain {
+    #[default]
+    Abaikan,
+    // Menghentikan playback yang sedang berjalan tanpa memulai macro ini
+    Hentikan,
+    // Menghentikan playback yang sedang berjalan lalu memainkan macro ini
+    Ganti,
+    // Memainkan macro ini setelah playback yang sedang berjalan selesai
+    Antre
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pintasan {
+    pub tombol: String,
+    // Nama file di pustaka
+    pub file: String,
+    #[serde(default)]
+    pub saat_bermain: SaatBermain
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kombinasi {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    meta: bool,
+    key: Key
+}
+
+fn apakah_modifier(key: Key) -> bool {
+    matches!(key, Key::ControlLeft | Key::ControlRight | Key::Alt | Key::AltGr | Key::ShiftLeft | Key::ShiftRight | Key::MetaLeft | Key::MetaRight)
+}
+
+// Huruf dan angka boleh ditulis langsung ("A", "1"), tombol lain memakai nama yang sama dengan di file macro ("F1", "Escape")
+pub fn parse_tombol(teks: &str) -> Result<Key, String> {
+    let nama = match teks.chars().collect::<Vec<char>>()[..] {
+        [huruf] if huruf.is_ascii_alphabetic() => format!("Key{}", huruf.to_ascii_uppercase()),
+        [angka] if angka.is_ascii_digit() => format!("Num{}", angka),
+        _ => teks.to_string()
+    };
+
+    UpdateKey::from_str(&nama).map(|key| *key.0).map_err(|_| format!("Tombol {} tidak dikenal", teks))
+}
+
+impl FromStr for Kombinasi {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut ctrl, mut alt, mut shift, mut meta) = (false, false, false, false);
+        let mut key: Option<Key> = None;
+        for bagian in s.split('+').map(|bagian| bagian.trim()) {
+            match bagian.to_lowercase().as_str() {
+                "ctrl" | "control" => ctrl = true,
+                "alt" => alt = true,
+                "shift" => shift = true,
+                "meta" | "super" | "win" | "cmd" => meta = true,
+                _ if key.is_some() => return Err(format!("Pintasan {} punya lebih dari satu tombol utama", s)),
+                _ => key = Some(parse_tombol(bagian)?)
+            }
+        }
+
+        let key = key.ok_or(format!("Pintasan {} tidak punya tombol utama", s))?;
+        if apakah_modifier(key) {
+            return Err(format!("Tombol utama pintasan {} tidak boleh modifier", s));
+        }
+        // Tombol recorder tanpa modifier tidak boleh dipakai
+        if !(ctrl || alt || shift || meta) && pengaturan::baca(|pengaturan| pengaturan.tombol.semua().contains(&key)) {
+            return Err(format!("Tombol {:?} sudah dipakai recorder", key));
+        }
+
+        Ok(Kombinasi { ctrl, alt, shift, meta, key })
+    }
+}
+
+impl fmt::Display for Kombinasi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifier = [(self.ctrl, "Ctrl"), (self.alt, "Alt"), (self.shift, "Shift"), (self.meta, "Meta")];
+        for (_, nama) in modifier.iter().filter(|(aktif, _)| *aktif) {
+            write!(f, "{}+", nama)?;
+        }
+        write!(f, "{}", UpdateKey(&self.key))
+    }
+}
+
+#[derive(Default)]
+struct StatusTombol {
+    modifier_ditekan: Vec<Key>,
+    // Tombol yang release-nya (dan press berulang dari auto-repeat) tidak boleh terekam
+    ditelan: Vec<Key>
+}
+
+#[derive(Debug, Default)]
+pub struct HasilPintasan {
+    pub pintasan: Option<Pintasan>,
+    // Event ini bagian dari pintasan, jangan direkam dan jangan diproses sebagai tombol recorder
+    pub telan: bool,
+    // Modifier yang sedang ditekan waktu pintasan cocok, press-nya sudah terlanjur terekam
+    pub modifier_ditekan: Vec<Key>
+}
+
+static DAFTAR_PINTASAN: Lazy<Mutex<Vec<(Kombinasi, Pintasan)>>> = Lazy::new(|| Mutex::new(muat()));
+static STATUS_TOMBOL: Lazy<Mutex<StatusTombol>> = Lazy::new(|| Mutex::new(StatusTombol::default()));
+
+fn muat() -> Vec<(Kombinasi, Pintasan)> {
+    let daftar: Vec<Pintasan> = pengaturan::muat_json(NAMA_FILE);
+    daftar.into_iter().filter_map(|pintasan| match Kombinasi::from_str(&pintasan.tombol) {
+        Ok(kombinasi) => Some((kombinasi, pintasan)),
+        Err(e) => {
+            println!("Pintasan dilewati: {}", e);
+            None
+        }
+    }).collect()
+}
+
+fn simpan(daftar: &[(Kombinasi, Pintasan)]) -> Result<(), String> {
+    let daftar: Vec<&Pintasan> = daftar.iter().map(|(_, pintasan)| pintasan).collect();
+    pengaturan::simpan_json(NAMA_FILE, &daftar)
+}
+
+pub fn daftar() -> Vec<Pintasan> {
+    DAFTAR_PINTASAN.lock().unwrap().iter().map(|(_, pintasan)| pintasan.clone()).collect()
+}
+
+// Pintasan dengan kombinasi yang sama diganti
+pub fn atur(mut pintasan: Pintasan) -> Result<Pintasan, String> {
+    let kombinasi = Kombinasi::from_str(&pintasan.tombol)?;
+    pustaka::path_macro(&pintasan.file)?;
+    pintasan.tombol = kombinasi.to_string();
+
+    let mut daftar = DAFTAR_PINTASAN.lock().unwrap();
+    daftar.retain(|(lama, _)| *lama != kombinasi);
+    daftar.push((kombinasi, pintasan.clone()));
+    simpan(&daftar)?;
+
+    Ok(pintasan)
+}
+
+pub fn hapus(tombol: &str) -> Result<(), String> {
+    let kombinasi = Kombinasi::from_str(tombol)?;
+
+    let mut daftar = DAFTAR_PINTASAN.lock().unwrap();
+    let jumlah = daftar.len();
+    daftar.retain(|(lama, _)| *lama != kombinasi);
+    if daftar.len() == jumlah {
+        return Err(format!("Pintasan {} tidak ada", kombinasi));
+    }
+
+    simpan(&daftar)
+}
+
+// Dipanggil waktu macro di pustaka diganti nama (Some) atau dihapus (None)
+pub fn ganti_file(lama: &str, baru: Option<&str>) -> Result<(), String> {
+    let mut daftar = DAFTAR_PINTASAN.lock().unwrap();
+    let jumlah = daftar.len();
+    match baru {
+        Some(baru) => daftar.iter_mut().filter(|(_, pintasan)| pintasan.file == lama).for_each(|(_, pintasan)| pintasan.file = baru.to_string()),
+        None => daftar.retain(|(_, pintasan)| pintasan.file != lama)
+    }
+
+    if baru.is_some() || daftar.len() != jumlah {
+        simpan(&daftar)?;
+    }
+    Ok(())
+}
+
+// Dipanggil listener untuk setiap event sebelum event itu direkam
+pub fn proses_event(event_type: &EventType) -> HasilPintasan {
+    let mut status = STATUS_TOMBOL.lock().unwrap();
+    match *event_type {
+        EventType::KeyPress(key) if status.ditelan.contains(&key) => HasilPintasan { telan: true, ..Default::default() },
+        EventType::KeyPress(key) if apakah_modifier(key) => {
+            if !status.modifier_ditekan.contains(&key) {
+                status.modifier_ditekan.push(key);
+            }
+            HasilPintasan::default()
+        },
+        EventType::KeyPress(key) => {
+            let ditekan = |pilihan: &[Key]| status.modifier_ditekan.iter().any(|key| pilihan.contains(key));
+            let sekarang = Kombinasi {
+                ctrl: ditekan(&[Key::ControlLeft, Key::ControlRight]),
+                alt: ditekan(&[Key::Alt, Key::AltGr]),
+                shift: ditekan(&[Key::ShiftLeft, Key::ShiftRight]),
+                meta: ditekan(&[Key::MetaLeft, Key::MetaRight]),
+                key
+            };
+
+            let pintasan = DAFTAR_PINTASAN.lock().unwrap().iter().find(|(kombinasi, _)| *kombinasi == sekarang).map(|(_, pintasan)| pintasan.clone());
+            if pintasan.is_none() {
+                return HasilPintasan::default();
+            }
+
+            // Modifier yang masih ditahan dari pintasan sebelumnya sudah tidak ada di rekaman
+            let modifier_ditekan: Vec<Key> = status.modifier_ditekan.iter().filter(|key| !status.ditelan.contains(key)).copied().collect();
+            status.ditelan.push(key);
+            status.ditelan.extend(modifier_ditekan.iter().copied());
+            HasilPintasan { pintasan, telan: true, modifier_ditekan }
+        },
+        EventType::KeyRelease(key) => {
+            status.modifier_ditekan.retain(|ditekan| *ditekan != key);
+            let telan = status.ditelan.contains(&key);
+            status.ditelan.retain(|ditelan| *ditelan != key);
+            HasilPintasan { telan, ..Default::default() }
+        },
+        _ => HasilPintasan::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_kombinasi() {
+        let teks = |teks: &str| Kombinasi::from_str(teks).map(|kombinasi| kombinasi.to_string());
+        assert_eq!(teks("ctrl+alt+1"), Ok(String::from("Ctrl+Alt+Num1")));
+        assert_eq!(teks(" Shift + a "), Ok(String::from("Shift+KeyA")));
+        assert_eq!(teks("Win+Control+F6"), Ok(String::from("Ctrl+Meta+F6")));
+        assert_eq!(teks("Ctrl+Alt+Num1"), teks("Alt+Ctrl+1"));
+
+        assert_eq!(teks("Ctrl+Alt"), Err(String::from("Pintasan Ctrl+Alt tidak punya tombol utama")));
+        assert_eq!(teks("Ctrl+A+B"), Err(String::from("Pintasan Ctrl+A+B punya lebih dari satu tombol utama")));
+        assert_eq!(teks("Ctrl+ShiftLeft"), Err(String::from("Tombol utama pintasan Ctrl+ShiftLeft tidak boleh modifier")));
+        assert_eq!(teks("Ctrl+Lompat"), Err(String::from("Tombol Lompat tidak dikenal")));
+        // Tombol recorder hanya boleh dipakai dengan modifier
+        assert!(teks("F6").is_err());
+    }
+
+    #[test]
+    fn pintasan_ditekan() {
+        let pintasan = Pintasan { tombol: String::from("Ctrl+Alt+1"), file: String::from("timesheet.rf"), saat_bermain: SaatBermain::Abaikan };
+        *DAFTAR_PINTASAN.lock().unwrap() = vec![(Kombinasi::from_str(&pintasan.tombol).unwrap(), pintasan)];
+        let file = |hasil: &HasilPintasan| hasil.pintasan.as_ref().map(|pintasan| pintasan.file.clone());
+
+        // Modifier tetap terekam sampai kombinasinya lengkap
+        assert!(!proses_event(&EventType::KeyPress(Key::ControlLeft)).telan);
+        assert!(!proses_event(&EventType::KeyPress(Key::Alt)).telan);
+        let hasil = proses_event(&EventType::KeyPress(Key::Num1));
+        assert_eq!((file(&hasil), hasil.telan, hasil.modifier_ditekan), (Some(String::from("timesheet.rf")), true, vec![Key::ControlLeft, Key::Alt]));
+
+        // Auto-repeat dan release dari kombinasi itu ditelan, tanpa memainkan lagi
+        let hasil = proses_event(&EventType::KeyPress(Key::Num1));
+        assert_eq!((file(&hasil), hasil.telan), (None, true));
+        for key in [Key::Num1, Key::ControlLeft, Key::Alt] {
+            assert!(proses_event(&EventType::KeyRelease(key)).telan);
+        }
+
+        // Modifier harus persis sama
+        assert!(!proses_event(&EventType::KeyPress(Key::Num1)).telan);
+        assert!(!proses_event(&EventType::KeyRelease(Key::Num1)).telan);
+        for event_type in [EventType::KeyPress(Key::ControlLeft), EventType::KeyPress(Key::ShiftLeft), EventType::KeyPress(Key::Alt)] {
+            proses_event(&event_type);
+        }
+        assert_eq!(file(&proses_event(&EventType::KeyPress(Key::Num1))), None);
+        for key in [Key::Num1, Key::ControlLeft, Key::ShiftLeft, Key::Alt] {
+            assert!(!proses_event(&EventType::KeyRelease(key)).telan);
+        }
+    }
+}
@@ -0,0 +1,1531 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use enigo::{Enigo, KeyboardControllable, MouseControllable};
+use once_cell::sync::Lazy;
+use rdev::EventType;
+use serde::{Deserialize, Serialize};
+
+use crate::alur::{self, BatasTunggu, Langkah, SaatHabis};
+use crate::event::{cari_penanda, Aksi, DataKey};
+use crate::layar::{self, Cocok, Gambar, TargetLayar};
+use crate::manusiawi::{Manusiawi, OpsiManusiawi, TitikGerak};
+use crate::panggilan::{self, DaftarPanggilan, MacroDipanggil};
+use crate::sumber_data::{HasilBaris, OpsiData, PenulisHasil, StatusBaris};
+use crate::{format, papan_klip, pengaturan, perintah, send, skrip, variabel, PengirimEvent, APAKAH_LAGI_MAIN};
+
+// Titik awal/akhir playback. Dari frontend dikirim sebagai { jenis: "Index", nilai: 10 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "jenis", content = "nilai")]
+pub enum Batas {
+    Index(usize),
+    Waktu(f32),
+    Penanda(String)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpsiPlayback {
+    pub mulai: Option<Batas>,
+    pub akhir: Option<Batas>,
+    // Pindahkan mouse ke posisi terakhir sebelum titik mulai, supaya event pertama tidak dimainkan dari posisi yang salah
+    pub pulihkan_posisi_mouse: bool,
+    // Mode debug: berhenti sebelum setiap event, atau hanya di breakpoint (id event) kalau ada
+    pub debug: bool,
+    pub breakpoint: Vec<usize>,
+    // Tombol yang dilepas waktu jeda ditekan lagi waktu playback dilanjutkan
+    pub tekan_ulang_saat_lanjut: bool,
+    // None berarti dimainkan sekali, Some(0) berarti terus diulang sampai dihentikan
+    pub jumlah_ulang: Option<u32>,
+    // 2.0 berarti dua kali lebih cepat dari rekamannya
+    pub kecepatan: f32,
+    // Nilai variabel yang dideklarasikan di header macro, menimpa nilai bawaannya
+    pub variabel: HashMap<String, String>,
+    // Mainkan sekali untuk setiap baris CSV, lihat sumber_data.rs
+    pub data: Option<OpsiData>,
+    // Path file macro yang dimainkan kalau ada, untuk Call dengan path relatif
+    pub file_macro: Option<String>,
+    // Waktu, posisi klik dan gerakan mouse dibuat mirip manusia, lihat manusiawi.rs
+    pub manusiawi: Option<OpsiManusiawi>
+}
+
+impl Default for OpsiPlayback {
+    fn default() -> Self {
+        OpsiPlayback {
+            mulai: None,
+            akhir: None,
+            pulihkan_posisi_mouse: false,
+            debug: false,
+            breakpoint: Vec::new(),
+            tekan_ulang_saat_lanjut: false,
+            jumlah_ulang: None,
+            kecepatan: pengaturan::baca(|pengaturan| pengaturan.kecepatan_default),
+            variabel: HashMap::new(),
+            data: None,
+            file_macro: None,
+            manusiawi: pengaturan::baca(|pengaturan| pengaturan.manusiawi.clone())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgresPlayback {
+    // id event yang baru saja dimainkan, supaya baris di tabel bisa di-highlight
+    pub index: usize,
+    pub urutan: usize,
+    pub total: usize,
+    pub berlalu: f32,
+    // Perkiraan sisa waktu dalam detik, None kalau diulang terus
+    pub sisa: Option<f32>,
+    pub ulangan: u32,
+    pub jumlah_ulang: Option<u32>
+}
+
+// Progres dikirim paling sering sekali setiap interval ini supaya frontend tidak kebanjiran event
+const INTERVAL_PROGRES: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum PerintahDebug {
+    Step,
+    // Mainkan satu grup sekaligus: tekan sampai lepas tombol yang sama, atau penanda sampai penanda berikutnya
+    StepGrup,
+    Lanjut,
+    Batal
+}
+
+pub static mut APAKAH_DIJEDA: Option<bool> = Some(false);
+
+static PERINTAH_DEBUG: Lazy<Mutex<Option<Sender<PerintahDebug>>>> = Lazy::new(|| Mutex::new(None));
+// Dipegang selama mainkan() berjalan, sampai flag-nya direset di akhir
+static KUNCI_PLAYER: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// Mengembalikan false kalau tidak ada playback debug yang sedang berjalan
+pub fn kirim_perintah_debug(perintah: PerintahDebug) -> bool {
+    match PERINTAH_DEBUG.lock().unwrap().as_ref() {
+        Some(pengirim) => pengirim.send(perintah).is_ok(),
+        None => false
+    }
+}
+
+pub(crate) fn apakah_lagi_main() -> bool {
+    unsafe { APAKAH_LAGI_MAIN.unwrap_or(false) }
+}
+
+// Menunggu sampai playback yang sedang berjalan (kalau ada) benar-benar selesai, termasuk jeda di akhirnya
+pub fn tunggu_selesai() {
+    while apakah_lagi_main() {
+        thread::sleep(Duration::from_millis(20));
+    }
+    drop(KUNCI_PLAYER.lock().unwrap_or_else(|e| e.into_inner()));
+}
+
+fn apakah_dijeda() -> bool {
+    unsafe { APAKAH_DIJEDA.unwrap_or(false) }
+}
+
+// Mengembalikan false kalau tidak ada playback yang bisa dijeda/dilanjutkan
+pub fn atur_jeda(jeda: bool) -> bool {
+    if !apakah_lagi_main() {
+        return false;
+    }
+
+    unsafe {
+        APAKAH_DIJEDA = Some(jeda);
+    }
+    true
+}
+
+fn lepas_event(event_type: &EventType) -> Option<EventType> {
+    match event_type {
+        EventType::KeyPress(key) => Some(EventType::KeyRelease(*key)),
+        EventType::ButtonPress(button) => Some(EventType::ButtonRelease(*button)),
+        _ => None
+    }
+}
+
+// Melepas semua tombol yang masih ditekan lalu menunggu sampai playback dilanjutkan.
+// Mengembalikan lama jeda, atau None kalau playback dihentikan selama dijeda
+fn tahan_selama_jeda<P: PengirimEvent>(pengirim: &P, ditahan: &[EventType], tekan_ulang: bool) -> Option<Duration> {
+    for event_type in ditahan.iter().rev() {
+        if let Some(lepas) = lepas_event(event_type) {
+            send(&lepas);
+        }
+    }
+    pengirim.kirim_event("PlaybackPaused", true);
+
+    let mulai_jeda = SystemTime::now();
+    while apakah_dijeda() {
+        if !apakah_lagi_main() {
+            return None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    if tekan_ulang {
+        for event_type in ditahan {
+            send(event_type);
+        }
+    }
+    pengirim.kirim_event("PlaybackPaused", false);
+
+    Some(mulai_jeda.elapsed().unwrap_or_default())
+}
+
+// Tidur yang ikut berhenti selama jeda, false kalau playback dihentikan. Dipakai oleh wait() di skrip
+pub(crate) fn tidur(durasi: Duration) -> bool {
+    let mut sisa = durasi;
+    while !sisa.is_zero() {
+        if !apakah_lagi_main() {
+            return false;
+        }
+        if apakah_dijeda() {
+            thread::sleep(Duration::from_millis(20));
+            continue
+        }
+
+        let langkah = sisa.min(Duration::from_millis(10));
+        thread::sleep(langkah);
+        sisa -= langkah;
+    }
+
+    apakah_lagi_main()
+}
+
+// Menunggu perintah debug, None kalau playback dihentikan lewat F7 selama menunggu
+fn tunggu_perintah_debug(penerima: &Receiver<PerintahDebug>) -> Option<PerintahDebug> {
+    loop {
+        match penerima.recv_timeout(Duration::from_millis(50)) {
+            Ok(perintah) => return Some(perintah),
+            Err(RecvTimeoutError::Timeout) => {
+                if !apakah_lagi_main() {
+                    return None;
+                }
+            },
+            Err(RecvTimeoutError::Disconnected) => return None
+        }
+    }
+}
+
+// Perkiraan dari posisi di file, blok yang diulang atau dilewati tidak dihitung. None kalau diulang terus
+fn perkiraan_sisa(sisa_putaran: f32, ulangan: u32, jumlah_ulang: u32, durasi: f32) -> Option<f32> {
+    if jumlah_ulang == 0 {
+        return None;
+    }
+    Some(sisa_putaran.max(0.0) + (jumlah_ulang - ulangan) as f32 * durasi)
+}
+
+// Index event terakhir dari grup yang dimulai di index
+fn akhir_grup(data_macro: &[DataKey], index: usize, akhir: usize) -> usize {
+    let mut sisa = data_macro[index + 1..akhir].iter();
+    match data_macro[index].aksi {
+        // Grup penanda berhenti tepat sebelum penanda berikutnya
+        Aksi::Penanda => match sisa.position(|val| matches!(val.aksi, Aksi::Penanda)) {
+            Some(posisi) => index + posisi,
+            None => akhir - 1
+        },
+        Aksi::Input(EventType::KeyPress(key)) => sisa.position(|val| matches!(val.aksi, Aksi::Input(EventType::KeyRelease(k)) if k == key)).map_or(index, |posisi| index + 1 + posisi),
+        Aksi::Input(EventType::ButtonPress(button)) => sisa.position(|val| matches!(val.aksi, Aksi::Input(EventType::ButtonRelease(b)) if b == button)).map_or(index, |posisi| index + 1 + posisi),
+        // Blok Repeat/If dimainkan sampai End-nya
+        Aksi::Ulang(..) | Aksi::Jika(..) => alur::akhir_blok(data_macro, index).filter(|akhir_blok| *akhir_blok < akhir).unwrap_or(index),
+        _ => index
+    }
+}
+
+fn index_batas(data_macro: &[DataKey], batas: &Batas, apakah_akhir: bool) -> Result<usize, String> {
+    match batas {
+        Batas::Index(index) => {
+            if *index >= data_macro.len() {
+                return Err(format!("Event {} tidak ada", index));
+            }
+
+            // Event akhir ikut dimainkan
+            Ok(if apakah_akhir { index + 1 } else { *index })
+        },
+        Batas::Waktu(waktu) => {
+            let index = if apakah_akhir {
+                data_macro.iter().position(|val| val.waktu > *waktu)
+            } else {
+                data_macro.iter().position(|val| val.waktu >= *waktu)
+            };
+
+            Ok(index.unwrap_or(data_macro.len()))
+        },
+        Batas::Penanda(nama) => cari_penanda(data_macro, nama).ok_or(format!("Penanda {} tidak ada", nama))
+    }
+}
+
+// Rentang [mulai, akhir) dari event yang akan dimainkan beserta semua macro yang dipanggil lewat Call
+fn siapkan_rentang(data_macro: &[DataKey], opsi: &OpsiPlayback) -> Result<(usize, usize, DaftarPanggilan), String> {
+    if opsi.kecepatan <= 0.0 {
+        return Err(String::from("Kecepatan harus lebih dari 0"));
+    }
+    if let Some(manusiawi) = &opsi.manusiawi {
+        manusiawi.periksa()?;
+    }
+
+    let mulai = match &opsi.mulai {
+        Some(batas) => index_batas(data_macro, batas, false)?,
+        None => 0
+    };
+    let akhir = match &opsi.akhir {
+        Some(batas) => index_batas(data_macro, batas, true)?,
+        None => data_macro.len()
+    };
+
+    if mulai >= akhir {
+        return Err(String::from("Tidak ada event di rentang tersebut"));
+    }
+    // Rentang tidak boleh memotong blok Repeat/If
+    alur::susun(data_macro, mulai, akhir)?;
+    if let Some(data) = &opsi.data {
+        data.periksa()?;
+    }
+    // Pemanggil yang memeriksa dulu dapat error, bukan event MintaVariabel
+    let kurang = variabel_kurang(data_macro, opsi)?;
+    if !kurang.is_empty() {
+        return Err(variabel::pesan_belum_diisi(&kurang));
+    }
+    let daftar = panggilan::muat(data_macro, opsi.file_macro.as_deref().map(Path::new))?;
+    let pakai_perintah = data_macro[mulai..akhir].iter().chain(daftar.macro_dipanggil.values().flat_map(|val| val.data_macro.iter()))
+        .any(|val| matches!(val.aksi, Aksi::Perintah(..)));
+    if pakai_perintah && !perintah::apakah_diizinkan() {
+        return Err(String::from("Macro berisi aksi Run, aktifkan izinkan_perintah di pengaturan untuk memainkannya"));
+    }
+
+    Ok((mulai, akhir, daftar))
+}
+
+// Mengembalikan rentang [mulai, akhir) dari event yang akan dimainkan
+pub fn rentang_playback(data_macro: &[DataKey], opsi: &OpsiPlayback) -> Result<(usize, usize), String> {
+    siapkan_rentang(data_macro, opsi).map(|(mulai, akhir, _)| (mulai, akhir))
+}
+
+// Variabel yang tidak punya nilai dari opsi.variabel maupun nilai bawaan. Kolom CSV diisi di setiap baris jadi tidak ikut dihitung
+pub fn variabel_kurang(data_macro: &[DataKey], opsi: &OpsiPlayback) -> Result<Vec<variabel::Deklarasi>, String> {
+    let mut nilai = opsi.variabel.clone();
+    if let Some(data) = &opsi.data {
+        nilai.extend(data.tabel()?.kolom.iter().map(|kolom| (kolom.clone(), String::new())));
+    }
+
+    Ok(variabel::belum_diisi(data_macro, &nilai))
+}
+
+// "x, y" dari value klik
+fn parse_posisi(teks: &str) -> Option<(f64, f64)> {
+    let posisi_mos: Vec<&str> = teks.split(", ").collect();
+    if posisi_mos.len() < 2 {
+        return None;
+    }
+
+    match (posisi_mos[0].trim().parse::<f64>(), posisi_mos[1].trim().parse::<f64>()) {
+        (Ok(x), Ok(y)) => Some((x, y)),
+        _ => None
+    }
+}
+
+fn posisi_mouse(val: &DataKey) -> Option<(f64, f64)> {
+    match val.aksi {
+        Aksi::Input(EventType::MouseMove { x, y }) => Some((x, y)),
+        Aksi::Input(EventType::ButtonPress(..)) | Aksi::Input(EventType::ButtonRelease(..)) => parse_posisi(&val.value),
+        _ => None
+    }
+}
+
+pub fn posisi_mouse_sebelum(data_macro: &[DataKey], index: usize) -> Option<(f64, f64)> {
+    data_macro[..index].iter().rev().find_map(posisi_mouse)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HasilCocok {
+    pub index: usize,
+    #[serde(flatten)]
+    pub cocok: Cocok
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KesalahanAksi {
+    pub index: usize,
+    pub pesan: String
+}
+
+// Macro yang dimainkan dari dalam macro lain tidak mengirim progres, index-nya bukan index macro yang sedang dibuka
+struct TanpaPengirim;
+impl PengirimEvent for TanpaPengirim {
+    fn kirim_event<S: Serialize + Clone>(&self, _nama: &str, _payload: S) {}
+}
+
+const MAKS_KEDALAMAN_SISIPAN: usize = 8;
+
+thread_local! {
+    static KEDALAMAN_SISIPAN: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    // Acakan untuk play() dari aksi Script yang sedang berjalan, supaya macro-nya tidak mulai dari seed yang sama
+    static MANUSIAWI_SKRIP: std::cell::RefCell<Option<Manusiawi>> = const { std::cell::RefCell::new(None) };
+}
+
+enum Lompatan {
+    Lanjut,
+    Putus,
+    Lanjutkan,
+    Berhenti,
+    // WaitWindow, WaitPixel atau WaitImage dengan on_timeout=retry, bagian blok sebelumnya dimainkan lagi
+    Ulangi
+}
+
+// Hasil akhir mainkan, dipakai jadwal untuk riwayat dan CLI untuk kode keluar
+#[derive(Debug, Clone, PartialEq)]
+pub enum HasilPlayback {
+    Selesai,
+    Dihentikan,
+    Gagal(String)
+}
+
+const JEDA_SEBELUM_PULIHKAN_PAPAN_KLIP: Duration = Duration::from_millis(300);
+
+const INTERVAL_CEK_TUNGGU: Duration = Duration::from_millis(100);
+
+// Status satu kali playback selama pohon langkah dimainkan
+struct Pemutar<'a, P: PengirimEvent> {
+    pengirim: &'a P,
+    data_macro: &'a [DataKey],
+    opsi: &'a OpsiPlayback,
+    mulai: usize,
+    akhir: usize,
+    // Hasil panggilan::muat untuk aksi Call di macro ini
+    tujuan: &'a HashMap<usize, PathBuf>,
+    macro_dipanggil: &'a HashMap<PathBuf, MacroDipanggil>,
+    penerima: Option<Receiver<PerintahDebug>>,
+    berhenti_sebelum: bool,
+    // Rentang index grup yang sedang dimainkan dengan StepGrup
+    grup_debug: Option<(usize, usize)>,
+    // Tombol dan button yang sedang ditekan, dilepas waktu playback dijeda
+    ditahan: Vec<EventType>,
+    // Waktu macro (detik) yang sudah dijalani di ulangan ini, dijumlah dari jarak antar aksi yang benar-benar dimainkan
+    // supaya blok yang dilewati tidak ditunggu dan blok yang diulang tetap dengan jarak yang sama
+    jam: f32,
+    waktu_terakhir: f32,
+    waktu_di_tunggu: SystemTime,
+    variabel: HashMap<String, String>,
+    // Putaran setiap Repeat yang sedang berjalan, yang terakhir paling dalam
+    iterasi: Vec<u32>,
+    // Berapa kali WaitWindow, WaitPixel atau WaitImage di index sudah mengulang, dihapus begitu yang ditunggu ditemukan
+    percobaan_tunggu: HashMap<usize, u32>,
+    ulangan: u32,
+    jumlah_ulang: u32,
+    durasi: f32,
+    waktu_mulai_main: SystemTime,
+    waktu_progres_terakhir: Option<SystemTime>,
+    // Pesan kalau playback berhenti karena aksi yang gagal
+    kesalahan: Option<String>,
+    manusiawi: Option<Manusiawi>,
+    // Jam dan geser waktu event input terakhir, geser berikutnya dibatasi menurut jaraknya
+    input_sebelumnya: Option<(f32, f32)>,
+    // Posisi kursor terakhir yang dikirim playback, None kalau belum pernah (dibaca dari enigo)
+    posisi_kursor: Option<(f64, f64)>,
+    // Posisi klik asli dan hasil gesernya, supaya ButtonRelease dilepas di titik yang sama dengan ButtonPress-nya
+    klik_digeser: Option<((f64, f64), (f64, f64))>
+}
+
+impl<P: PengirimEvent> Pemutar<'_, P> {
+    fn jalankan(&mut self, langkah: &[Langkah]) -> Lompatan {
+        let mut posisi = 0;
+        while posisi < langkah.len() {
+            let lompatan = match &langkah[posisi] {
+                Langkah::Aksi(index) => self.aksi(*index),
+                Langkah::Ulang { index, jumlah, isi, akhir } => self.ulang(*index, *jumlah, isi, *akhir),
+                Langkah::Jika { index, kondisi, isi, lainnya, akhir } => {
+                    if !self.masuk(*index) {
+                        return Lompatan::Berhenti;
+                    }
+
+                    let lompatan = if alur::evaluasi(kondisi, &self.variabel) {
+                        self.jalankan(isi)
+                    } else if let Some((index_lainnya, lainnya)) = lainnya {
+                        self.waktu_terakhir = self.data_macro[*index_lainnya].waktu;
+                        self.jalankan(lainnya)
+                    } else {
+                        Lompatan::Lanjut
+                    };
+                    self.keluar_blok(*akhir);
+                    lompatan
+                }
+            };
+
+            match lompatan {
+                Lompatan::Lanjut => posisi += 1,
+                Lompatan::Ulangi => {
+                    // Mundur ke penanda terakhir sebelum WaitWindow di blok yang sama, atau ke awal blok
+                    posisi = langkah[..posisi].iter()
+                        .rposition(|val| matches!(val, Langkah::Aksi(index) if matches!(self.data_macro[*index].aksi, Aksi::Penanda)))
+                        .unwrap_or(0);
+                    self.waktu_terakhir = self.data_macro[langkah[posisi].index()].waktu;
+                },
+                _ => return lompatan
+            }
+        }
+
+        Lompatan::Lanjut
+    }
+
+    fn ulang(&mut self, index: usize, jumlah: u32, isi: &[Langkah], akhir: usize) -> Lompatan {
+        if !self.masuk(index) {
+            return Lompatan::Berhenti;
+        }
+
+        self.iterasi.push(0);
+        let mut berhenti = false;
+        for ke in 1.. {
+            if jumlah != 0 && ke > jumlah {
+                break
+            }
+            if !apakah_lagi_main() {
+                berhenti = true;
+                break
+            }
+
+            // Setiap putaran dimulai dengan jarak waktu dari baris Repeat
+            self.waktu_terakhir = self.data_macro[index].waktu;
+            if let Some(iterasi) = self.iterasi.last_mut() {
+                *iterasi = ke;
+            }
+            match self.jalankan(isi) {
+                Lompatan::Putus => break,
+                Lompatan::Berhenti => {
+                    berhenti = true;
+                    break
+                },
+                Lompatan::Lanjut | Lompatan::Lanjutkan | Lompatan::Ulangi => {}
+            }
+        }
+        self.iterasi.pop();
+
+        if berhenti {
+            return Lompatan::Berhenti;
+        }
+        self.keluar_blok(akhir);
+        Lompatan::Lanjut
+    }
+
+    // Aksi sesudah End diberi jarak dari End, bukan dari isi blok yang dilewati
+    fn keluar_blok(&mut self, akhir: usize) {
+        self.waktu_terakhir = self.waktu_terakhir.max(self.data_macro[akhir].waktu);
+    }
+
+    // Berhenti dulu kalau mode debug, lalu menunggu sampai waktunya aksi di index. false kalau playback dihentikan
+    fn masuk(&mut self, index: usize) -> bool {
+        self.masuk_dengan(index, 0.0)
+    }
+
+    // maju: detik sebelum waktunya, untuk kursor yang harus sudah bergerak supaya kliknya tepat waktu
+    fn masuk_dengan(&mut self, index: usize, maju: f32) -> bool {
+        if !apakah_lagi_main() {
+            return false;
+        }
+
+        let val = &self.data_macro[index];
+        if let Some(penerima) = &self.penerima {
+            let dalam_grup = self.grup_debug.is_some_and(|(awal, akhir)| (awal..=akhir).contains(&index));
+            if !dalam_grup && (self.berhenti_sebelum || self.opsi.breakpoint.contains(&index)) {
+                self.grup_debug = None;
+                self.pengirim.kirim_event("DebugBerhenti", &val.ke_raw(index));
+
+                let mulai_jeda = SystemTime::now();
+                match tunggu_perintah_debug(penerima) {
+                    Some(PerintahDebug::Step) => self.berhenti_sebelum = true,
+                    Some(PerintahDebug::StepGrup) => {
+                        self.berhenti_sebelum = true;
+                        self.grup_debug = Some((index, akhir_grup(self.data_macro, index, self.akhir)));
+                    },
+                    Some(PerintahDebug::Lanjut) => self.berhenti_sebelum = false,
+                    Some(PerintahDebug::Batal) | None => return false
+                }
+
+                // Waktu selama berhenti tidak dihitung, jadi jarak antar event tetap sama
+                self.waktu_di_tunggu += mulai_jeda.elapsed().unwrap_or_default();
+            }
+        }
+
+        self.jam += (val.waktu - self.waktu_terakhir).max(0.0);
+        self.waktu_terakhir = val.waktu;
+        // Geser acak hanya untuk event input, jamnya sendiri tidak ikut bergeser jadi gesernya tidak menumpuk
+        let geser = match (&mut self.manusiawi, &val.aksi) {
+            (Some(manusiawi), Aksi::Input(..)) => {
+                let geser = match self.input_sebelumnya {
+                    Some((jam, geser)) => manusiawi.geser_waktu_setelah((self.jam - jam) / self.opsi.kecepatan, geser),
+                    None => manusiawi.geser_waktu()
+                };
+                self.input_sebelumnya = Some((self.jam, geser));
+                geser
+            },
+            _ => 0.0
+        };
+        self.tunggu_jadwal(geser - maju)
+    }
+
+    // geser dalam detik yang sebenarnya, tidak ikut kecepatan playback
+    fn tunggu_jadwal(&mut self, geser: f32) -> bool {
+        let target = self.jam / self.opsi.kecepatan + geser;
+        loop {
+            let berlalu = SystemTime::now().duration_since(self.waktu_di_tunggu).unwrap_or_default().as_secs_f32();
+            if berlalu >= target {
+                return true;
+            }
+            if !apakah_lagi_main() {
+                return false;
+            }
+
+            if apakah_dijeda() {
+                // Jam jadwal dibekukan selama jeda, sisa event tetap dengan jarak waktu yang sama
+                match tahan_selama_jeda(self.pengirim, &self.ditahan, self.opsi.tekan_ulang_saat_lanjut) {
+                    Some(lama_jeda) => self.waktu_di_tunggu += lama_jeda,
+                    None => return false
+                }
+            } else if target - berlalu > 0.02 {
+                // Tunggu yang lama (misalnya aksi Wait) tidak perlu memakan CPU
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+
+    // Posisi klik boleh berisi variabel, misalnya "{match_x}, {match_y}" dari WaitImage.
+    // Digeser acak kalau playback dibuat mirip manusia, ButtonRelease di posisi yang sama memakai geser ButtonPress-nya
+    fn posisi_klik(&mut self, val: &DataKey) -> Result<Option<(f64, f64)>, String> {
+        let posisi = if val.value.contains('{') {
+            let teks = self.isi_variabel(&val.value)?;
+            Some(parse_posisi(&teks).ok_or(format!("Posisi klik {} bukan angka", teks))?)
+        } else {
+            posisi_mouse(val)
+        };
+        let (Some(asli), Some(manusiawi)) = (posisi, self.manusiawi.as_mut()) else {
+            return Ok(posisi);
+        };
+
+        let digeser = match self.klik_digeser {
+            Some((sebelumnya, digeser)) if sebelumnya == asli && matches!(val.aksi, Aksi::Input(EventType::ButtonRelease(..))) => digeser,
+            _ => manusiawi.geser_klik(asli)
+        };
+        self.klik_digeser = Some((asli, digeser));
+        Ok(Some(digeser))
+    }
+
+    fn kursor_sekarang(&self) -> (f64, f64) {
+        self.posisi_kursor.unwrap_or_else(|| {
+            let (x, y) = Enigo::new().mouse_location();
+            (x as f64, y as f64)
+        })
+    }
+
+    fn gerak_ke(&mut self, x: f64, y: f64) {
+        send(&EventType::MouseMove { x, y });
+        self.posisi_kursor = Some((x, y));
+    }
+
+    // Menggerakkan kursor mengikuti rencana dari Manusiawi::rencana_gerak. false kalau playback dihentikan
+    fn gerak(&mut self, rencana: &[TitikGerak]) -> bool {
+        let mut mulai = SystemTime::now();
+        for titik in rencana {
+            loop {
+                if !apakah_lagi_main() {
+                    return false;
+                }
+                if apakah_dijeda() {
+                    match tahan_selama_jeda(self.pengirim, &self.ditahan, self.opsi.tekan_ulang_saat_lanjut) {
+                        Some(lama_jeda) => {
+                            mulai += lama_jeda;
+                            self.waktu_di_tunggu += lama_jeda;
+                        },
+                        None => return false
+                    }
+                    continue;
+                }
+                let sisa = titik.waktu - mulai.elapsed().unwrap_or_default().as_secs_f32();
+                if sisa <= 0.0 {
+                    break;
+                }
+                thread::sleep(Duration::from_secs_f32(sisa.min(0.01)));
+            }
+            self.gerak_ke(titik.x, titik.y);
+        }
+        true
+    }
+
+    fn lepas_semua(&mut self) {
+        for event_type in self.ditahan.drain(..).rev() {
+            if let Some(lepas) = lepas_event(&event_type) {
+                send(&lepas);
+            }
+        }
+    }
+
+    // Mengisi {…} di teks, {iteration} dari Repeat terdalam atau putaran playback
+    fn isi_variabel(&self, teks: &str) -> Result<String, String> {
+        variabel::isi(teks, &self.variabel, self.iterasi.last().copied().unwrap_or(self.ulangan))
+    }
+
+    // Memanggil cek setiap INTERVAL_CEK_TUNGGU sampai hasilnya Some atau batas waktunya habis. Ok(None) kalau habis lalu dilewati,
+    // Err berisi lompatan untuk diulang, dihentikan atau gagal. Waktu selama dijeda tidak dihitung ke batas waktu
+    fn tunggu_sampai<T>(&mut self, index: usize, batas: &BatasTunggu, nama: &str, mut cek: impl FnMut() -> Result<Option<T>, String>) -> Result<Option<T>, Lompatan> {
+        let mulai_tunggu = SystemTime::now();
+        let lama_batas = Duration::from_millis(batas.batas_ms);
+        let mut ditunggu = Duration::ZERO;
+        let hasil = loop {
+            let mulai_cek = SystemTime::now();
+            match cek() {
+                Ok(Some(hasil)) => break Ok(Some(hasil)),
+                Ok(None) => {},
+                Err(pesan) => break Err(pesan)
+            }
+            ditunggu += mulai_cek.elapsed().unwrap_or_default();
+            if ditunggu >= lama_batas {
+                break Ok(None);
+            }
+            if !tidur(INTERVAL_CEK_TUNGGU) {
+                return Err(Lompatan::Berhenti);
+            }
+            ditunggu += INTERVAL_CEK_TUNGGU;
+        };
+        // Lama menunggu tidak menggeser jarak ke event berikutnya
+        self.waktu_di_tunggu += mulai_tunggu.elapsed().unwrap_or_default();
+
+        match hasil {
+            Ok(Some(hasil)) => {
+                self.percobaan_tunggu.remove(&index);
+                Ok(Some(hasil))
+            },
+            Ok(None) => match batas.saat_habis {
+                SaatHabis::Lewati => {
+                    println!("Event {}: {} tidak ditemukan, dilewati", index, nama);
+                    Ok(None)
+                },
+                SaatHabis::Ulangi if self.percobaan_tunggu.get(&index).copied().unwrap_or(0) < batas.maks_ulang => {
+                    *self.percobaan_tunggu.entry(index).or_insert(0) += 1;
+                    Err(Lompatan::Ulangi)
+                },
+                SaatHabis::Ulangi => {
+                    self.percobaan_tunggu.remove(&index);
+                    Err(self.gagal(index, "AksiGagal", format!("{} tidak ditemukan setelah diulang {} kali", nama, batas.maks_ulang)))
+                },
+                SaatHabis::Gagal => Err(self.gagal(index, "AksiGagal", format!("{} tidak ditemukan dalam {} ms", nama, batas.batas_ms)))
+            },
+            Err(pesan) => Err(self.gagal(index, "AksiGagal", pesan))
+        }
+    }
+
+    // Playback berhenti karena aksi di index gagal. Kalau berhenti karena playback dihentikan bukan kesalahan
+    fn gagal(&mut self, index: usize, nama_event: &str, pesan: String) -> Lompatan {
+        if apakah_lagi_main() {
+            println!("Event {} gagal: {}", index, pesan);
+            self.pengirim.kirim_event(nama_event, &KesalahanAksi { index, pesan: pesan.clone() });
+            self.kesalahan = Some(pesan);
+        }
+        Lompatan::Berhenti
+    }
+
+    fn aksi(&mut self, index: usize) -> Lompatan {
+        let data_macro = self.data_macro;
+        let val = &data_macro[index];
+
+        // Posisi klik dan gerakan kursor ke sana dihitung dulu, gerakannya dimulai sebelum waktunya supaya kliknya tepat waktu
+        let klik = match val.aksi {
+            Aksi::Input(EventType::ButtonPress(..) | EventType::ButtonRelease(..)) => match self.posisi_klik(val) {
+                Ok(posisi) => posisi,
+                Err(pesan) => return self.gagal(index, "AksiGagal", pesan)
+            },
+            _ => None
+        };
+        let rencana = match klik {
+            Some(ke) if self.manusiawi.is_some() => {
+                let dari = self.kursor_sekarang();
+                self.manusiawi.as_mut().map(|manusiawi| manusiawi.rencana_gerak(dari, ke)).unwrap_or_default()
+            },
+            _ => Vec::new()
+        };
+        if !self.masuk_dengan(index, rencana.last().map_or(0.0, |titik| titik.waktu)) {
+            return Lompatan::Berhenti;
+        }
+
+        let lompatan = match &val.aksi {
+            Aksi::Input(event_type) => {
+                let event_type = *event_type;
+                if !self.gerak(&rencana) {
+                    return Lompatan::Berhenti;
+                }
+                if let Some((x, y)) = klik {
+                    self.gerak_ke(x, y);
+                }
+                if let EventType::MouseMove { x, y } = event_type {
+                    self.posisi_kursor = Some((x, y));
+                }
+
+                send(&event_type);
+
+                match event_type {
+                    EventType::KeyPress(..) | EventType::ButtonPress(..) if !self.ditahan.contains(&event_type) => self.ditahan.push(event_type),
+                    EventType::KeyRelease(key) => self.ditahan.retain(|val| *val != EventType::KeyPress(key)),
+                    EventType::ButtonRelease(button) => self.ditahan.retain(|val| *val != EventType::ButtonPress(button)),
+                    _ => {}
+                }
+                Lompatan::Lanjut
+            },
+            Aksi::Tunggu { min_ms, maks_ms } => {
+                // Lama tunggu tidak ikut dipercepat oleh kecepatan playback
+                self.jam += alur::lama_tunggu(*min_ms, *maks_ms).as_secs_f32() * self.opsi.kecepatan;
+                if !self.tunggu_jadwal(0.0) {
+                    return Lompatan::Berhenti;
+                }
+                Lompatan::Lanjut
+            },
+            Aksi::TungguWindow(tunggu) => {
+                if !cfg!(target_os = "linux") {
+                    return self.gagal(index, "AksiGagal", String::from("WaitWindow hanya bisa dipakai di Linux/X11"));
+                }
+
+                match self.tunggu_sampai(index, &tunggu.batas, &format!("Window {}", tunggu), || Ok(tunggu.ditemukan().then_some(()))) {
+                    Ok(..) => Lompatan::Lanjut,
+                    Err(lompatan) => return lompatan
+                }
+            },
+            Aksi::TungguLayar(tunggu) => {
+                let template = match &tunggu.target {
+                    TargetLayar::Gambar { file, .. } => match Gambar::baca_png(&layar::path_template(file, self.opsi.file_macro.as_deref())) {
+                        Ok(template) => Some(template),
+                        Err(pesan) => return self.gagal(index, "AksiGagal", pesan)
+                    },
+                    TargetLayar::Piksel { .. } => None
+                };
+
+                match self.tunggu_sampai(index, &tunggu.batas, &tunggu.to_string(), || layar::cek(&tunggu.target, template.as_ref())) {
+                    Ok(Some(cocok)) => {
+                        self.variabel.insert(String::from("match_x"), cocok.x.to_string());
+                        self.variabel.insert(String::from("match_y"), cocok.y.to_string());
+                        self.variabel.insert(String::from("match_confidence"), format!("{:.3}", cocok.keyakinan));
+                        self.pengirim.kirim_event("LayarCocok", &HasilCocok { index, cocok });
+                        Lompatan::Lanjut
+                    },
+                    // Dilewati, posisi dari WaitPixel atau WaitImage sebelumnya tidak dipakai lagi
+                    Ok(None) => {
+                        for nama in ["match_x", "match_y", "match_confidence"] {
+                            self.variabel.remove(nama);
+                        }
+                        Lompatan::Lanjut
+                    },
+                    Err(lompatan) => return lompatan
+                }
+            },
+            Aksi::Atur { nama, nilai } => match self.isi_variabel(nilai) {
+                Ok(nilai) => {
+                    self.variabel.insert(nama.clone(), nilai);
+                    Lompatan::Lanjut
+                },
+                Err(pesan) => return self.gagal(index, "AksiGagal", pesan)
+            },
+            Aksi::Ketik(teks) => match self.isi_variabel(teks) {
+                Ok(teks) => {
+                    Enigo::new().key_sequence(&teks);
+                    Lompatan::Lanjut
+                },
+                Err(pesan) => return self.gagal(index, "AksiGagal", pesan)
+            },
+            Aksi::AturPapanKlip(teks) | Aksi::Tempel(teks) => {
+                let hasil = self.isi_variabel(teks).and_then(|teks| papan_klip::tulis(&teks));
+                match hasil {
+                    Ok(()) => {
+                        if matches!(val.aksi, Aksi::Tempel(..)) {
+                            papan_klip::tekan_paste();
+                        }
+                        Lompatan::Lanjut
+                    },
+                    Err(pesan) => return self.gagal(index, "AksiGagal", pesan)
+                }
+            },
+            Aksi::BacaPapanKlip(nama) => match papan_klip::baca() {
+                Ok(isi) => {
+                    self.variabel.insert(nama.clone(), isi);
+                    Lompatan::Lanjut
+                },
+                Err(pesan) => return self.gagal(index, "AksiGagal", pesan)
+            },
+            Aksi::Perintah(perintah) => {
+                // Dicek lagi untuk macro dari play() di skrip yang tidak lewat siapkan_rentang
+                if !perintah::apakah_diizinkan() {
+                    return self.gagal(index, "AksiGagal", String::from("Aksi Run belum diizinkan di pengaturan"));
+                }
+
+                let mulai_perintah = SystemTime::now();
+                let hasil = perintah.jalankan(|teks| self.isi_variabel(teks), apakah_lagi_main);
+                // Lama program berjalan tidak menggeser jarak ke event berikutnya
+                self.waktu_di_tunggu += mulai_perintah.elapsed().unwrap_or_default();
+                if !apakah_lagi_main() {
+                    return Lompatan::Berhenti;
+                }
+
+                match hasil {
+                    Ok(hasil) => {
+                        self.variabel.extend(hasil.variabel(perintah));
+                        if !hasil.sukses() && !perintah.lanjut_saat_gagal {
+                            return self.gagal(index, "AksiGagal", perintah::pesan_gagal(perintah, &hasil));
+                        }
+                        Lompatan::Lanjut
+                    },
+                    // Program yang tidak bisa dijalankan dianggap keluar dengan -1, pesannya masuk ke stderr
+                    Err(pesan) if perintah.lanjut_saat_gagal => {
+                        let hasil = perintah::HasilPerintah { akhir: perintah::AkhirPerintah::TidakJalan, stdout: String::new(), stderr: pesan };
+                        self.variabel.extend(hasil.variabel(perintah));
+                        Lompatan::Lanjut
+                    },
+                    Err(pesan) => return self.gagal(index, "AksiGagal", pesan)
+                }
+            },
+            Aksi::Skrip(kode) => {
+                let mulai_skrip = SystemTime::now();
+                let sebelumnya = MANUSIAWI_SKRIP.with(|val| val.replace(self.manusiawi.as_mut().map(Manusiawi::turunan)));
+                let hasil = skrip::jalankan(kode, &mut self.variabel);
+                MANUSIAWI_SKRIP.with(|val| val.replace(sebelumnya));
+                // Lama skrip berjalan tidak menggeser jarak ke event berikutnya
+                self.waktu_di_tunggu += mulai_skrip.elapsed().unwrap_or_default();
+
+                match hasil {
+                    Ok(()) => Lompatan::Lanjut,
+                    Err(pesan) => return self.gagal(index, "SkripGagal", pesan)
+                }
+            },
+            Aksi::Panggil(panggilan) => {
+                let macro_dipanggil = self.macro_dipanggil;
+                let Some((path, dipanggil)) = self.tujuan.get(&index).and_then(|path| Some((path, macro_dipanggil.get(path)?))) else {
+                    return self.gagal(index, "AksiGagal", format!("{} belum dimuat", panggilan.file));
+                };
+
+                // Macro yang dipanggil hanya melihat argumennya, bukan variabel macro ini
+                let mut argumen = HashMap::new();
+                for (nama, nilai) in panggilan.argumen.iter() {
+                    match self.isi_variabel(nilai) {
+                        Ok(nilai) => argumen.insert(nama.clone(), nilai),
+                        Err(pesan) => return self.gagal(index, "AksiGagal", pesan)
+                    };
+                }
+
+                let mulai_panggilan = SystemTime::now();
+                let hasil = mainkan_dalam(&dipanggil.data_macro, path, &dipanggil.tujuan, macro_dipanggil, self.opsi.kecepatan, self.manusiawi.as_mut().map(Manusiawi::turunan), &mut argumen);
+                // Lama macro yang dipanggil tidak menggeser jarak ke event berikutnya
+                self.waktu_di_tunggu += mulai_panggilan.elapsed().unwrap_or_default();
+
+                match hasil {
+                    Ok(()) => Lompatan::Lanjut,
+                    Err(pesan) => return self.gagal(index, "AksiGagal", format!("{}: {}", panggilan.file, pesan))
+                }
+            },
+            Aksi::Putus => Lompatan::Putus,
+            Aksi::Lanjutkan => Lompatan::Lanjutkan,
+            _ => Lompatan::Lanjut
+        };
+
+        self.kirim_progres(index);
+        lompatan
+    }
+
+    fn kirim_progres(&mut self, index: usize) {
+        let sekarang = SystemTime::now();
+        let apakah_terakhir = index + 1 == self.akhir;
+        let sudah_waktunya = self.waktu_progres_terakhir.is_none_or(|waktu| sekarang.duration_since(waktu).unwrap_or_default() >= INTERVAL_PROGRES);
+        if !apakah_terakhir && !sudah_waktunya {
+            return;
+        }
+        self.waktu_progres_terakhir = Some(sekarang);
+
+        let sisa_putaran = (self.data_macro[self.akhir - 1].waktu - self.data_macro[index].waktu) / self.opsi.kecepatan;
+        let sisa = perkiraan_sisa(sisa_putaran, self.ulangan, self.jumlah_ulang, self.durasi);
+        let progres = ProgresPlayback {
+            index,
+            urutan: index - self.mulai + 1,
+            total: self.akhir - self.mulai,
+            berlalu: sekarang.duration_since(self.waktu_mulai_main).unwrap_or_default().as_secs_f32(),
+            sisa,
+            ulangan: self.ulangan,
+            jumlah_ulang: if self.opsi.data.is_some() { Some(self.jumlah_ulang) } else { self.opsi.jumlah_ulang }
+        };
+        self.pengirim.kirim_event("ProgresPlayback", &progres);
+    }
+}
+
+// Pemutar untuk macro yang dimainkan di tengah playback (Call atau play() dari skrip), tanpa progres, ulang dan debug.
+// Timeline-nya di-rebase ke event pertama, jadi langsung mulai setelah aksi yang memanggilnya
+fn mainkan_dalam(data_macro: &[DataKey], path: &Path, tujuan: &HashMap<usize, PathBuf>, macro_dipanggil: &HashMap<PathBuf, MacroDipanggil>, kecepatan: f32, manusiawi: Option<Manusiawi>, variabel: &mut HashMap<String, String>) -> Result<(), String> {
+    if data_macro.is_empty() {
+        return Ok(());
+    }
+    let langkah = alur::susun(data_macro, 0, data_macro.len())?;
+    let kurang = variabel::belum_diisi(data_macro, variabel);
+    if !kurang.is_empty() {
+        return Err(variabel::pesan_belum_diisi(&kurang));
+    }
+
+    let kedalaman = KEDALAMAN_SISIPAN.with(|kedalaman| kedalaman.get());
+    if kedalaman >= MAKS_KEDALAMAN_SISIPAN {
+        return Err(format!("Macro di dalam macro lebih dari {} tingkat", MAKS_KEDALAMAN_SISIPAN));
+    }
+    KEDALAMAN_SISIPAN.with(|val| val.set(kedalaman + 1));
+
+    // file_macro untuk mencari template WaitImage dari folder macro yang dimainkan
+    let opsi = OpsiPlayback { kecepatan, file_macro: Some(path.display().to_string()), manusiawi: None, ..Default::default() };
+    let waktu_awal = data_macro[0].waktu;
+    let mut pemutar = Pemutar {
+        pengirim: &TanpaPengirim,
+        data_macro,
+        opsi: &opsi,
+        mulai: 0,
+        akhir: data_macro.len(),
+        tujuan,
+        macro_dipanggil,
+        penerima: None,
+        berhenti_sebelum: false,
+        grup_debug: None,
+        ditahan: Vec::new(),
+        jam: 0.0,
+        waktu_terakhir: waktu_awal,
+        waktu_di_tunggu: SystemTime::now(),
+        variabel: variabel::nilai_awal(data_macro, variabel),
+        iterasi: Vec::new(),
+        percobaan_tunggu: HashMap::new(),
+        ulangan: 1,
+        jumlah_ulang: 1,
+        durasi: 0.0,
+        waktu_mulai_main: SystemTime::now(),
+        waktu_progres_terakhir: None,
+        kesalahan: None,
+        manusiawi,
+        posisi_kursor: None,
+        klik_digeser: None,
+        input_sebelumnya: None
+    };
+    let lompatan = pemutar.jalankan(&langkah);
+    *variabel = pemutar.variabel;
+    KEDALAMAN_SISIPAN.with(|val| val.set(kedalaman));
+
+    match lompatan {
+        Lompatan::Berhenti if !apakah_lagi_main() => Err(String::from("Playback dihentikan")),
+        Lompatan::Berhenti => Err(pemutar.kesalahan.unwrap_or(String::from("Macro yang dimainkan gagal"))),
+        _ => Ok(())
+    }
+}
+
+// play() dari skrip dengan kecepatan default dan opsi manusiawi dari aksi Script yang menjalankannya.
+// Variabelnya dipakai bersama skrip, tidak dipisah seperti Call
+pub(crate) fn mainkan_sisipan(path: &Path, variabel: &mut HashMap<String, String>) -> Result<(), String> {
+    let data_macro = format::baca_macro(path)?;
+    let daftar = panggilan::muat(&data_macro, Some(path))?;
+    let manusiawi = MANUSIAWI_SKRIP.with(|val| val.borrow_mut().as_mut().map(Manusiawi::turunan));
+    mainkan_dalam(&data_macro, path, &daftar.tujuan, &daftar.macro_dipanggil, OpsiPlayback::default().kecepatan, manusiawi, variabel)
+}
+
+fn gagal_data<P: PengirimEvent>(pengirim: &P, pesan: String) -> HasilPlayback {
+    println!("Data playback gagal: {}", pesan);
+    pengirim.kirim_event("DataPlaybackGagal", &pesan);
+    HasilPlayback::Gagal(pesan)
+}
+
+fn mainkan_semua<P: PengirimEvent>(pengirim: &P, data_macro: &[DataKey], opsi: &OpsiPlayback) -> HasilPlayback {
+    let tabel = match opsi.data.as_ref().map(|data| data.tabel()).transpose() {
+        Ok(tabel) => tabel,
+        Err(pesan) => return gagal_data(pengirim, pesan)
+    };
+
+    // Variabel tanpa nilai diminta ke frontend, lalu macro dimainkan lagi dengan opsi.variabel
+    match variabel_kurang(data_macro, opsi) {
+        Ok(kurang) if !kurang.is_empty() => {
+            pengirim.kirim_event("MintaVariabel", &kurang);
+            return HasilPlayback::Gagal(variabel::pesan_belum_diisi(&kurang));
+        },
+        Err(pesan) => return gagal_data(pengirim, pesan),
+        Ok(..) => {}
+    }
+
+    let (mulai, akhir, daftar) = match siapkan_rentang(data_macro, opsi) {
+        Ok(rentang) => rentang,
+        Err(pesan) => {
+            println!("Playback tidak bisa dimulai: {}", pesan);
+            return HasilPlayback::Gagal(pesan);
+        }
+    };
+
+    // File hasil baru dibuat setelah macro pasti bisa dimainkan
+    let mut data = match (tabel, &opsi.data) {
+        (Some(tabel), Some(opsi_data)) => match PenulisHasil::buat(&opsi_data.path_hasil(), tabel) {
+            Ok(penulis) => Some((tabel, penulis)),
+            Err(pesan) => return gagal_data(pengirim, pesan)
+        },
+        _ => None
+    };
+    // Bloknya sudah diperiksa oleh rentang_playback
+    let langkah = alur::susun(data_macro, mulai, akhir).unwrap_or_default();
+
+    // Clipboard pengguna disimpan kalau macro ini atau macro yang dipanggilnya memakai clipboard.
+    // Clipboard yang kosong atau bukan teks tidak bisa dikembalikan
+    let pakai_papan_klip = data_macro[mulai..akhir].iter().chain(daftar.macro_dipanggil.values().flat_map(|val| val.data_macro.iter()))
+        .any(|val| papan_klip::apakah_aksi_papan_klip(&val.aksi));
+    let papan_klip_asli = if pakai_papan_klip && pengaturan::baca(|pengaturan| pengaturan.pulihkan_clipboard) {
+        papan_klip::baca().ok()
+    } else {
+        None
+    };
+
+    if opsi.pulihkan_posisi_mouse {
+        if let Some((x, y)) = posisi_mouse_sebelum(data_macro, mulai) {
+            send(&EventType::MouseMove { x, y });
+        }
+    }
+
+    let penerima = if opsi.debug {
+        let (pengirim, penerima) = channel();
+        *PERINTAH_DEBUG.lock().unwrap() = Some(pengirim);
+        Some(penerima)
+    } else {
+        None
+    };
+    unsafe {
+        APAKAH_DIJEDA = Some(false);
+    }
+
+    // Timeline di-rebase ke event pertama yang dipilih
+    let waktu_awal = data_macro[mulai].waktu;
+    let mut pemutar = Pemutar {
+        pengirim,
+        data_macro,
+        opsi,
+        mulai,
+        akhir,
+        tujuan: &daftar.tujuan,
+        macro_dipanggil: &daftar.macro_dipanggil,
+        penerima,
+        berhenti_sebelum: opsi.debug && opsi.breakpoint.is_empty(),
+        grup_debug: None,
+        ditahan: Vec::new(),
+        jam: 0.0,
+        waktu_terakhir: waktu_awal,
+        waktu_di_tunggu: SystemTime::now(),
+        variabel: variabel::nilai_awal(data_macro, &opsi.variabel),
+        iterasi: Vec::new(),
+        percobaan_tunggu: HashMap::new(),
+        ulangan: 0,
+        // Dengan data CSV setiap baris dimainkan sekali, jumlah_ulang tidak dipakai
+        jumlah_ulang: match &data {
+            Some((tabel, _)) => tabel.baris.len() as u32,
+            None => opsi.jumlah_ulang.unwrap_or(1)
+        },
+        durasi: (data_macro[akhir - 1].waktu - waktu_awal) / opsi.kecepatan,
+        waktu_mulai_main: SystemTime::now(),
+        waktu_progres_terakhir: None,
+        kesalahan: None,
+        manusiawi: opsi.manusiawi.as_ref().map(Manusiawi::baru),
+        posisi_kursor: None,
+        klik_digeser: None,
+        input_sebelumnya: None
+    };
+
+    let mut berhenti = false;
+    let mut baris_gagal = 0;
+    for ulangan in 1.. {
+        if pemutar.jumlah_ulang != 0 && ulangan > pemutar.jumlah_ulang {
+            break
+        }
+
+        pemutar.ulangan = ulangan;
+        pemutar.jam = 0.0;
+        pemutar.waktu_terakhir = waktu_awal;
+        pemutar.input_sebelumnya = None;
+        pemutar.waktu_di_tunggu = SystemTime::now();
+
+        let Some((tabel, penulis)) = data.as_mut() else {
+            if matches!(pemutar.jalankan(&langkah), Lompatan::Berhenti) {
+                berhenti = true;
+                break
+            }
+            continue
+        };
+
+        // Setiap baris mulai dengan variabel yang bersih, kolom CSV menimpa opsi.variabel
+        let index_baris = ulangan as usize - 1;
+        pemutar.variabel = variabel::nilai_awal(data_macro, &opsi.variabel);
+        pemutar.variabel.extend(tabel.variabel(index_baris));
+        pemutar.kesalahan = None;
+
+        let mulai_baris = SystemTime::now();
+        let status = match pemutar.jalankan(&langkah) {
+            Lompatan::Berhenti if pemutar.kesalahan.is_some() => StatusBaris::Gagal,
+            Lompatan::Berhenti => StatusBaris::Dihentikan,
+            _ => StatusBaris::Sukses
+        };
+        if status == StatusBaris::Gagal {
+            // Baris berikutnya tetap dimainkan, tombol yang tertinggal ditekan dilepas dulu
+            pemutar.lepas_semua();
+            baris_gagal += 1;
+        }
+
+        let hasil = HasilBaris { baris: index_baris + 1, status, pesan: pemutar.kesalahan.take(), durasi: mulai_baris.elapsed().unwrap_or_default().as_secs_f32() };
+        if let Err(e) = penulis.tulis(tabel, &hasil) {
+            println!("Hasil baris {} tidak bisa ditulis: {}", hasil.baris, e);
+        }
+        pengirim.kirim_event("HasilBarisData", &hasil);
+
+        if status == StatusBaris::Dihentikan {
+            berhenti = true;
+            break
+        }
+    }
+
+    if pemutar.penerima.is_some() {
+        *PERINTAH_DEBUG.lock().unwrap() = None;
+    }
+
+    if let Some(isi) = papan_klip_asli {
+        // Beri waktu aplikasi tujuan mengambil isi dari Paste terakhir sebelum clipboard diganti
+        thread::sleep(JEDA_SEBELUM_PULIHKAN_PAPAN_KLIP);
+        if let Err(e) = papan_klip::tulis(&isi) {
+            println!("Clipboard tidak bisa dikembalikan: {}", e);
+        }
+    }
+
+    // Dengan data CSV playback gagal kalau ada baris yang gagal, walaupun baris lainnya tetap dimainkan
+    match (pemutar.kesalahan, &data) {
+        (Some(pesan), _) => HasilPlayback::Gagal(pesan),
+        _ if berhenti => HasilPlayback::Dihentikan,
+        (None, Some((tabel, _))) if baris_gagal > 0 => HasilPlayback::Gagal(format!("{} dari {} baris data gagal", baris_gagal, tabel.baris.len())),
+        _ => HasilPlayback::Selesai
+    }
+}
+
+// Loop player yang dipakai oleh F7, command mainkan_recorder dan CLI. APAKAH_LAGI_MAIN harus sudah di-set oleh pemanggil
+pub fn mainkan<P: PengirimEvent>(pengirim: &P, data_macro: &[DataKey], opsi: &OpsiPlayback) -> HasilPlayback {
+    let _kunci = KUNCI_PLAYER.lock().unwrap_or_else(|e| e.into_inner());
+    let hasil = mainkan_semua(pengirim, data_macro, opsi);
+
+    pengirim.kirim_event("SelesaiRecording", true);
+
+    thread::sleep(Duration::from_millis(pengaturan::baca(|pengaturan| pengaturan.jeda_setelah_main_ms)));
+    unsafe {
+        APAKAH_DIJEDA = Some(false);
+        APAKAH_LAGI_MAIN = Some(false);
+    }
+    hasil
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::fs::{self, File};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, MutexGuard};
+    use super::*;
+    use crate::layar::{LayarMemori, SumberLayar, Warna, Wilayah};
+    use crate::papan_klip::{PapanKlipMemori, PenyediaPapanKlip};
+
+    // Layar, papan klip dan APAKAH_LAGI_MAIN dipakai bersama, jadi tes playback (juga tes skrip) tidak boleh berjalan bersamaan
+    static KUNCI: Mutex<()> = Mutex::new(());
+
+    pub(crate) fn kunci() -> MutexGuard<'static, ()> {
+        KUNCI.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn mainkan_teks(teks: &str, path: &Path, variabel: &mut HashMap<String, String>) -> Result<(), String> {
+        unsafe {
+            APAKAH_LAGI_MAIN = Some(true);
+        }
+        let data_macro = format::parse_macro(teks);
+        mainkan_dalam(&data_macro, path, &HashMap::new(), &HashMap::new(), 1.0, None, variabel)
+    }
+
+    fn folder_tes(nama: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("recordflow-player-{}-{}", nama, std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    fn tulis_png(path: &Path, gambar: &Gambar) {
+        let mut encoder = png::Encoder::new(File::create(path).unwrap(), gambar.lebar, gambar.tinggi);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(&gambar.piksel.concat()).unwrap();
+    }
+
+    // Mencatat nama event yang dikirim player
+    #[derive(Default)]
+    struct PengirimTes {
+        event: Mutex<Vec<String>>
+    }
+
+    impl PengirimEvent for PengirimTes {
+        fn kirim_event<S: Serialize + Clone>(&self, nama: &str, _payload: S) {
+            self.event.lock().unwrap().push(nama.to_string());
+        }
+    }
+
+    // Layar 1x1 yang baru berwarna putih mulai dari tangkapan ke-cocok_mulai
+    struct LayarBerhitung {
+        jumlah: AtomicUsize,
+        cocok_mulai: usize
+    }
+
+    impl SumberLayar for LayarBerhitung {
+        fn tangkap(&self, wilayah: Option<Wilayah>) -> Result<(Wilayah, Gambar), String> {
+            let ke = self.jumlah.fetch_add(1, Ordering::SeqCst) + 1;
+            let warna = if ke >= self.cocok_mulai { Warna(255, 255, 255) } else { Warna(0, 0, 0) };
+            Ok((wilayah.unwrap_or(Wilayah { x: 0, y: 0, lebar: 1, tinggi: 1 }), Gambar::baru(1, 1, warna)))
+        }
+    }
+
+    #[test]
+    fn tunggu_piksel_menyimpan_posisi() {
+        let _kunci = kunci();
+        let mut layar = Gambar::baru(20, 10, Warna(0, 0, 0));
+        layar.atur_warna(5, 7, Warna(255, 136, 0));
+        layar::atur_sumber(Arc::new(LayarMemori::baru(layar)));
+
+        let mut variabel = HashMap::new();
+        let teks = "WaitPixel|||x=5; y=7; color=#ff8000; tolerance=10; timeout=0|||0\n\
+                    Set|||klik={match_x}, {match_y}|||0";
+        mainkan_teks(teks, Path::new("tes.rf"), &mut variabel).unwrap();
+        assert_eq!(variabel["match_x"], "5");
+        assert_eq!(variabel["match_y"], "7");
+        assert_eq!(variabel["match_confidence"], "0.969");
+        assert_eq!(variabel["klik"], "5, 7");
+    }
+
+    #[test]
+    fn tunggu_gambar_menyimpan_titik_tengah() {
+        let _kunci = kunci();
+        let folder = folder_tes("gambar");
+        let mut template = Gambar::baru(4, 3, Warna(0, 0, 0));
+        for (index, piksel) in template.piksel.iter_mut().enumerate() {
+            *piksel = [20 * index as u8, 255 - 20 * index as u8, 90, 255];
+        }
+        tulis_png(&folder.join("tombol.png"), &template);
+        let mut layar = Gambar::baru(30, 20, Warna(128, 128, 128));
+        layar.tempel(&template, 11, 6);
+        layar::atur_sumber(Arc::new(LayarMemori::baru(layar)));
+
+        // Template dicari dari folder macro, posisinya tetap koordinat layar walaupun dibatasi region
+        let mut variabel = HashMap::new();
+        let teks = "WaitImage|||image=tombol.png; region=10,5,10,10; confidence=1; timeout=0|||0";
+        let hasil = mainkan_teks(teks, &folder.join("form.rf"), &mut variabel);
+        fs::remove_dir_all(&folder).unwrap();
+        hasil.unwrap();
+        assert_eq!((variabel["match_x"].as_str(), variabel["match_y"].as_str()), ("13", "7"));
+        assert_eq!(variabel["match_confidence"], "1.000");
+
+        // Template yang tidak ada menggagalkan playback
+        let hasil = mainkan_teks("WaitImage|||image=tidak-ada.png; timeout=0|||0", &folder.join("form.rf"), &mut HashMap::new());
+        assert!(hasil.unwrap_err().contains("tidak-ada.png"));
+    }
+
+    #[test]
+    fn tunggu_layar_habis_waktu() {
+        let _kunci = kunci();
+        layar::atur_sumber(Arc::new(LayarMemori::baru(Gambar::baru(20, 10, Warna(0, 0, 0)))));
+
+        let hasil = mainkan_teks("WaitPixel|||x=5; y=7; color=#ffffff; timeout=0|||0\nSet|||lanjut=ya|||0", Path::new("tes.rf"), &mut HashMap::new());
+        assert_eq!(hasil, Err(String::from("x=5; y=7; color=#ffffff; timeout=0; on_timeout=fail tidak ditemukan dalam 0 ms")));
+
+        // Dilewati: posisi dari tunggu sebelumnya dibuang dan playback lanjut
+        let mut variabel: HashMap<String, String> = [("match_x", "1"), ("match_y", "2"), ("match_confidence", "1.000")].into_iter()
+            .map(|(nama, nilai)| (nama.to_string(), nilai.to_string()))
+            .collect();
+        let teks = "WaitPixel|||x=5; y=7; color=#ffffff; timeout=0; on_timeout=skip|||0\nSet|||lanjut=ya|||0";
+        mainkan_teks(teks, Path::new("tes.rf"), &mut variabel).unwrap();
+        assert_eq!(variabel.get("match_x"), None);
+        assert_eq!(variabel.get("match_y"), None);
+        assert_eq!(variabel.get("match_confidence"), None);
+        assert_eq!(variabel["lanjut"], "ya");
+    }
+
+    #[test]
+    fn tunggu_layar_diulang() {
+        let _kunci = kunci();
+        let teks = |ulang: u32| format!("WaitPixel|||x=0; y=0; color=#ffffff; timeout=0; on_timeout=retry; retries={}|||0", ulang);
+
+        // Cocok di tangkapan ketiga, setelah diulang dua kali
+        let sumber = Arc::new(LayarBerhitung { jumlah: AtomicUsize::new(0), cocok_mulai: 3 });
+        layar::atur_sumber(sumber.clone());
+        let mut variabel = HashMap::new();
+        mainkan_teks(&teks(2), Path::new("tes.rf"), &mut variabel).unwrap();
+        assert_eq!(sumber.jumlah.load(Ordering::SeqCst), 3);
+        assert_eq!(variabel["match_x"], "0");
+
+        let sumber = Arc::new(LayarBerhitung { jumlah: AtomicUsize::new(0), cocok_mulai: 3 });
+        layar::atur_sumber(sumber.clone());
+        let hasil = mainkan_teks(&teks(1), Path::new("tes.rf"), &mut HashMap::new());
+        assert!(hasil.unwrap_err().ends_with("tidak ditemukan setelah diulang 1 kali"));
+        assert_eq!(sumber.jumlah.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn baca_papan_klip_ke_variabel() {
+        let _kunci = kunci();
+        papan_klip::atur_penyedia(Arc::new(PapanKlipMemori::default()));
+
+        let mut variabel = HashMap::from([(String::from("nama"), String::from("Budi"))]);
+        mainkan_teks("ClipboardSet|||Halo {nama}|||0\nClipboardGet|||salinan|||0", Path::new("tes.rf"), &mut variabel).unwrap();
+        assert_eq!(variabel["salinan"], "Halo Budi");
+        assert_eq!(papan_klip::baca(), Ok(String::from("Halo Budi")));
+
+        // Clipboard kosong menggagalkan ClipboardGet
+        papan_klip::atur_penyedia(Arc::new(PapanKlipMemori::default()));
+        assert_eq!(mainkan_teks("ClipboardGet|||salinan|||0", Path::new("tes.rf"), &mut HashMap::new()), Err(String::from("Clipboard kosong")));
+    }
+
+    #[test]
+    fn papan_klip_dikembalikan_setelah_playback() {
+        let _kunci = kunci();
+        let data_macro = format::parse_macro("ClipboardSet|||dari macro|||0\nClipboardGet|||salinan|||0");
+        let mainkan_dengan = |isi_awal: Option<&str>, pulihkan: bool| {
+            let penyedia = Arc::new(PapanKlipMemori::default());
+            if let Some(isi) = isi_awal {
+                penyedia.tulis(isi).unwrap();
+            }
+            papan_klip::atur_penyedia(penyedia.clone());
+            pengaturan::atur_sementara(|pengaturan| pengaturan.pulihkan_clipboard = pulihkan);
+            unsafe {
+                APAKAH_LAGI_MAIN = Some(true);
+            }
+
+            let pengirim = PengirimTes::default();
+            assert_eq!(mainkan_semua(&pengirim, &data_macro, &OpsiPlayback::default()), HasilPlayback::Selesai);
+            assert!(!pengirim.event.lock().unwrap().iter().any(|nama| nama == "AksiGagal"));
+            penyedia.baca()
+        };
+
+        assert_eq!(mainkan_dengan(Some("punya pengguna"), true), Ok(String::from("punya pengguna")));
+        assert_eq!(mainkan_dengan(Some("punya pengguna"), false), Ok(String::from("dari macro")));
+        // Clipboard yang awalnya kosong tidak bisa dikembalikan
+        assert_eq!(mainkan_dengan(None, true), Ok(String::from("dari macro")));
+        pengaturan::atur_sementara(|pengaturan| pengaturan.pulihkan_clipboard = true);
+    }
+
+    #[test]
+    fn hasil_playback() {
+        let _kunci = kunci();
+        papan_klip::atur_penyedia(Arc::new(PapanKlipMemori::default()));
+        let mainkan_dengan = |teks: &str, lagi_main: bool| {
+            unsafe {
+                APAKAH_LAGI_MAIN = Some(lagi_main);
+            }
+            mainkan_semua(&PengirimTes::default(), &format::parse_macro(teks), &OpsiPlayback::default())
+        };
+
+        assert_eq!(mainkan_dengan("Set|||a=1|||0", true), HasilPlayback::Selesai);
+        assert_eq!(mainkan_dengan("ClipboardGet|||salinan|||0", true), HasilPlayback::Gagal(String::from("Clipboard kosong")));
+        assert_eq!(mainkan_dengan("Set|||a=1|||0", false), HasilPlayback::Dihentikan);
+    }
+
+    #[test]
+    fn rentang_dari_batas() {
+        let data_macro = format::parse_macro("Set|||a=1|||0\nMarker|||awal|||1\nSet|||a=2|||2\nRepeat|||2|||3\nSet|||a=3|||3.5\nEnd||||||4\nMarker|||akhir|||5\nSet|||a=4|||6");
+        let rentang = |mulai: Option<Batas>, akhir: Option<Batas>| rentang_playback(&data_macro, &OpsiPlayback { mulai, akhir, ..OpsiPlayback::default() });
+
+        assert_eq!(rentang(None, None), Ok((0, 8)));
+        // Event akhir ikut dimainkan
+        assert_eq!(rentang(Some(Batas::Index(2)), Some(Batas::Index(6))), Ok((2, 7)));
+        assert_eq!(rentang(Some(Batas::Index(8)), None), Err(String::from("Event 8 tidak ada")));
+
+        // Dari event pertama di waktu mulai sampai event terakhir di waktu akhir
+        assert_eq!(rentang(Some(Batas::Waktu(1.5)), Some(Batas::Waktu(5.0))), Ok((2, 7)));
+        assert_eq!(rentang(Some(Batas::Waktu(1.0)), Some(Batas::Waktu(100.0))), Ok((1, 8)));
+        assert_eq!(rentang(Some(Batas::Waktu(100.0)), None), Err(String::from("Tidak ada event di rentang tersebut")));
+
+        // Bagian akhir berhenti sebelum penanda akhir
+        assert_eq!(rentang(Some(Batas::Penanda(String::from("awal"))), Some(Batas::Penanda(String::from("akhir")))), Ok((1, 6)));
+        assert_eq!(rentang(Some(Batas::Penanda(String::from("tengah"))), None), Err(String::from("Penanda tengah tidak ada")));
+        assert_eq!(rentang(Some(Batas::Penanda(String::from("akhir"))), Some(Batas::Penanda(String::from("awal")))), Err(String::from("Tidak ada event di rentang tersebut")));
+
+        // Rentang yang memotong blok Repeat ditolak
+        assert!(rentang(Some(Batas::Index(4)), None).is_err());
+    }
+
+    #[test]
+    fn akhir_grup_step() {
+        let data_macro = format::parse_macro(concat!(
+            "Marker|||login|||0\nKey Press ShiftLeft|||ShiftLeft|||1\nKey Press KeyA|||KeyA|||2\nKey Release KeyA|||KeyA|||3\n",
+            "Key Release ShiftLeft|||ShiftLeft|||4\nButton Press Left|||1, 1|||5\nMarker|||kirim|||6\nRepeat|||2|||7\n",
+            "Button Release Left|||1, 1|||8\nEnd||||||9\nKey Press Return|||Return|||10"
+        ));
+
+        // Penanda sampai sebelum penanda berikutnya, atau sampai akhir rentang
+        assert_eq!(akhir_grup(&data_macro, 0, data_macro.len()), 5);
+        assert_eq!(akhir_grup(&data_macro, 6, data_macro.len()), 10);
+        assert_eq!(akhir_grup(&data_macro, 6, 8), 7);
+        // Tekan sampai lepas tombol yang sama
+        assert_eq!(akhir_grup(&data_macro, 1, data_macro.len()), 4);
+        assert_eq!(akhir_grup(&data_macro, 2, data_macro.len()), 3);
+        assert_eq!(akhir_grup(&data_macro, 5, data_macro.len()), 8);
+        // Tombol yang tidak dilepas dan event lain hanya dirinya sendiri
+        assert_eq!(akhir_grup(&data_macro, 10, data_macro.len()), 10);
+        assert_eq!(akhir_grup(&data_macro, 3, data_macro.len()), 3);
+        // Blok sampai End, kecuali End-nya di luar rentang
+        assert_eq!(akhir_grup(&data_macro, 7, data_macro.len()), 9);
+        assert_eq!(akhir_grup(&data_macro, 7, 9), 7);
+    }
+
+    #[test]
+    fn perkiraan_sisa_waktu() {
+        // Sekali main: sisa di putaran ini saja
+        assert_eq!(perkiraan_sisa(2.5, 1, 1, 10.0), Some(2.5));
+        // Putaran kedua dari empat: ditambah dua putaran penuh
+        assert_eq!(perkiraan_sisa(2.5, 2, 4, 10.0), Some(22.5));
+        // Waktu event yang tidak urut tidak membuat sisa negatif
+        assert_eq!(perkiraan_sisa(-1.0, 3, 3, 10.0), Some(0.0));
+        assert_eq!(perkiraan_sisa(2.5, 7, 0, 10.0), None);
+    }
+
+    // Menyimpan isi file hasil setiap kali hasil satu baris dikirim
+    struct PengirimHasilData {
+        path: PathBuf,
+        isi: Mutex<Vec<String>>
+    }
+
+    impl PengirimEvent for PengirimHasilData {
+        fn kirim_event<S: Serialize + Clone>(&self, nama: &str, _payload: S) {
+            if nama == "HasilBarisData" {
+                self.isi.lock().unwrap().push(fs::read_to_string(&self.path).unwrap_or_default());
+            }
+        }
+    }
+
+    #[test]
+    fn mainkan_per_baris_data() {
+        let _kunci = kunci();
+        let folder = folder_tes("data");
+        let path_data = folder.join("data.csv");
+        fs::write(&path_data, "nama,kota\nbudi,Bandung\nrusak,Bogor\nsiti,Solo\n").unwrap();
+        let path_hasil = folder.join("hasil.csv");
+
+        // Baris "rusak" memakai variabel yang belum diisi, baris lain tetap dimainkan
+        let data_macro = format::parse_macro("If|||var nama == rusak|||0\nSet|||a={tidak_ada}|||0\nEnd||||||0\nSet|||sapa=Halo {nama} dari {kota}|||0");
+        let opsi = OpsiPlayback {
+            data: Some(OpsiData::baru(path_data.to_string_lossy().into_owned(), Some(path_hasil.to_string_lossy().into_owned()))),
+            ..OpsiPlayback::default()
+        };
+        let pengirim = PengirimHasilData { path: path_hasil.clone(), isi: Mutex::new(Vec::new()) };
+        unsafe {
+            APAKAH_LAGI_MAIN = Some(true);
+        }
+        assert_eq!(mainkan_semua(&pengirim, &data_macro, &opsi), HasilPlayback::Gagal(String::from("1 dari 3 baris data gagal")));
+
+        let status = |isi: &str| isi.lines().skip(1).map(|baris| baris.split(',').take(3).collect::<Vec<_>>().join(",")).collect::<Vec<_>>();
+        let isi = pengirim.isi.lock().unwrap().clone();
+        assert_eq!(isi.len(), 3);
+        // Setiap baris sudah ada di file waktu hasilnya dikirim
+        assert_eq!(status(&isi[0]), ["budi,Bandung,Sukses"]);
+        assert_eq!(status(&isi[1]), ["budi,Bandung,Sukses", "rusak,Bogor,Gagal"]);
+        assert_eq!(status(&isi[2]), ["budi,Bandung,Sukses", "rusak,Bogor,Gagal", "siti,Solo,Sukses"]);
+        assert!(isi[2].contains("tidak_ada"));
+
+        // Playback yang dihentikan menandai baris itu Dihentikan dan tidak lanjut ke baris berikutnya
+        let pengirim = PengirimHasilData { path: path_hasil.clone(), isi: Mutex::new(Vec::new()) };
+        unsafe {
+            APAKAH_LAGI_MAIN = Some(false);
+        }
+        assert_eq!(mainkan_semua(&pengirim, &data_macro, &opsi), HasilPlayback::Dihentikan);
+        assert_eq!(status(&fs::read_to_string(&path_hasil).unwrap()), ["budi,Bandung,Dihentikan"]);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
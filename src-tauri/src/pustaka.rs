@@ -0,0 +1,332 @@
+// Pustaka macro: folder macro di folder config beserta index.json yang menyimpan nama, deskripsi, tag dan statistik setiap file.
+// Index dicocokkan lagi dengan isi folder setiap kali dipakai, jadi file yang ditambah, diubah atau dihapus di luar aplikasi ikut terbaca.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::event::{durasi_macro, Aksi, DataKey};
+use crate::format::{self, Format};
+use crate::pengaturan;
+
+const NAMA_FOLDER: &str = "macro";
+const NAMA_INDEX: &str = "index.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroPustaka {
+    // Nama file di folder pustaka, sekaligus dipakai sebagai id
+    pub file: String,
+    pub nama: String,
+    pub deskripsi: String,
+    pub tag: Vec<String>,
+    // Unix detik
+    pub dibuat: u64,
+    pub diubah: u64,
+    pub jumlah_event: usize,
+    pub durasi: f32
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct IndexPustaka {
+    daftar: Vec<MacroPustaka>
+}
+
+// Index dibaca, diubah lalu ditulis lagi dalam satu kali kunci supaya perubahan dari dua command tidak saling menimpa
+static KUNCI_INDEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+pub fn direktori() -> Result<PathBuf, String> {
+    let direktori = pengaturan::direktori_config().ok_or(String::from("Folder config tidak ditemukan"))?.join(NAMA_FOLDER);
+    fs::create_dir_all(&direktori).map_err(|e| format!("{}: {}", direktori.display(), e))?;
+    Ok(direktori)
+}
+
+fn detik_unix(waktu: SystemTime) -> u64 {
+    waktu.duration_since(SystemTime::UNIX_EPOCH).map(|durasi| durasi.as_secs()).unwrap_or(0)
+}
+
+fn apakah_file_macro(path: &Path) -> bool {
+    let ekstensi = path.extension().and_then(|ekstensi| ekstensi.to_str()).map(|ekstensi| ekstensi.to_lowercase());
+    path.is_file() && path.file_name().is_some_and(|nama| nama != NAMA_INDEX) && matches!(ekstensi.as_deref(), Some("rf") | Some("json") | Some("rhai"))
+}
+
+// Nama macro dipakai juga untuk nama file, karakter yang tidak boleh ada di nama file diganti dengan _
+fn nama_file(nama: &str) -> Result<String, String> {
+    let nama = nama.trim();
+    if nama.is_empty() {
+        return Err(String::from("Nama macro tidak boleh kosong"));
+    }
+
+    let aman: String = nama.chars().map(|karakter| if "/\\:*?\"<>|".contains(karakter) || karakter.is_control() { '_' } else { karakter }).collect();
+    let aman = aman.trim_start_matches('.');
+    if aman.is_empty() {
+        return Err(format!("Nama macro \"{}\" tidak valid", nama));
+    }
+
+    Ok(format!("{}.rf", aman))
+}
+
+fn rapikan_tag(tag: Vec<String>) -> Vec<String> {
+    let mut hasil: Vec<String> = Vec::new();
+    for tag in tag.into_iter().map(|tag| tag.trim().to_string()) {
+        if !tag.is_empty() && !hasil.iter().any(|ada| ada.eq_ignore_ascii_case(&tag)) {
+            hasil.push(tag);
+        }
+    }
+
+    hasil
+}
+
+fn statistik(data_macro: &[DataKey]) -> (usize, f32) {
+    let jumlah_event = data_macro.iter().filter(|val| matches!(val.aksi, Aksi::Input(..))).count();
+    (jumlah_event, durasi_macro(data_macro))
+}
+
+fn muat_index(direktori: &Path) -> IndexPustaka {
+    let contents = match fs::read_to_string(direktori.join(NAMA_INDEX)) {
+        Ok(contents) => contents,
+        Err(..) => return IndexPustaka::default()
+    };
+
+    // Index yang rusak tidak masalah, isinya dibangun ulang dari file yang ada. Hanya deskripsi dan tag yang hilang
+    serde_json::from_str(&contents).unwrap_or_else(|e| {
+        println!("Index pustaka tidak bisa dibaca, dibangun ulang: {}", e);
+        IndexPustaka::default()
+    })
+}
+
+fn simpan_index(direktori: &Path, index: &IndexPustaka) -> Result<(), String> {
+    let path = direktori.join(NAMA_INDEX);
+    let contents = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn sinkronkan(direktori: &Path, index: &mut IndexPustaka) -> Result<(), String> {
+    let mut file_ada: Vec<(String, fs::Metadata)> = Vec::new();
+    for entry in fs::read_dir(direktori).map_err(|e| format!("{}: {}", direktori.display(), e))?.flatten() {
+        let path = entry.path();
+        if !apakah_file_macro(&path) {
+            continue
+        }
+        if let (Some(nama), Ok(metadata)) = (path.file_name().and_then(|nama| nama.to_str()), entry.metadata()) {
+            file_ada.push((nama.to_string(), metadata));
+        }
+    }
+
+    index.daftar.retain(|info| file_ada.iter().any(|(nama, _)| *nama == info.file));
+
+    for (nama, metadata) in file_ada {
+        let diubah = metadata.modified().map(detik_unix).unwrap_or(0);
+        let posisi = index.daftar.iter().position(|info| info.file == nama);
+        if posisi.is_some_and(|posisi| index.daftar[posisi].diubah == diubah) {
+            continue
+        }
+
+        // File yang tidak bisa dibaca tetap ditampilkan supaya user tahu file itu ada
+        let (jumlah_event, durasi) = format::baca_macro(&direktori.join(&nama)).map(|data_macro| statistik(&data_macro)).unwrap_or((0, 0.0));
+        match posisi {
+            Some(posisi) => {
+                let info = &mut index.daftar[posisi];
+                info.diubah = diubah;
+                info.jumlah_event = jumlah_event;
+                info.durasi = durasi;
+            },
+            None => {
+                let nama_macro = Path::new(&nama).file_stem().and_then(|stem| stem.to_str()).unwrap_or(&nama).to_string();
+                index.daftar.push(MacroPustaka {
+                    file: nama,
+                    nama: nama_macro,
+                    deskripsi: String::new(),
+                    tag: Vec::new(),
+                    dibuat: metadata.created().map(detik_unix).unwrap_or(diubah),
+                    diubah,
+                    jumlah_event,
+                    durasi
+                });
+            }
+        }
+    }
+
+    index.daftar.sort_by_key(|info| info.nama.to_lowercase());
+    Ok(())
+}
+
+fn dengan_index<T>(aksi: impl FnOnce(&Path, &mut IndexPustaka) -> Result<T, String>) -> Result<T, String> {
+    let _kunci = KUNCI_INDEX.lock().unwrap();
+    let direktori = direktori()?;
+    let mut index = muat_index(&direktori);
+    sinkronkan(&direktori, &mut index)?;
+
+    let hasil = aksi(&direktori, &mut index);
+    // Hasil sinkronisasi tetap disimpan walaupun aksinya gagal
+    simpan_index(&direktori, &index)?;
+    hasil
+}
+
+fn cari_info<'a>(index: &'a mut IndexPustaka, file: &str) -> Result<&'a mut MacroPustaka, String> {
+    index.daftar.iter_mut().find(|info| info.file == file).ok_or(format!("Macro {} tidak ada di pustaka", file))
+}
+
+fn waktu_ubah(path: &Path) -> u64 {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).map(detik_unix).unwrap_or(0)
+}
+
+pub fn daftar() -> Result<Vec<MacroPustaka>, String> {
+    dengan_index(|_, index| Ok(index.daftar.clone()))
+}
+
+// Kata dicari di nama, deskripsi dan tag tanpa membedakan huruf besar kecil. Semua tag yang diminta harus dimiliki macro
+pub fn cari(kata: &str, tag: &[String]) -> Result<Vec<MacroPustaka>, String> {
+    let kata = kata.trim().to_lowercase();
+    let tag = rapikan_tag(tag.to_vec());
+
+    Ok(daftar()?.into_iter().filter(|info| {
+        let cocok_kata = kata.is_empty()
+            || info.nama.to_lowercase().contains(&kata)
+            || info.deskripsi.to_lowercase().contains(&kata)
+            || info.tag.iter().any(|tag| tag.to_lowercase().contains(&kata));
+        let cocok_tag = tag.iter().all(|dicari| info.tag.iter().any(|tag| tag.eq_ignore_ascii_case(dicari)));
+
+        cocok_kata && cocok_tag
+    }).collect())
+}
+
+pub fn path_macro(file: &str) -> Result<PathBuf, String> {
+    dengan_index(|direktori, index| cari_info(index, file).map(|info| direktori.join(&info.file)))
+}
+
+// Macro dengan nama yang sama ditimpa, tanggal dibuatnya tetap dipertahankan
+pub fn simpan(nama: &str, deskripsi: String, tag: Vec<String>, data_macro: &[DataKey]) -> Result<MacroPustaka, String> {
+    let file = nama_file(nama)?;
+    dengan_index(|direktori, index| {
+        let path = direktori.join(&file);
+        format::tulis_macro(&path, data_macro, Format::Rf)?;
+
+        let (jumlah_event, durasi) = statistik(data_macro);
+        let diubah = waktu_ubah(&path);
+        let dibuat = index.daftar.iter().find(|info| info.file == file).map(|info| info.dibuat).unwrap_or(diubah);
+        let info = MacroPustaka { file: file.clone(), nama: nama.trim().to_string(), deskripsi, tag: rapikan_tag(tag), dibuat, diubah, jumlah_event, durasi };
+
+        index.daftar.retain(|lama| lama.file != file);
+        index.daftar.push(info.clone());
+        index.daftar.sort_by_key(|info| info.nama.to_lowercase());
+        Ok(info)
+    })
+}
+
+pub fn ubah_info(file: &str, deskripsi: String, tag: Vec<String>) -> Result<MacroPustaka, String> {
+    dengan_index(|_, index| {
+        let info = cari_info(index, file)?;
+        info.deskripsi = deskripsi;
+        info.tag = rapikan_tag(tag);
+        Ok(info.clone())
+    })
+}
+
+pub fn ganti_nama(file: &str, nama_baru: &str) -> Result<MacroPustaka, String> {
+    let file_baru = nama_file(nama_baru)?;
+    dengan_index(|direktori, index| {
+        if file_baru != file && index.daftar.iter().any(|info| info.file == file_baru) {
+            return Err(format!("Macro {} sudah ada", nama_baru.trim()));
+        }
+
+        let info = cari_info(index, file)?;
+        fs::rename(direktori.join(file), direktori.join(&file_baru)).map_err(|e| e.to_string())?;
+        info.file = file_baru;
+        info.nama = nama_baru.trim().to_string();
+        let info = info.clone();
+
+        index.daftar.sort_by_key(|info| info.nama.to_lowercase());
+        Ok(info)
+    })
+}
+
+pub fn duplikat(file: &str, nama_baru: &str) -> Result<MacroPustaka, String> {
+    let file_baru = nama_file(nama_baru)?;
+    dengan_index(|direktori, index| {
+        if index.daftar.iter().any(|info| info.file == file_baru) {
+            return Err(format!("Macro {} sudah ada", nama_baru.trim()));
+        }
+
+        let mut info = cari_info(index, file)?.clone();
+        let path_baru = direktori.join(&file_baru);
+        fs::copy(direktori.join(file), &path_baru).map_err(|e| e.to_string())?;
+        info.file = file_baru;
+        info.nama = nama_baru.trim().to_string();
+        info.diubah = waktu_ubah(&path_baru);
+        info.dibuat = info.diubah;
+
+        index.daftar.push(info.clone());
+        index.daftar.sort_by_key(|info| info.nama.to_lowercase());
+        Ok(info)
+    })
+}
+
+pub fn hapus(file: &str) -> Result<(), String> {
+    dengan_index(|direktori, index| {
+        cari_info(index, file)?;
+        fs::remove_file(direktori.join(file)).map_err(|e| e.to_string())?;
+        index.daftar.retain(|info| info.file != file);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::time::Duration;
+    use super::*;
+
+    #[test]
+    fn nama_file_aman() {
+        assert_eq!(nama_file("  Login harian "), Ok(String::from("Login harian.rf")));
+        assert_eq!(nama_file("a/b\\c:d*?\"<>|e\tf"), Ok(String::from("a_b_c_d______e_f.rf")));
+        // Tidak bisa jadi file tersembunyi atau keluar dari folder pustaka
+        assert_eq!(nama_file("../rahasia"), Ok(String::from("_rahasia.rf")));
+        assert_eq!(nama_file(".env"), Ok(String::from("env.rf")));
+        assert!(nama_file("   ").is_err());
+        assert!(nama_file("...").is_err());
+
+        assert_eq!(rapikan_tag(vec![String::from(" kerja "), String::from("Kerja"), String::from(""), String::from("harian")]), vec![String::from("kerja"), String::from("harian")]);
+    }
+
+    #[test]
+    fn index_disinkronkan_dengan_folder() {
+        let direktori = std::env::temp_dir().join(format!("recordflow-pustaka-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&direktori);
+        fs::create_dir_all(&direktori).unwrap();
+        fs::write(direktori.join("b.rf"), "Key Press KeyA|||KeyA|||0\nKey Release KeyA|||KeyA|||1.5").unwrap();
+        fs::write(direktori.join("A.json"), "rusak").unwrap();
+        fs::write(direktori.join("catatan.txt"), "bukan macro").unwrap();
+
+        // File baru masuk index, yang tidak bisa dibaca tetap ditampilkan, urut nama
+        let mut index = IndexPustaka::default();
+        sinkronkan(&direktori, &mut index).unwrap();
+        let ringkas = |index: &IndexPustaka| index.daftar.iter().map(|info| (info.file.clone(), info.nama.clone(), info.jumlah_event, info.durasi)).collect::<Vec<_>>();
+        assert_eq!(ringkas(&index), vec![(String::from("A.json"), String::from("A"), 0, 0.0), (String::from("b.rf"), String::from("b"), 2, 1.5)]);
+
+        // Deskripsi dan tag dipertahankan, file yang berubah dihitung ulang, file yang dihapus dibuang
+        index.daftar[1].deskripsi = String::from("isi form");
+        index.daftar[1].tag = vec![String::from("kerja")];
+        simpan_index(&direktori, &index).unwrap();
+        fs::write(direktori.join("b.rf"), "Key Press KeyA|||KeyA|||0").unwrap();
+        File::options().write(true).open(direktori.join("b.rf")).unwrap().set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        fs::remove_file(direktori.join("A.json")).unwrap();
+
+        let mut index = muat_index(&direktori);
+        sinkronkan(&direktori, &mut index).unwrap();
+        assert_eq!(ringkas(&index), vec![(String::from("b.rf"), String::from("b"), 1, 0.0)]);
+        assert_eq!((index.daftar[0].deskripsi.as_str(), index.daftar[0].tag.clone()), ("isi form", vec![String::from("kerja")]));
+
+        // Index yang rusak dibangun ulang dari isi folder
+        fs::write(direktori.join(NAMA_INDEX), "{").unwrap();
+        let mut index = muat_index(&direktori);
+        sinkronkan(&direktori, &mut index).unwrap();
+        assert_eq!(ringkas(&index), vec![(String::from("b.rf"), String::from("b"), 1, 0.0)]);
+        assert_eq!(index.daftar[0].deskripsi, "");
+
+        fs::remove_dir_all(&direktori).unwrap();
+    }
+}
@@ -0,0 +1,41 @@
+use std::time::SystemTime;
+use enigo::{Enigo, MouseControllable};
+use rdev::EventType;
+
+use crate::event::{DataKey, UpdateKey};
+use crate::{jendela, pengaturan, WAKTU_MULAI_RECORD};
+
+// Detik sejak recording dimulai
+pub fn selisih_waktu() -> f32 {
+    let waktu_sekarang = SystemTime::now();
+    unsafe {
+        match waktu_sekarang.duration_since(*WAKTU_MULAI_RECORD) {
+            Ok(durasi) =>  durasi.as_secs_f32(),
+            Err(..) => 0.0
+        }
+    }
+}
+
+pub fn mulai_waktu_record() {
+    unsafe {
+        WAKTU_MULAI_RECORD = once_cell::sync::Lazy::new(SystemTime::now);
+    }
+}
+
+// Mengubah event dari rdev menjadi DataKey, None untuk event yang tidak direkam (mouse move, kecuali diaktifkan di pengaturan)
+pub fn rekam_event(event_type: EventType, waktu: f32, enigo: &Enigo) -> Option<DataKey> {
+    match event_type {
+        EventType::ButtonPress(..) | EventType::ButtonRelease(..) => {
+            let posisi_mos = enigo.mouse_location();
+            let mut data = DataKey::input(event_type, format!("{}, {}", posisi_mos.0, posisi_mos.1), waktu);
+            if matches!(event_type, EventType::ButtonPress(..)) && pengaturan::baca(|pengaturan| pengaturan.rekam_judul_window) {
+                data.window = jendela::judul_aktif();
+            }
+            Some(data)
+        },
+        EventType::Wheel { delta_x, delta_y } => Some(DataKey::input(event_type, format!("{}, {}", delta_x, delta_y), waktu)),
+        EventType::KeyPress(key) | EventType::KeyRelease(key) => Some(DataKey::input(event_type, UpdateKey(&key).to_string(), waktu)),
+        EventType::MouseMove { x, y } if pengaturan::baca(|pengaturan| pengaturan.rekam_gerakan_mouse) => Some(DataKey::input(event_type, format!("{}, {}", x, y), waktu)),
+        EventType::MouseMove { .. } => None
+    }
+}
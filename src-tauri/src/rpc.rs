@@ -0,0 +1,267 @@
+// Server JSON-RPC 2.0 lokal lewat Unix domain socket, supaya proses lain (test harness dan sebagainya) bisa mengontrol recorder.
+// Hanya aktif kalau RECORDFLOW_SOCKET berisi path socket. Satu pesan JSON per baris, ke dua arah.
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use macro_recroder::event::RawDataKey;
+use macro_recroder::player::{self, OpsiPlayback};
+use macro_recroder::{siaran, APAKAH_LAGI_MAIN, APAKAH_RECORD, DATA_MACRO};
+
+use crate::{buka_file, mulai_record, siapkan_playback, simpan_file, PengirimWindow};
+
+pub const ENV_SOCKET: &str = "RECORDFLOW_SOCKET";
+
+const ERROR_PARSE: i64 = -32700;
+const ERROR_REQUEST: i64 = -32600;
+const ERROR_METHOD: i64 = -32601;
+const ERROR_PARAMS: i64 = -32602;
+const ERROR_APLIKASI: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value
+}
+
+#[derive(Debug, Deserialize)]
+struct ParamsPath {
+    path: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ParamsMainkan {
+    opsi: Option<OpsiPlayback>
+}
+
+struct ErrorRpc {
+    kode: i64,
+    pesan: String
+}
+
+impl ErrorRpc {
+    fn baru(kode: i64, pesan: impl Into<String>) -> Self {
+        ErrorRpc { kode, pesan: pesan.into() }
+    }
+}
+
+fn params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, ErrorRpc> {
+    // Params boleh tidak diisi untuk method yang semua parameternya opsional
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| ErrorRpc::baru(ERROR_PARAMS, e.to_string()))
+}
+
+fn status() -> Value {
+    unsafe {
+        json!({
+            "record": APAKAH_RECORD.unwrap_or(false),
+            "lagi_main": APAKAH_LAGI_MAIN.unwrap_or(false),
+            "dijeda": player::APAKAH_DIJEDA.unwrap_or(false),
+            "jumlah_event": DATA_MACRO.lock().unwrap().len()
+        })
+    }
+}
+
+fn jalankan_method(window: &tauri::Window, method: &str, params_request: Value) -> Result<Value, ErrorRpc> {
+    match method {
+        "status" => Ok(status()),
+        "data_macro" => {
+            let data_macro: Vec<RawDataKey> = unsafe { DATA_MACRO.lock().unwrap().iter().enumerate().map(|(id, val)| val.ke_raw(id)).collect() };
+            Ok(json!(data_macro))
+        },
+        "mulai_record" => {
+            mulai_record(window.clone());
+            Ok(status())
+        },
+        "mainkan_recorder" => {
+            let opsi = params::<ParamsMainkan>(params_request)?.opsi.unwrap_or_default();
+            let data_macro = siapkan_playback(&opsi).map_err(|e| ErrorRpc::baru(ERROR_APLIKASI, e))?;
+            let dimulai = data_macro.is_some();
+            if let Some(data_macro) = data_macro {
+                // Playback jalan di thread sendiri, selesainya dikabarkan lewat notifikasi SelesaiRecording
+                let window = window.clone();
+                thread::spawn(move || player::mainkan(&PengirimWindow(window), &data_macro, &opsi));
+            }
+
+            Ok(json!({ "dimulai": dimulai }))
+        },
+        "hentikan_playback" => {
+            unsafe {
+                APAKAH_LAGI_MAIN = Some(false);
+            }
+            Ok(status())
+        },
+        "jeda_playback" | "lanjutkan_playback" => {
+            if !player::atur_jeda(method == "jeda_playback") {
+                return Err(ErrorRpc::baru(ERROR_APLIKASI, "Tidak ada playback yang berjalan"));
+            }
+            Ok(status())
+        },
+        "simpan_file" => {
+            let ParamsPath { path } = params(params_request)?;
+            simpan_file(window.clone(), path).map_err(|e| ErrorRpc::baru(ERROR_APLIKASI, e))?;
+            Ok(Value::Bool(true))
+        },
+        "buka_file" => {
+            let ParamsPath { path } = params(params_request)?;
+            buka_file(window.clone(), path).map_err(|e| ErrorRpc::baru(ERROR_APLIKASI, e))?;
+            Ok(status())
+        },
+        _ => Err(ErrorRpc::baru(ERROR_METHOD, format!("Method {} tidak ada", method)))
+    }
+}
+
+fn tulis_pesan<W: Write>(penulis: &Mutex<W>, pesan: &Value) -> bool {
+    let mut penulis = penulis.lock().unwrap();
+    writeln!(penulis, "{}", pesan).and_then(|_| penulis.flush()).is_ok()
+}
+
+// Meneruskan semua event ke klien sebagai notifikasi JSON-RPC sampai koneksinya putus
+fn teruskan_notifikasi<W: Write + Send + 'static>(penulis: Arc<Mutex<W>>) {
+    let penerima = siaran::langganan();
+    thread::spawn(move || {
+        for pesan in penerima {
+            let notifikasi = json!({ "jsonrpc": "2.0", "method": pesan.nama, "params": pesan.payload });
+            if !tulis_pesan(&penulis, &notifikasi) {
+                break
+            }
+        }
+    });
+}
+
+fn layani_klien<R: BufRead, W: Write + Send + 'static>(window: tauri::Window, pembaca: R, penulis: W) {
+    let penulis = Arc::new(Mutex::new(penulis));
+    let mut sudah_langganan = false;
+
+    for baris in pembaca.lines() {
+        let baris = match baris {
+            Ok(baris) => baris,
+            Err(..) => break
+        };
+        if baris.trim().is_empty() {
+            continue
+        }
+
+        let (id, hasil) = match serde_json::from_str::<Request>(&baris) {
+            Err(e) => (Value::Null, Err(ErrorRpc::baru(ERROR_PARSE, e.to_string()))),
+            Ok(request) if request.jsonrpc != "2.0" => (request.id.unwrap_or(Value::Null), Err(ErrorRpc::baru(ERROR_REQUEST, "jsonrpc harus \"2.0\""))),
+            Ok(request) => {
+                let hasil = if request.method == "langganan" {
+                    if !sudah_langganan {
+                        sudah_langganan = true;
+                        teruskan_notifikasi(penulis.clone());
+                    }
+                    Ok(Value::Bool(true))
+                } else {
+                    jalankan_method(&window, &request.method, request.params)
+                };
+
+                // Request tanpa id adalah notifikasi, tidak perlu dibalas
+                match request.id {
+                    Some(id) => (id, hasil),
+                    None => continue
+                }
+            }
+        };
+
+        let balasan = match hasil {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": error.kode, "message": error.pesan } })
+        };
+        if !tulis_pesan(&penulis, &balasan) {
+            break
+        }
+    }
+}
+
+// Socket sisa dari sesi sebelumnya dihapus, tapi file lain di path itu tidak disentuh.
+// Socket dibuat dengan umask 177 supaya hanya user yang sama yang bisa konek, bahkan sebelum sempat di-chmod
+#[cfg(unix)]
+fn buka_socket(path_socket: &str) -> Result<std::os::unix::net::UnixListener, String> {
+    use std::fs;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+
+    match fs::symlink_metadata(path_socket) {
+        Ok(meta) if meta.file_type().is_socket() => fs::remove_file(path_socket).map_err(|e| format!("{}: {}", path_socket, e))?,
+        Ok(..) => return Err(format!("{} sudah ada dan bukan socket", path_socket)),
+        Err(..) => {}
+    }
+
+    let umask_lama = unsafe { libc::umask(0o177) };
+    let hasil = UnixListener::bind(path_socket);
+    unsafe {
+        libc::umask(umask_lama);
+    }
+    hasil.map_err(|e| format!("{}: {}", path_socket, e))
+}
+
+#[cfg(unix)]
+pub fn jalankan_jika_diaktifkan(window: tauri::Window) {
+    let path_socket = match std::env::var(ENV_SOCKET) {
+        Ok(path) if !path.is_empty() => path,
+        _ => return
+    };
+
+    let listener = match buka_socket(&path_socket) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("Tidak bisa membuka socket {}", e);
+            return;
+        }
+    };
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(..) => continue
+            };
+            let penulis = match stream.try_clone() {
+                Ok(penulis) => penulis,
+                Err(..) => continue
+            };
+
+            let window = window.clone();
+            thread::spawn(move || layani_klien(window, BufReader::new(stream), penulis));
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn jalankan_jika_diaktifkan(_window: tauri::Window) {}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs;
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    use super::*;
+
+    #[test]
+    fn socket_hanya_menimpa_socket() {
+        let folder = std::env::temp_dir().join(format!("recordflow-rpc-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(&folder).unwrap();
+
+        // File biasa di path socket tidak dihapus
+        let path_file = folder.join("bukan-socket");
+        fs::write(&path_file, "penting").unwrap();
+        assert!(buka_socket(path_file.to_str().unwrap()).unwrap_err().contains("bukan socket"));
+        assert_eq!(fs::read_to_string(&path_file).unwrap(), "penting");
+
+        // Socket sisa sesi sebelumnya diganti, dan hanya bisa dibuka pemiliknya
+        let path_socket = folder.join("rpc.sock");
+        drop(buka_socket(path_socket.to_str().unwrap()).unwrap());
+        let _listener = buka_socket(path_socket.to_str().unwrap()).unwrap();
+        let meta = fs::symlink_metadata(&path_socket).unwrap();
+        assert!(meta.file_type().is_socket());
+        assert_eq!(meta.permissions().mode() & 0o777, 0o600);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::Mutex;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+// Event yang sama dengan yang dikirim ke window, untuk diteruskan ke klien di luar aplikasi (socket RPC dan sebagainya)
+#[derive(Debug, Clone, Serialize)]
+pub struct PesanEvent {
+    pub nama: String,
+    pub payload: serde_json::Value
+}
+
+static PELANGGAN: Lazy<Mutex<Vec<Sender<PesanEvent>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub fn langganan() -> Receiver<PesanEvent> {
+    let (pengirim, penerima) = channel();
+    PELANGGAN.lock().unwrap().push(pengirim);
+    penerima
+}
+
+pub fn siarkan<S: Serialize>(nama: &str, payload: &S) {
+    let mut pelanggan = PELANGGAN.lock().unwrap();
+    if pelanggan.is_empty() {
+        return;
+    }
+
+    let pesan = PesanEvent { nama: nama.to_string(), payload: serde_json::to_value(payload).unwrap_or_default() };
+    // Pelanggan yang penerimanya sudah ditutup langsung dibuang
+    pelanggan.retain(|pengirim| pengirim.send(pesan.clone()).is_ok());
+}
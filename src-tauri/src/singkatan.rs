@@ -0,0 +1,245 @@
+// Ekspansi teks: mengetik singkatan seperti ";sig" menghapus singkatan itu lalu mengetik teks pengganti atau memainkan macro dari pustaka.
+// Disimpan di singkatan.json di folder config. Input yang dikirim sendiri oleh engine ini tidak ikut dicocokkan.
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use enigo::{Enigo, KeyboardControllable};
+use once_cell::sync::Lazy;
+use rdev::{EventType, Key};
+use serde::{Deserialize, Serialize};
+
+use crate::{pengaturan, pustaka, send};
+
+const NAMA_FILE: &str = "singkatan.json";
+const MAKS_PANJANG: usize = 32;
+// Event dari input yang baru dikirim kadang baru sampai ke listener sesudah pengiriman selesai
+const JEDA_ABAIKAN: Duration = Duration::from_millis(150);
+
+// Dari frontend dikirim sebagai { jenis: "Teks", nilai: "Salam, Budi" } atau { jenis: "Macro", nilai: "<file di pustaka>" }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "jenis", content = "nilai")]
+pub enum AksiSingkatan {
+    Teks(String),
+    Macro(String)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Singkatan {
+    pub pemicu: String,
+    pub aksi: AksiSingkatan,
+    // Kalau false, ";SIG" juga cocok dengan ";sig"
+    #[serde(default)]
+    pub peka_huruf: bool,
+    // Hanya cocok kalau karakter sebelum pemicu bukan huruf atau angka, jadi "kasig" tidak memicu "sig"
+    #[serde(default = "default_awal_kata")]
+    pub awal_kata: bool
+}
+
+fn default_awal_kata() -> bool {
+    true
+}
+
+#[derive(Default)]
+struct StatusSingkatan {
+    // Karakter terakhir yang diketik. Awal buffer dianggap batas kata
+    buffer: Vec<char>,
+    menyuntik: bool,
+    abaikan_sampai: Option<Instant>
+}
+
+static DAFTAR_SINGKATAN: Lazy<Mutex<Vec<Singkatan>>> = Lazy::new(|| Mutex::new(pengaturan::muat_json(NAMA_FILE)));
+static STATUS_SINGKATAN: Lazy<Mutex<StatusSingkatan>> = Lazy::new(|| Mutex::new(StatusSingkatan::default()));
+
+fn periksa(singkatan: &Singkatan) -> Result<(), String> {
+    if singkatan.pemicu.is_empty() || singkatan.pemicu.chars().any(|karakter| karakter.is_whitespace() || karakter.is_control()) {
+        return Err(String::from("Pemicu tidak boleh kosong atau berisi spasi"));
+    }
+    if singkatan.pemicu.chars().count() > MAKS_PANJANG {
+        return Err(format!("Pemicu maksimal {} karakter", MAKS_PANJANG));
+    }
+
+    match &singkatan.aksi {
+        AksiSingkatan::Teks(teks) if teks.is_empty() => Err(String::from("Teks pengganti tidak boleh kosong")),
+        AksiSingkatan::Teks(..) => Ok(()),
+        AksiSingkatan::Macro(file) => pustaka::path_macro(file).map(|_| ())
+    }
+}
+
+pub fn daftar() -> Vec<Singkatan> {
+    DAFTAR_SINGKATAN.lock().unwrap().clone()
+}
+
+// Singkatan dengan pemicu yang sama diganti
+pub fn atur(singkatan: Singkatan) -> Result<Singkatan, String> {
+    periksa(&singkatan)?;
+
+    let mut daftar = DAFTAR_SINGKATAN.lock().unwrap();
+    daftar.retain(|lama| lama.pemicu != singkatan.pemicu);
+    daftar.push(singkatan.clone());
+    pengaturan::simpan_json(NAMA_FILE, &*daftar)?;
+
+    Ok(singkatan)
+}
+
+pub fn hapus(pemicu: &str) -> Result<(), String> {
+    let mut daftar = DAFTAR_SINGKATAN.lock().unwrap();
+    let jumlah = daftar.len();
+    daftar.retain(|singkatan| singkatan.pemicu != pemicu);
+    if daftar.len() == jumlah {
+        return Err(format!("Singkatan {} tidak ada", pemicu));
+    }
+
+    pengaturan::simpan_json(NAMA_FILE, &*daftar)
+}
+
+// Dipanggil waktu recording atau playback berjalan, ketikan saat itu tidak boleh tersambung dengan ketikan sesudahnya
+pub fn kosongkan() {
+    STATUS_SINGKATAN.lock().unwrap().buffer.clear();
+}
+
+fn cocok(buffer: &[char], singkatan: &Singkatan) -> bool {
+    let pemicu: Vec<char> = singkatan.pemicu.chars().collect();
+    if buffer.len() < pemicu.len() {
+        return false;
+    }
+
+    let awal = buffer.len() - pemicu.len();
+    let sama = buffer[awal..].iter().zip(pemicu.iter()).all(|(a, b)| {
+        if singkatan.peka_huruf { a == b } else { a.to_lowercase().eq(b.to_lowercase()) }
+    });
+    let batas_kata = !singkatan.awal_kata || awal == 0 || !buffer[awal - 1].is_alphanumeric();
+
+    sama && batas_kata
+}
+
+// Mengembalikan singkatan yang cocok dan jumlah karakter yang harus dihapus. nama adalah karakter yang dihasilkan tombol (Event::name dari rdev)
+pub fn proses_event(event_type: &EventType, nama: Option<&str>) -> Option<(Singkatan, usize)> {
+    let mut status = STATUS_SINGKATAN.lock().unwrap();
+    if status.menyuntik || status.abaikan_sampai.is_some_and(|sampai| Instant::now() < sampai) {
+        return None;
+    }
+
+    match *event_type {
+        EventType::KeyPress(Key::Backspace) => {
+            status.buffer.pop();
+            return None;
+        },
+        EventType::KeyPress(Key::ShiftLeft | Key::ShiftRight | Key::CapsLock | Key::AltGr) => return None,
+        EventType::KeyPress(..) => match nama.filter(|nama| !nama.is_empty() && !nama.chars().any(|karakter| karakter.is_control())) {
+            Some(nama) => status.buffer.extend(nama.chars()),
+            // Enter, panah, Ctrl+... dan sebagainya memindahkan kursor atau bukan ketikan biasa
+            None => {
+                status.buffer.clear();
+                return None;
+            }
+        },
+        // Klik bisa memindahkan kursor ke tempat lain
+        EventType::ButtonPress(..) => {
+            status.buffer.clear();
+            return None;
+        },
+        _ => return None
+    }
+
+    if status.buffer.len() > MAKS_PANJANG * 2 {
+        let lebih = status.buffer.len() - MAKS_PANJANG * 2;
+        status.buffer.drain(..lebih);
+    }
+
+    let singkatan = DAFTAR_SINGKATAN.lock().unwrap().iter().find(|singkatan| cocok(&status.buffer, singkatan)).cloned()?;
+    status.buffer.clear();
+    let jumlah_hapus = singkatan.pemicu.chars().count();
+    Some((singkatan, jumlah_hapus))
+}
+
+// Menghapus pemicu yang sudah diketik lalu mengetik teks pengganti (kalau ada)
+pub fn suntik(jumlah_hapus: usize, teks: &str) {
+    STATUS_SINGKATAN.lock().unwrap().menyuntik = true;
+
+    for _ in 0..jumlah_hapus {
+        send(&EventType::KeyPress(Key::Backspace));
+        send(&EventType::KeyRelease(Key::Backspace));
+        thread::sleep(Duration::from_millis(5));
+    }
+    if !teks.is_empty() {
+        Enigo::new().key_sequence(teks);
+    }
+
+    let mut status = STATUS_SINGKATAN.lock().unwrap();
+    status.menyuntik = false;
+    status.abaikan_sampai = Some(Instant::now() + JEDA_ABAIKAN);
+    status.buffer.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn singkatan(pemicu: &str, peka_huruf: bool, awal_kata: bool) -> Singkatan {
+        Singkatan { pemicu: pemicu.to_string(), aksi: AksiSingkatan::Teks(String::from("Salam, Budi")), peka_huruf, awal_kata }
+    }
+
+    fn buffer(teks: &str) -> Vec<char> {
+        teks.chars().collect()
+    }
+
+    #[test]
+    fn cocok_batas_kata_dan_huruf() {
+        let sig = singkatan("sig", false, true);
+        assert!(cocok(&buffer("sig"), &sig));
+        assert!(cocok(&buffer("halo sig"), &sig));
+        assert!(cocok(&buffer("(sig"), &sig));
+        assert!(cocok(&buffer("halo SIG"), &sig));
+        assert!(!cocok(&buffer("kasig"), &sig));
+        assert!(!cocok(&buffer("ka1sig"), &sig));
+        assert!(!cocok(&buffer("si"), &sig));
+        assert!(!cocok(&buffer("sig "), &sig));
+
+        // Tanpa awal_kata boleh di tengah kata
+        assert!(cocok(&buffer("kasig"), &singkatan("sig", false, false)));
+
+        let peka = singkatan(";Sig", true, true);
+        assert!(cocok(&buffer(";Sig"), &peka));
+        assert!(!cocok(&buffer(";sig"), &peka));
+        // Huruf non-ASCII juga dibandingkan tanpa membedakan besar kecil
+        assert!(cocok(&buffer("ÉTÉ"), &singkatan("été", false, true)));
+    }
+
+    #[test]
+    fn pemicu_tidak_valid() {
+        assert!(periksa(&singkatan("", false, true)).is_err());
+        assert!(periksa(&singkatan("a b", false, true)).is_err());
+        assert!(periksa(&singkatan(&"x".repeat(MAKS_PANJANG + 1), false, true)).is_err());
+        assert!(periksa(&singkatan(";sig", false, true)).is_ok());
+        let mut kosong = singkatan(";sig", false, true);
+        kosong.aksi = AksiSingkatan::Teks(String::new());
+        assert!(periksa(&kosong).is_err());
+    }
+
+    #[test]
+    fn ketikan_dicocokkan() {
+        *DAFTAR_SINGKATAN.lock().unwrap() = vec![singkatan(";sig", false, true)];
+        kosongkan();
+        let ketik = |nama: &str| proses_event(&EventType::KeyPress(Key::KeyA), Some(nama)).map(|(singkatan, jumlah)| (singkatan.pemicu, jumlah));
+
+        // Backspace menghapus karakter terakhir di buffer
+        for nama in [";", "s", "x"] {
+            assert_eq!(ketik(nama), None);
+        }
+        assert!(proses_event(&EventType::KeyPress(Key::Backspace), None).is_none());
+        assert_eq!(ketik("i"), None);
+        assert_eq!(ketik("g"), Some((String::from(";sig"), 4)));
+
+        // Klik atau tombol yang bukan ketikan memutus singkatan
+        ketik(";");
+        ketik("s");
+        proses_event(&EventType::ButtonPress(rdev::Button::Left), None);
+        ketik("i");
+        assert_eq!(ketik("g"), None);
+        ketik(";");
+        ketik("s");
+        proses_event(&EventType::KeyPress(Key::LeftArrow), None);
+        ketik("i");
+        assert_eq!(ketik("g"), None);
+    }
+}
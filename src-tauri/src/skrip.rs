@@ -0,0 +1,213 @@
+// Skrip Rhai untuk macro yang butuh logika, dijalankan sebagai aksi Script di dalam macro atau dari file .rhai.
+// Fungsi yang tersedia: press, release, tap, click, move_to, type_text, wait, play, get_var dan set_var.
+// Skrip tidak bisa import modul atau eval, dan dibatasi jumlah operasi serta lama jalannya sesuai pengaturan.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use enigo::{Enigo, KeyboardControllable};
+use rdev::{Button, EventType, Key};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+
+use crate::{pengaturan, pintasan, player, pustaka, send};
+
+type HasilRhai<T> = Result<T, Box<EvalAltResult>>;
+
+#[derive(Default)]
+struct StatusSkrip {
+    // Variabel macro (aksi Set), bisa dibaca dan diubah dari skrip
+    variabel: HashMap<String, String>,
+    // Lama wait() dan play(), tidak dihitung ke batas waktu skrip
+    di_luar_skrip: Duration
+}
+
+fn tombol(nama: &str) -> HasilRhai<Key> {
+    pintasan::parse_tombol(nama).map_err(Into::into)
+}
+
+fn tombol_mouse(nama: &str) -> HasilRhai<Button> {
+    match nama {
+        "Left" => Ok(Button::Left),
+        "Right" => Ok(Button::Right),
+        "Middle" => Ok(Button::Middle),
+        _ => Err(format!("Button {} tidak dikenal", nama).into())
+    }
+}
+
+fn klik(x: i64, y: i64, button: Button) {
+    send(&EventType::MouseMove { x: x as f64, y: y as f64 });
+    send(&EventType::ButtonPress(button));
+    send(&EventType::ButtonRelease(button));
+}
+
+fn buat_engine(status: &Rc<RefCell<StatusSkrip>>) -> Engine {
+    let batas = pengaturan::baca(|pengaturan| pengaturan.skrip.clone());
+    let mut engine = Engine::new();
+    engine.set_max_operations(batas.maks_operasi)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(1 << 20)
+        .set_max_array_size(10_000)
+        .set_max_map_size(10_000)
+        .set_max_modules(0)
+        .set_module_resolver(DummyModuleResolver::new())
+        .disable_symbol("eval");
+
+    let mulai = Instant::now();
+    let status_progres = status.clone();
+    engine.on_progress(move |_| {
+        if !player::apakah_lagi_main() {
+            return Some(Dynamic::from("Playback dihentikan"));
+        }
+
+        let berjalan = mulai.elapsed().saturating_sub(status_progres.borrow().di_luar_skrip);
+        if batas.maks_detik != 0 && berjalan > Duration::from_secs(batas.maks_detik) {
+            return Some(Dynamic::from(format!("Skrip berjalan lebih dari {} detik", batas.maks_detik)));
+        }
+        None
+    });
+    engine.on_print(|teks| println!("[skrip] {}", teks));
+
+    engine.register_fn("press", |nama: &str| -> HasilRhai<()> {
+        send(&EventType::KeyPress(tombol(nama)?));
+        Ok(())
+    });
+    engine.register_fn("release", |nama: &str| -> HasilRhai<()> {
+        send(&EventType::KeyRelease(tombol(nama)?));
+        Ok(())
+    });
+    engine.register_fn("tap", |nama: &str| -> HasilRhai<()> {
+        let key = tombol(nama)?;
+        send(&EventType::KeyPress(key));
+        send(&EventType::KeyRelease(key));
+        Ok(())
+    });
+    engine.register_fn("click", |x: i64, y: i64| klik(x, y, Button::Left));
+    engine.register_fn("click", |x: i64, y: i64, nama: &str| -> HasilRhai<()> {
+        klik(x, y, tombol_mouse(nama)?);
+        Ok(())
+    });
+    engine.register_fn("move_to", |x: i64, y: i64| send(&EventType::MouseMove { x: x as f64, y: y as f64 }));
+    engine.register_fn("type_text", |teks: &str| Enigo::new().key_sequence(teks));
+
+    let status_tunggu = status.clone();
+    engine.register_fn("wait", move |ms: i64| -> HasilRhai<()> {
+        let mulai = Instant::now();
+        let lanjut = player::tidur(Duration::from_millis(ms.max(0) as u64));
+        status_tunggu.borrow_mut().di_luar_skrip += mulai.elapsed();
+        if lanjut { Ok(()) } else { Err("Playback dihentikan".into()) }
+    });
+
+    // Memainkan macro lain dari pustaka, variabelnya ikut dipakai bersama
+    let status_main = status.clone();
+    engine.register_fn("play", move |file: &str| -> HasilRhai<()> {
+        let path = pustaka::path_macro(file)?;
+
+        let mulai = Instant::now();
+        let mut variabel = status_main.borrow().variabel.clone();
+        let hasil = player::mainkan_sisipan(&path, &mut variabel);
+        let mut status = status_main.borrow_mut();
+        status.variabel = variabel;
+        status.di_luar_skrip += mulai.elapsed();
+        hasil.map_err(Into::into)
+    });
+
+    let status_baca = status.clone();
+    engine.register_fn("get_var", move |nama: &str| status_baca.borrow().variabel.get(nama).cloned().unwrap_or_default());
+    let status_tulis = status.clone();
+    engine.register_fn("set_var", move |nama: &str, nilai: &str| {
+        status_tulis.borrow_mut().variabel.insert(nama.to_string(), nilai.to_string());
+    });
+    let status_tulis = status.clone();
+    engine.register_fn("set_var", move |nama: &str, nilai: i64| {
+        status_tulis.borrow_mut().variabel.insert(nama.to_string(), nilai.to_string());
+    });
+
+    engine
+}
+
+// Alasan dari on_progress tidak ikut ditulis di pesan ErrorTerminated bawaan Rhai
+fn pesan_error(e: &EvalAltResult) -> String {
+    match e {
+        EvalAltResult::ErrorTerminated(alasan, _) if alasan.is_string() => alasan.clone().into_string().unwrap_or_default(),
+        e => e.to_string()
+    }
+}
+
+// Hanya memeriksa sintaks, dipakai sebelum skrip disimpan ke macro
+pub fn periksa(kode: &str) -> Result<(), String> {
+    let status = Rc::new(RefCell::new(StatusSkrip::default()));
+    buat_engine(&status).compile(kode).map(|_| ()).map_err(|e| e.to_string())
+}
+
+// Dijalankan di thread player. Variabel yang diubah skrip dikembalikan ke macro
+pub fn jalankan(kode: &str, variabel: &mut HashMap<String, String>) -> Result<(), String> {
+    let status = Rc::new(RefCell::new(StatusSkrip { variabel: variabel.clone(), ..Default::default() }));
+    let engine = buat_engine(&status);
+
+    let hasil = engine.compile(kode).map_err(|e| e.to_string())
+        .and_then(|ast| engine.run_ast_with_scope(&mut Scope::new(), &ast).map_err(|e| pesan_error(&e)));
+    variabel.clone_from(&status.borrow().variabel);
+    hasil
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::tests::kunci;
+    use crate::APAKAH_LAGI_MAIN;
+
+    fn jalankan_main(kode: &str, variabel: &mut HashMap<String, String>) -> Result<(), String> {
+        unsafe {
+            APAKAH_LAGI_MAIN = Some(true);
+        }
+        jalankan(kode, variabel)
+    }
+
+    #[test]
+    fn periksa_sintaks() {
+        assert!(periksa("let a = 1; if a > 0 { set_var(\"b\", a); }").is_ok());
+        assert!(periksa("let a = ;").is_err());
+        assert!(periksa("if true {").is_err());
+    }
+
+    #[test]
+    fn eval_dan_import_ditolak() {
+        let _kunci = kunci();
+        assert!(periksa("eval(\"1 + 1\")").is_err());
+        assert!(jalankan_main("import \"modul\" as m;", &mut HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn variabel_bersama_macro() {
+        let _kunci = kunci();
+        let mut variabel = HashMap::from([(String::from("nama"), String::from("Budi"))]);
+        let kode = "set_var(\"salam\", \"Halo \" + get_var(\"nama\")); set_var(\"jumlah\", 40 + 2); set_var(\"nama\", get_var(\"tidak_ada\"));";
+        jalankan_main(kode, &mut variabel).unwrap();
+        assert_eq!(variabel["salam"], "Halo Budi");
+        assert_eq!(variabel["jumlah"], "42");
+        assert_eq!(variabel["nama"], "");
+
+        // Perubahan sebelum skrip gagal tetap sampai ke macro
+        let mut variabel = HashMap::new();
+        let hasil = jalankan_main("set_var(\"a\", 1); throw \"gagal\";", &mut variabel);
+        assert!(hasil.unwrap_err().contains("gagal"));
+        assert_eq!(variabel["a"], "1");
+    }
+
+    #[test]
+    fn batas_operasi() {
+        let _kunci = kunci();
+        pengaturan::atur_sementara(|pengaturan| pengaturan.skrip.maks_operasi = 1000);
+        let hasil = jalankan_main("let i = 0; loop { i += 1; }", &mut HashMap::new());
+        pengaturan::atur_sementara(|pengaturan| pengaturan.skrip = pengaturan::PengaturanSkrip::default());
+        assert!(hasil.unwrap_err().contains("operations"));
+
+        // Playback yang dihentikan juga menghentikan skrip
+        unsafe {
+            APAKAH_LAGI_MAIN = Some(false);
+        }
+        assert_eq!(jalankan("loop { }", &mut HashMap::new()), Err(String::from("Playback dihentikan")));
+    }
+}
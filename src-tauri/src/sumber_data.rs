@@ -0,0 +1,194 @@
+// Playback berbasis data: macro dimainkan sekali untuk setiap baris CSV, setiap kolom menjadi variabel {nama_kolom}.
+// Hasil setiap baris (Sukses, Gagal atau Dihentikan) ditulis ke CSV hasil begitu baris selesai, jadi tetap ada kalau playback dihentikan di tengah.
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::variabel;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpsiData {
+    pub file: String,
+    // Default <nama file>-hasil.csv di folder yang sama
+    #[serde(default)]
+    pub hasil: Option<String>,
+    // Isi CSV yang sudah dibaca, supaya file-nya hanya dibaca sekali dari pemeriksaan sampai playback selesai
+    #[serde(skip)]
+    tabel: OnceCell<TabelData>
+}
+
+impl OpsiData {
+    pub fn baru(file: String, hasil: Option<String>) -> OpsiData {
+        OpsiData { file, hasil, tabel: OnceCell::new() }
+    }
+
+    pub fn tabel(&self) -> Result<&TabelData, String> {
+        self.tabel.get_or_try_init(|| baca(&self.file))
+    }
+
+    // File hasil dikosongkan waktu playback dimulai, jadi tidak boleh sama dengan file data
+    pub fn periksa(&self) -> Result<(), String> {
+        self.tabel()?;
+        let path_data = fs::canonicalize(&self.file).map_err(|e| format!("{}: {}", self.file, e))?;
+        let path_hasil = self.path_hasil();
+        if fs::canonicalize(&path_hasil).is_ok_and(|path_hasil| path_hasil == path_data) {
+            return Err(format!("File hasil {} sama dengan file data", path_hasil.display()));
+        }
+
+        Ok(())
+    }
+
+    pub fn path_hasil(&self) -> PathBuf {
+        match &self.hasil {
+            Some(hasil) => PathBuf::from(hasil),
+            None => {
+                let path = Path::new(&self.file);
+                let nama = path.file_stem().map(|nama| nama.to_string_lossy().into_owned()).unwrap_or_default();
+                path.with_file_name(format!("{}-hasil.csv", nama))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TabelData {
+    pub kolom: Vec<String>,
+    pub baris: Vec<Vec<String>>
+}
+
+impl TabelData {
+    // Nilai variabel untuk baris ke-index
+    pub fn variabel(&self, index: usize) -> impl Iterator<Item = (String, String)> + '_ {
+        self.kolom.iter().cloned().zip(self.baris[index].iter().cloned())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum StatusBaris {
+    Sukses,
+    Gagal,
+    Dihentikan
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HasilBaris {
+    // Mulai dari 1, tanpa baris header
+    pub baris: usize,
+    pub status: StatusBaris,
+    pub pesan: Option<String>,
+    pub durasi: f32
+}
+
+// Baris pertama adalah nama kolom, harus bisa dipakai sebagai nama variabel
+pub fn baca(path: &str) -> Result<TabelData, String> {
+    let mut reader = csv::ReaderBuilder::new().flexible(false).from_path(path).map_err(|e| format!("{}: {}", path, e))?;
+
+    let kolom: Vec<String> = reader.headers().map_err(|e| format!("{}: {}", path, e))?.iter().map(|nama| nama.trim().to_string()).collect();
+    if let Some(nama) = kolom.iter().find(|nama| !variabel::apakah_nama_valid(nama)) {
+        return Err(format!("{}: nama kolom \"{}\" hanya boleh berisi huruf, angka dan _", path, nama));
+    }
+
+    let mut baris = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("{}: {}", path, e))?;
+        baris.push(record.iter().map(String::from).collect());
+    }
+    if baris.is_empty() {
+        return Err(format!("{} tidak berisi baris data", path));
+    }
+
+    Ok(TabelData { kolom, baris })
+}
+
+pub struct PenulisHasil {
+    writer: csv::Writer<File>
+}
+
+impl PenulisHasil {
+    // Kolom data ditulis lagi di depan supaya baris yang gagal mudah dicari dan diulang
+    pub fn buat(path: &Path, tabel: &TabelData) -> Result<Self, String> {
+        let mut writer = csv::Writer::from_path(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut header = tabel.kolom.clone();
+        header.extend(["status", "pesan", "durasi"].map(String::from));
+        writer.write_record(&header).and_then(|_| Ok(writer.flush()?)).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        Ok(PenulisHasil { writer })
+    }
+
+    pub fn tulis(&mut self, tabel: &TabelData, hasil: &HasilBaris) -> Result<(), String> {
+        let mut record = tabel.baris[hasil.baris - 1].clone();
+        record.push(format!("{:?}", hasil.status));
+        record.push(hasil.pesan.clone().unwrap_or_default());
+        record.push(format!("{:.3}", hasil.durasi));
+
+        self.writer.write_record(&record).and_then(|_| Ok(self.writer.flush()?)).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hasil_tidak_boleh_file_data() {
+        let folder = std::env::temp_dir().join(format!("recordflow-sumber-data-{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("data.csv");
+        fs::write(&path, "nama,umur\nbudi,30\n").unwrap();
+        let file = path.to_string_lossy().into_owned();
+
+        assert!(OpsiData::baru(file.clone(), None).periksa().is_ok());
+        // Ditulis lewat path lain yang menunjuk ke file yang sama
+        let sama = folder.join(".").join("data.csv").to_string_lossy().into_owned();
+        let hasil = OpsiData::baru(file.clone(), Some(sama)).periksa();
+        assert!(hasil.is_err_and(|e| e.contains("sama dengan file data")));
+
+        let opsi = OpsiData::baru(file, None);
+        let tabel = opsi.tabel().unwrap();
+        assert_eq!(tabel.kolom, ["nama", "umur"]);
+        assert_eq!(tabel.variabel(0).collect::<Vec<_>>(), [(String::from("nama"), String::from("budi")), (String::from("umur"), String::from("30"))]);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn csv_tidak_valid() {
+        let folder = std::env::temp_dir().join(format!("recordflow-sumber-data-salah-{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let baca_isi = |nama: &str, isi: &str| {
+            let path = folder.join(nama);
+            fs::write(&path, isi).unwrap();
+            baca(&path.to_string_lossy())
+        };
+
+        // Spasi di sekitar nama kolom dibuang
+        let tabel = baca_isi("baik.csv", " nama ,kota_2\nbudi,\"Bandung, Jawa Barat\"\nsiti,\n").unwrap();
+        assert_eq!(tabel.kolom, ["nama", "kota_2"]);
+        assert_eq!(tabel.baris, [vec![String::from("budi"), String::from("Bandung, Jawa Barat")], vec![String::from("siti"), String::new()]]);
+
+        assert!(baca_isi("header.csv", "nama,nama lengkap\nbudi,Budi S\n").unwrap_err().contains("nama kolom \"nama lengkap\""));
+        assert!(baca_isi("header-kosong.csv", "nama,\nbudi,1\n").unwrap_err().contains("nama kolom \"\""));
+        assert!(baca_isi("tidak-rata.csv", "nama,kota\nbudi,Bandung\nsiti\n").is_err());
+        assert!(baca_isi("tanpa-data.csv", "nama,kota\n").unwrap_err().ends_with("tidak berisi baris data"));
+        assert!(baca(&folder.join("tidak-ada.csv").to_string_lossy()).is_err());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn hasil_ditulis_per_baris() {
+        let folder = std::env::temp_dir().join(format!("recordflow-sumber-data-hasil-{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("hasil.csv");
+        let tabel = TabelData { kolom: vec![String::from("nama")], baris: vec![vec![String::from("budi")], vec![String::from("siti")]] };
+
+        let mut penulis = PenulisHasil::buat(&path, &tabel).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nama,status,pesan,durasi\n");
+        penulis.tulis(&tabel, &HasilBaris { baris: 2, status: StatusBaris::Gagal, pesan: Some(String::from("Clipboard kosong, coba lagi")), durasi: 1.23456 }).unwrap();
+        // Sudah ada di file sebelum penulisnya ditutup
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nama,status,pesan,durasi\nsiti,Gagal,\"Clipboard kosong, coba lagi\",1.235\n");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,103 @@
+// Daftar file macro yang terakhir dibuka atau disimpan, paling baru di depan. Disimpan di terbaru.json di folder config.
+use std::path::Path;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+
+use crate::pengaturan;
+
+const NAMA_FILE: &str = "terbaru.json";
+pub const MAKS_TERBARU: usize = 10;
+
+static DAFTAR_TERBARU: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(pengaturan::muat_json(NAMA_FILE)));
+
+fn simpan(daftar: &[String]) {
+    if let Err(e) = pengaturan::simpan_json(NAMA_FILE, &daftar) {
+        println!("Daftar file terbaru tidak bisa disimpan: {}", e);
+    }
+}
+
+// true kalau ada yang dibuang
+fn buang_yang_hilang(daftar: &mut Vec<String>) -> bool {
+    let jumlah = daftar.len();
+    daftar.retain(|path| Path::new(path).is_file());
+    daftar.len() != jumlah
+}
+
+// Path yang sama dipindah ke depan, yang paling lama dibuang kalau lebih dari MAKS_TERBARU
+fn tambahkan(daftar: &mut Vec<String>, path: String) {
+    daftar.retain(|lama| *lama != path);
+    daftar.insert(0, path);
+    daftar.truncate(MAKS_TERBARU);
+}
+
+// File yang sudah tidak ada langsung dibuang dari daftar
+pub fn daftar() -> Vec<String> {
+    let mut daftar = DAFTAR_TERBARU.lock().unwrap();
+    if buang_yang_hilang(&mut daftar) {
+        simpan(&daftar);
+    }
+
+    daftar.clone()
+}
+
+// Tanpa membuang file yang hilang, supaya index-nya tetap sama dengan item menu yang diklik
+pub fn ambil(index: usize) -> Option<String> {
+    DAFTAR_TERBARU.lock().unwrap().get(index).cloned()
+}
+
+pub fn terakhir() -> Option<String> {
+    daftar().into_iter().next()
+}
+
+pub fn catat(path: &Path) {
+    // Path absolut supaya file yang sama tidak tercatat dua kali
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf()).to_string_lossy().into_owned();
+
+    let mut daftar = DAFTAR_TERBARU.lock().unwrap();
+    tambahkan(&mut daftar, path);
+    simpan(&daftar);
+}
+
+pub fn kosongkan() {
+    let mut daftar = DAFTAR_TERBARU.lock().unwrap();
+    daftar.clear();
+    simpan(&daftar);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::*;
+
+    #[test]
+    fn paling_baru_di_depan() {
+        let mut daftar: Vec<String> = Vec::new();
+        for nomor in 0..12 {
+            tambahkan(&mut daftar, format!("/macro/{}.rf", nomor));
+        }
+        assert_eq!(daftar.len(), MAKS_TERBARU);
+        assert_eq!(daftar.first().map(String::as_str), Some("/macro/11.rf"));
+        assert_eq!(daftar.last().map(String::as_str), Some("/macro/2.rf"));
+
+        // File yang dibuka lagi pindah ke depan tanpa dobel
+        tambahkan(&mut daftar, String::from("/macro/5.rf"));
+        assert_eq!(daftar.len(), MAKS_TERBARU);
+        assert_eq!(&daftar[..3], ["/macro/5.rf", "/macro/11.rf", "/macro/10.rf"]);
+        assert_eq!(daftar.iter().filter(|path| *path == "/macro/5.rf").count(), 1);
+    }
+
+    #[test]
+    fn file_hilang_dibuang() {
+        let folder = std::env::temp_dir().join(format!("recordflow-terbaru-{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let ada = folder.join("ada.rf").to_string_lossy().into_owned();
+        fs::write(&ada, "").unwrap();
+
+        let mut daftar = vec![folder.join("hilang.rf").to_string_lossy().into_owned(), ada.clone(), folder.to_string_lossy().into_owned()];
+        assert!(buang_yang_hilang(&mut daftar));
+        assert_eq!(daftar, vec![ada]);
+        assert!(!buang_yang_hilang(&mut daftar));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+// Variabel macro dideklarasikan di header dengan baris "Var|||nama" atau "Var|||nama=bawaan" sebelum event pertama.
+// Nilainya diisi waktu playback (opsi.variabel, --var di CLI atau form dari event MintaVariabel) lalu dipakai lewat {nama} di aksi Type dan Set.
+// Selain itu ada {date}, {time}, {iteration} dan {random:1-100}. Tulis "{{" atau "}}" untuk kurung kurawal biasa.
+use std::collections::HashMap;
+use chrono::Local;
+use rand::Rng;
+use serde::Serialize;
+
+use crate::event::{Aksi, DataKey};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Deklarasi {
+    pub nama: String,
+    pub bawaan: Option<String>
+}
+
+pub fn apakah_nama_valid(nama: &str) -> bool {
+    !nama.is_empty() && nama.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// "nama=nilai" dari value baris Var, dari --var di CLI atau dari form
+pub fn parse_pasangan(teks: &str) -> Result<(String, String), String> {
+    match teks.split_once('=') {
+        Some((nama, nilai)) if apakah_nama_valid(nama.trim()) => Ok((nama.trim().to_string(), nilai.to_string())),
+        _ => Err(format!("{} harus berbentuk <nama>=<nilai>", teks))
+    }
+}
+
+// Banyaknya baris Var di awal macro
+pub fn panjang_header(data_macro: &[DataKey]) -> usize {
+    data_macro.iter().take_while(|val| matches!(val.aksi, Aksi::Variabel { .. })).count()
+}
+
+pub fn deklarasi(data_macro: &[DataKey]) -> Vec<Deklarasi> {
+    data_macro[..panjang_header(data_macro)].iter().filter_map(|val| match &val.aksi {
+        Aksi::Variabel { nama, bawaan } => Some(Deklarasi { nama: nama.clone(), bawaan: bawaan.clone() }),
+        _ => None
+    }).collect()
+}
+
+// Variabel yang belum punya nilai dari pemanggil maupun nilai bawaan
+pub fn belum_diisi(data_macro: &[DataKey], nilai: &HashMap<String, String>) -> Vec<Deklarasi> {
+    deklarasi(data_macro).into_iter().filter(|val| val.bawaan.is_none() && !nilai.contains_key(&val.nama)).collect()
+}
+
+pub fn pesan_belum_diisi(kurang: &[Deklarasi]) -> String {
+    format!("Variabel {} belum diisi", kurang.iter().map(|val| val.nama.as_str()).collect::<Vec<&str>>().join(", "))
+}
+
+// Nilai bawaan dari header ditimpa nilai dari pemanggil
+pub fn nilai_awal(data_macro: &[DataKey], nilai: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut hasil: HashMap<String, String> = deklarasi(data_macro).into_iter()
+        .filter_map(|val| Some((val.nama, val.bawaan?)))
+        .collect();
+    hasil.extend(nilai.iter().map(|(nama, nilai)| (nama.clone(), nilai.clone())));
+    hasil
+}
+
+fn parse_acak(teks: &str) -> Result<(i64, i64), String> {
+    let angka = |val: &str| val.trim().parse::<i64>().map_err(|_| format!("random:{} harus berbentuk random:<min>-<maks>", teks));
+    // Tanda minus di depan angka pertama bukan pemisah
+    let pemisah = teks.char_indices().skip(1).find(|(_, c)| *c == '-').map(|(posisi, _)| posisi)
+        .ok_or(format!("random:{} harus berbentuk random:<min>-<maks>", teks))?;
+    let (min, maks) = (angka(&teks[..pemisah])?, angka(&teks[pemisah + 1..])?);
+    if min > maks {
+        return Err(format!("random:{} terbalik", teks));
+    }
+
+    Ok((min, maks))
+}
+
+// Memanggil ganti untuk setiap {isi} dan menyalin teks lainnya
+fn ganti_semua(teks: &str, mut ganti: impl FnMut(&str) -> Result<String, String>) -> Result<String, String> {
+    let mut hasil = String::new();
+    let mut sisa = teks;
+    while let Some(posisi) = sisa.find(['{', '}']) {
+        hasil.push_str(&sisa[..posisi]);
+        let tanda = &sisa[posisi..posisi + 1];
+        sisa = &sisa[posisi + 1..];
+
+        if let Some(setelah) = sisa.strip_prefix(tanda) {
+            hasil.push_str(tanda);
+            sisa = setelah;
+            continue
+        }
+        if tanda == "}" {
+            return Err(format!("\"}}\" tanpa pasangan di {}", teks));
+        }
+
+        let tutup = sisa.find('}').ok_or(format!("\"{{\" tanpa pasangan di {}", teks))?;
+        hasil.push_str(&ganti(sisa[..tutup].trim())?);
+        sisa = &sisa[tutup + 1..];
+    }
+    hasil.push_str(sisa);
+
+    Ok(hasil)
+}
+
+// Memeriksa penulisan {…} tanpa mengisinya, dipakai waktu baris dibaca
+pub fn periksa(teks: &str) -> Result<(), String> {
+    ganti_semua(teks, |nama| {
+        match nama.split_once(':') {
+            Some(("random", rentang)) => parse_acak(rentang).map(|_| String::new()),
+            Some(..) => Err(format!("{{{}}} tidak dikenal", nama)),
+            None if apakah_nama_valid(nama) => Ok(String::new()),
+            None => Err(format!("Nama variabel {{{}}} tidak valid", nama))
+        }
+    }).map(|_| ())
+}
+
+// iterasi adalah putaran Repeat terdalam, atau putaran playback kalau di luar Repeat
+pub fn isi(teks: &str, variabel: &HashMap<String, String>, iterasi: u32) -> Result<String, String> {
+    ganti_semua(teks, |nama| {
+        if let Some(nilai) = variabel.get(nama) {
+            return Ok(nilai.clone());
+        }
+
+        match nama.split_once(':') {
+            None if nama == "date" => Ok(Local::now().format("%Y-%m-%d").to_string()),
+            None if nama == "time" => Ok(Local::now().format("%H:%M:%S").to_string()),
+            None if nama == "iteration" => Ok(iterasi.to_string()),
+            Some(("random", rentang)) => {
+                let (min, maks) = parse_acak(rentang)?;
+                Ok(rand::thread_rng().gen_range(min..=maks).to_string())
+            },
+            _ => Err(format!("Variabel {} belum diisi", nama))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format;
+
+    fn variabel(pasangan: &[(&str, &str)]) -> HashMap<String, String> {
+        pasangan.iter().map(|(nama, nilai)| (nama.to_string(), nilai.to_string())).collect()
+    }
+
+    #[test]
+    fn isi_variabel_dan_bawaan() {
+        let nilai = variabel(&[("nama", "Budi"), ("kota", "Bandung")]);
+        assert_eq!(isi("Halo {nama} dari { kota }", &nilai, 1).unwrap(), "Halo Budi dari Bandung");
+        assert_eq!(isi("Putaran {iteration}", &nilai, 3).unwrap(), "Putaran 3");
+        // Variabel dengan nama yang sama menimpa nama bawaan
+        assert_eq!(isi("{iteration}", &variabel(&[("iteration", "x")]), 3).unwrap(), "x");
+        assert_eq!(isi("tanpa kurung", &nilai, 1).unwrap(), "tanpa kurung");
+
+        let tanggal = isi("{date} {time}", &nilai, 1).unwrap();
+        let (date, time) = tanggal.split_once(' ').unwrap();
+        assert!(chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok());
+        assert!(chrono::NaiveTime::parse_from_str(time, "%H:%M:%S").is_ok());
+    }
+
+    #[test]
+    fn isi_kurung_ganda() {
+        let nilai = variabel(&[("nama", "Budi")]);
+        assert_eq!(isi("{{nama}} = {nama}", &nilai, 1).unwrap(), "{nama} = Budi");
+        assert_eq!(isi("}}{{", &nilai, 1).unwrap(), "}{");
+        // Nilai variabel tidak diisi ulang
+        assert_eq!(isi("{a}", &variabel(&[("a", "{b}")]), 1).unwrap(), "{b}");
+    }
+
+    #[test]
+    fn isi_acak_dalam_rentang() {
+        for _ in 0..200 {
+            let angka: i64 = isi("{random:-5-5}", &HashMap::new(), 1).unwrap().parse().unwrap();
+            assert!((-5..=5).contains(&angka));
+        }
+        assert_eq!(isi("{random:7-7}", &HashMap::new(), 1).unwrap(), "7");
+    }
+
+    #[test]
+    fn isi_gagal() {
+        let nilai = variabel(&[("nama", "Budi")]);
+        assert_eq!(isi("Halo {kota}", &nilai, 1).unwrap_err(), "Variabel kota belum diisi");
+        assert!(isi("{random:9-1}", &nilai, 1).is_err());
+        assert!(isi("{nama", &nilai, 1).is_err());
+        assert!(isi("nama}", &nilai, 1).is_err());
+    }
+
+    #[test]
+    fn periksa_penulisan() {
+        assert!(periksa("Halo {nama}, {{bukan variabel}} {random:1-100} {iteration}").is_ok());
+        assert!(periksa("{random:-10--1}").is_ok());
+        assert_eq!(periksa("{nama").unwrap_err(), "\"{\" tanpa pasangan di {nama");
+        assert_eq!(periksa("nama}").unwrap_err(), "\"}\" tanpa pasangan di nama}");
+        assert_eq!(periksa("{nama lengkap}").unwrap_err(), "Nama variabel {nama lengkap} tidak valid");
+        assert_eq!(periksa("{env:HOME}").unwrap_err(), "{env:HOME} tidak dikenal");
+        assert!(periksa("{random:1}").is_err());
+        assert!(periksa("{random:10-1}").is_err());
+        assert!(periksa("{}").is_err());
+    }
+
+    #[test]
+    fn ganti_semua_memanggil_per_kurung() {
+        let mut dipanggil = Vec::new();
+        let hasil = ganti_semua("a{x}b{ y }c{{d}}", |nama| {
+            dipanggil.push(nama.to_string());
+            Ok(nama.to_uppercase())
+        }).unwrap();
+        assert_eq!(hasil, "aXbYc{d}");
+        assert_eq!(dipanggil, ["x", "y"]);
+    }
+
+    #[test]
+    fn parse_pasangan_nama_nilai() {
+        assert_eq!(parse_pasangan("kota=Bandung Barat").unwrap(), (String::from("kota"), String::from("Bandung Barat")));
+        assert_eq!(parse_pasangan("rumus=a=b").unwrap(), (String::from("rumus"), String::from("a=b")));
+        assert_eq!(parse_pasangan("kosong=").unwrap(), (String::from("kosong"), String::new()));
+        assert!(parse_pasangan("tanpa nilai").is_err());
+        assert!(parse_pasangan("nama lengkap=x").is_err());
+    }
+
+    #[test]
+    fn header_dan_nilai_awal() {
+        let data_macro = format::parse_macro("Var|||nama|||0\nVar|||kota=Bandung|||0\nType|||{nama} {kota}|||0.5");
+        assert_eq!(panjang_header(&data_macro), 2);
+        let deklarasi: Vec<(String, Option<String>)> = deklarasi(&data_macro).into_iter().map(|val| (val.nama, val.bawaan)).collect();
+        assert_eq!(deklarasi, [(String::from("nama"), None), (String::from("kota"), Some(String::from("Bandung")))]);
+
+        let kurang: Vec<String> = belum_diisi(&data_macro, &HashMap::new()).into_iter().map(|val| val.nama).collect();
+        assert_eq!(kurang, ["nama"]);
+        assert!(belum_diisi(&data_macro, &variabel(&[("nama", "Budi")])).is_empty());
+
+        let awal = nilai_awal(&data_macro, &variabel(&[("nama", "Budi"), ("kota", "Jakarta")]));
+        assert_eq!(awal, variabel(&[("nama", "Budi"), ("kota", "Jakarta")]));
+        assert_eq!(nilai_awal(&data_macro, &HashMap::new()), variabel(&[("kota", "Bandung")]));
+    }
+}
@@ -0,0 +1,190 @@
+// Server WebSocket di localhost yang menyiarkan setiap input yang direkam (payload KirimDataInput) ke dashboard luar.
+// Klien terhubung ke ws://127.0.0.1:<port>/?token=<token>&tipe=Key,Button Press
+// dan bisa mengganti filter kapan saja dengan mengirim {"tipe": ["Mouse Wheel"]}. Filter kosong berarti semua event.
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::TryRecvError;
+use std::thread;
+use std::time::Duration;
+use serde::Deserialize;
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::{Error, Message};
+
+use crate::pengaturan::PengaturanWebsocket;
+use crate::siaran;
+
+const EVENT_INPUT: &str = "KirimDataInput";
+
+#[derive(Debug, Deserialize)]
+struct PesanFilter {
+    tipe: Vec<String>
+}
+
+fn decode_query(teks: &str) -> String {
+    let bytes = teks.as_bytes();
+    let mut hasil: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => hasil.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let byte = std::str::from_utf8(&bytes[index + 1..index + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match byte {
+                    Some(byte) => {
+                        hasil.push(byte);
+                        index += 2;
+                    },
+                    None => hasil.push(b'%')
+                }
+            },
+            byte => hasil.push(byte)
+        }
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&hasil).into_owned()
+}
+
+fn nilai_query(request: &Request, nama: &str) -> Option<String> {
+    request.uri().query()?.split('&').find_map(|pasangan| {
+        let (kunci, nilai) = pasangan.split_once('=').unwrap_or((pasangan, ""));
+        if kunci == nama { Some(decode_query(nilai)) } else { None }
+    })
+}
+
+// Perbandingan yang lamanya tidak tergantung di mana karakter pertama yang beda
+fn token_sama(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |beda, (x, y)| beda | (x ^ y)) == 0
+}
+
+// "Key" cocok dengan "Key Press" dan "Key Release", "Button Press Left" hanya cocok dengan klik kiri
+fn cocok_filter(filter: &[String], tipe: &str) -> bool {
+    filter.is_empty() || filter.iter().any(|awalan| tipe.starts_with(awalan.as_str()))
+}
+
+fn pisah_filter(teks: &str) -> Vec<String> {
+    teks.split(',').map(|tipe| tipe.trim().to_string()).filter(|tipe| !tipe.is_empty()).collect()
+}
+
+// ErrorResponse dari tungstenite memang besar, bentuk closure-nya ditentukan oleh accept_hdr
+#[allow(clippy::result_large_err)]
+fn layani_klien(stream: TcpStream, token: String) {
+    let mut filter: Vec<String> = Vec::new();
+    let cek_token = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+        match nilai_query(request, "token") {
+            Some(token_klien) if token_sama(&token_klien, &token) => {
+                if let Some(tipe) = nilai_query(request, "tipe") {
+                    filter = pisah_filter(&tipe);
+                }
+                Ok(response)
+            },
+            _ => {
+                let mut error = ErrorResponse::new(Some(String::from("Token tidak valid")));
+                *error.status_mut() = StatusCode::UNAUTHORIZED;
+                Err(error)
+            }
+        }
+    };
+
+    let mut websocket = match tungstenite::accept_hdr(stream, cek_token) {
+        Ok(websocket) => websocket,
+        Err(..) => return
+    };
+    // Baca dengan timeout supaya thread yang sama bisa bergantian membaca filter dan mengirim event
+    if websocket.get_ref().set_read_timeout(Some(Duration::from_millis(50))).is_err() {
+        return;
+    }
+
+    let penerima = siaran::langganan();
+    loop {
+        match websocket.read() {
+            Ok(Message::Text(teks)) => {
+                if let Ok(pesan) = serde_json::from_str::<PesanFilter>(&teks) {
+                    filter = pesan.tipe;
+                }
+            },
+            Ok(Message::Close(..)) | Err(Error::ConnectionClosed) | Err(Error::AlreadyClosed) => break,
+            Ok(..) => {},
+            Err(Error::Io(e)) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {},
+            Err(..) => break
+        }
+
+        loop {
+            let pesan = match penerima.try_recv() {
+                Ok(pesan) => pesan,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return
+            };
+            if pesan.nama != EVENT_INPUT {
+                continue
+            }
+
+            let tipe = pesan.payload.get("tipe").and_then(|tipe| tipe.as_str()).unwrap_or("");
+            if cocok_filter(&filter, tipe) && websocket.send(Message::Text(pesan.payload.to_string())).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// Hanya bind ke 127.0.0.1, dashboard dari komputer lain tidak bisa terhubung
+pub fn jalankan(pengaturan: &PengaturanWebsocket) -> Result<(), String> {
+    if pengaturan.token.is_empty() {
+        return Err(String::from("Token websocket belum diatur"));
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", pengaturan.port)).map_err(|e| format!("Port {}: {}", pengaturan.port, e))?;
+    let token = pengaturan.token.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let token = token.clone();
+            thread::spawn(move || layani_klien(stream, token));
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str) -> Request {
+        Request::builder().uri(uri).body(()).unwrap()
+    }
+
+    #[test]
+    fn decode_nilai_query() {
+        assert_eq!(decode_query("Button+Press%20Left"), "Button Press Left");
+        assert_eq!(decode_query("a%2Cb%25"), "a,b%");
+        // Escape yang tidak lengkap atau bukan hex dibiarkan
+        assert_eq!(decode_query("100%"), "100%");
+        assert_eq!(decode_query("%zz%4"), "%zz%4");
+
+        let request = request("/?token=ab%2Bcd&tipe=Key,Button+Press&kosong");
+        assert_eq!(nilai_query(&request, "token").as_deref(), Some("ab+cd"));
+        assert_eq!(nilai_query(&request, "tipe").as_deref(), Some("Key,Button Press"));
+        assert_eq!(nilai_query(&request, "kosong").as_deref(), Some(""));
+        assert_eq!(nilai_query(&request, "tok"), None);
+        assert_eq!(nilai_query(&self::request("/"), "token"), None);
+    }
+
+    #[test]
+    fn token_dan_filter() {
+        assert!(token_sama("abc123", "abc123"));
+        assert!(!token_sama("abc123", "abc124"));
+        assert!(!token_sama("abc", "abc123"));
+        assert!(!token_sama("", "abc"));
+
+        let filter = pisah_filter(" Key , Button Press Left,,");
+        assert_eq!(filter, vec![String::from("Key"), String::from("Button Press Left")]);
+        assert!(cocok_filter(&filter, "Key Press"));
+        assert!(cocok_filter(&filter, "Key Release"));
+        assert!(cocok_filter(&filter, "Button Press Left"));
+        assert!(!cocok_filter(&filter, "Button Press Right"));
+        assert!(!cocok_filter(&filter, "Mouse Move"));
+        // Filter kosong berarti semua event
+        assert!(cocok_filter(&[], "Mouse Wheel"));
+    }
+}
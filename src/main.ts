@@ -46,6 +46,35 @@ async function GantiStatusMainRecorder(status: boolean) {
 	}
 }
 
+type Deklarasi = { nama: string, bawaan: string | null };
+type KesalahanAksi = { index: number, pesan: string };
+
+// Nilai variabel ditanyakan satu per satu, null kalau dibatalkan
+function TanyaVariabel(daftar: Deklarasi[]): Record<string, string> | null {
+	const variabel: Record<string, string> = {};
+	for(const deklarasi of daftar) {
+		const nilai = prompt(`Nilai variabel ${deklarasi.nama}`, deklarasi.bawaan ?? "");
+		if(nilai === null) {
+			return null;
+		}
+		variabel[deklarasi.nama] = nilai;
+	}
+
+	return variabel;
+}
+
+// Error dari mainkan_recorder berarti playback tidak jadi dimulai, tombolnya dikembalikan
+async function MainkanRecorder(variabel: Record<string, string>) {
+	GantiStatusMainRecorder(true);
+	await appWindow.minimize();
+	try {
+		await invoke("mainkan_recorder", { opsi: { variabel } });
+	} catch(e) {
+		await GantiStatusMainRecorder(false);
+		alert(`Playback tidak bisa dimulai: ${e}`);
+	}
+}
+
 console.log("mulai");
 await listen('KirimDataInput', (event: Event<{ tipe: string, value: string, waktu: number }>) => {
 	if(!ApakahLagiMain) {
@@ -71,6 +100,34 @@ await listen("SelesaiRecording", (event: Event<boolean>) => {
 	GantiStatusMainRecorder(!event.payload);
 });
 
+// Dikirim kalau F7 ditekan untuk macro yang punya variabel tanpa nilai bawaan
+await listen("MintaVariabel", async (event: Event<Deklarasi[]>) => {
+	await appWindow.show();
+	const variabel = TanyaVariabel(event.payload);
+	if(variabel) {
+		await MainkanRecorder(variabel);
+	}
+});
+
+await listen("AksiGagal", async (event: Event<KesalahanAksi>) => {
+	await appWindow.show();
+	alert(`Event ${event.payload.index} gagal: ${event.payload.pesan}`);
+});
+
+await listen("SkripGagal", async (event: Event<KesalahanAksi>) => {
+	await appWindow.show();
+	alert(`Skrip di event ${event.payload.index} gagal: ${event.payload.pesan}`);
+});
+
+await listen("DataPlaybackGagal", async (event: Event<string>) => {
+	await appWindow.show();
+	alert(`Data playback gagal: ${event.payload}`);
+});
+
+await listen("LayarCocok", (event: Event<{ index: number, x: number, y: number, keyakinan: number }>) => {
+	console.log("LayarCocok", event.payload);
+});
+
 await listen("DapatinSimpananFile", async (event: Event<boolean>) => {
 	if(event.payload) {
 		const savePath = await save({
@@ -114,8 +171,12 @@ TombolMainRecord.onclick = async (e) => {
 		return;
 	}
 
-	GantiStatusMainRecorder(true);
+	// Variabel yang punya nilai bawaan tidak perlu ditanyakan
+	const daftar = await invoke<Deklarasi[]>("variabel_macro");
+	const variabel = TanyaVariabel(daftar.filter((deklarasi) => deklarasi.bawaan === null));
+	if(!variabel) {
+		return;
+	}
 
-	await appWindow.minimize();
-	await invoke("mainkan_recorder");
+	await MainkanRecorder(variabel);
 }
\ No newline at end of file